@@ -3,14 +3,20 @@
 //! This command performs checks on the relationship between a bulloak tree and
 //! a Solidity file.
 
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use bulloak_foundry::{
     check::{
         context::{fix_order, Context},
         rules::{self, Checker},
     },
-    sol::find_contract,
+    config::{ActionRule, Config},
+    constants::{
+        DEFAULT_LICENSE, DEFAULT_SOL_VERSION, DEFAULT_SPLIT_CONTRACT_NAME,
+    },
     violation::{Violation, ViolationKind},
 };
 use bulloak_syntax::utils::pluralize;
@@ -18,11 +24,13 @@ use clap::Parser;
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 
-use crate::{cli::Cli, glob::expand_glob};
+use crate::{diff, glob::expand_glob};
 
 /// Check that the tests match the spec.
 #[doc(hidden)]
 #[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+// Each bool is a command line flag.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Check {
     /// The set of tree files to use as spec.
     ///
@@ -35,9 +43,133 @@ pub struct Check {
     /// to standard output instead of writing to files.
     #[arg(long, requires = "fix-violations", default_value_t = false)]
     pub stdout: bool,
+    /// When `--fix` is passed, use `--diff` to print a unified diff of the
+    /// fixes instead of writing them to files.
+    ///
+    /// Exits with a non-zero code if any file would change.
+    #[arg(
+        long,
+        visible_alias = "dry-run",
+        requires = "fix-violations",
+        conflicts_with = "stdout",
+        default_value_t = false
+    )]
+    pub diff: bool,
+    /// Whether to report functions and modifiers that are named like
+    /// generated code, but are not part of the tree, e.g. the tests of a
+    /// branch that was removed from it.
+    #[arg(long, default_value_t = false)]
+    pub orphans: bool,
+    /// Whether to report a missing function or modifier that most likely was
+    /// renamed, e.g. after a condition was reworded, as such.
+    ///
+    /// `--fix` then renames the existing one and keeps its body, instead of
+    /// adding an empty one.
+    #[arg(long, default_value_t = false)]
+    pub renames: bool,
+    /// Whether to skip checking that the comments of the actions in the
+    /// tests match the actions of the tree.
+    #[arg(long, default_value_t = false)]
+    pub skip_comments: bool,
+    /// When `--fix` is passed, use `--prune` to delete orphaned functions and
+    /// modifiers, or `--prune=comment` to comment them out.
+    ///
+    /// Implies `--orphans`.
+    #[arg(
+        long,
+        value_name = "MODE",
+        requires = "fix-violations",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "delete",
+        value_parser = ["delete", "comment"]
+    )]
+    pub prune: Option<String>,
+    /// Sets the Solidity version of the files created by `--fix`.
+    ///
+    /// Accepts any version requirement supported by Solidity's `pragma`
+    /// directive, e.g. `'>=0.8.22 <0.9.0'`.
+    #[arg(short = 's', long, default_value = DEFAULT_SOL_VERSION)]
+    pub solidity_version: String,
+    /// Sets the SPDX license identifier of the files created by `--fix`.
+    #[arg(long, default_value = DEFAULT_LICENSE)]
+    pub license: String,
+    /// Whether to add vm.skip(true) at the beginning of the tests added by
+    /// `--fix`.
+    #[arg(short = 'S', long = "vm-skip", default_value_t = false)]
+    pub vm_skip: bool,
+    /// Whether to add `vm.expectEmit()` skeletons to the tests added by
+    /// `--fix` for actions that emit events.
+    #[arg(short = 'E', long = "expect-emit", default_value_t = false)]
+    pub expect_emit: bool,
     /// Whether to emit modifiers.
     #[arg(short = 'm', long, default_value_t = false)]
     pub skip_modifiers: bool,
+    /// Adds a rule that inserts a Solidity snippet in the tests added by
+    /// `--fix` whose actions match a pattern, e.g. `--action-rule '^it should
+    /// transfer (\w+)=>token.transfer(bob, $1);'`.
+    #[arg(long = "action-rule", value_name = "PATTERN=>SNIPPET")]
+    pub action_rules: Vec<ActionRule>,
+    /// A contract the test contracts inherit from, e.g. `--base Base_Test`.
+    ///
+    /// Can be passed multiple times. Replaces forge-std's `Test`, which
+    /// is otherwise inherited when needed.
+    #[arg(long = "base", value_name = "CONTRACT")]
+    pub base_contracts: Vec<String>,
+    /// Adds an import directive to the test files, e.g. `--import
+    /// 'Base_Test=test/Base.t.sol'`.
+    ///
+    /// Several symbols can be imported from the same path by separating
+    /// them with commas. Without symbols, the whole file is imported.
+    #[arg(long = "import", value_name = "[SYMBOLS=]PATH")]
+    pub imports: Vec<String>,
+    /// Whether to add a `setUp` function to the test contracts.
+    ///
+    /// When `--base` is passed, it calls `super.setUp()`.
+    #[arg(long = "set-up", default_value_t = false)]
+    pub set_up: bool,
+    /// Whether the tests have anchors, i.e. `/// @custom:bulloak` comments
+    /// that identify the branch of the tree they were generated from.
+    ///
+    /// Tests are then matched by anchor before name, so that they can be
+    /// renamed, and tests added by `--fix` get an anchor.
+    #[arg(long, default_value_t = false)]
+    pub anchors: bool,
+    /// Maps the directory of the tree files to the directory of their
+    /// Solidity files, e.g. `--map-dir specs=test/unit/concrete`.
+    ///
+    /// Can be passed multiple times, in which case the first mapping that
+    /// matches a tree applies. Subdirectories are preserved.
+    #[arg(long = "map-dir", value_name = "FROM=TO")]
+    pub map_dirs: Vec<String>,
+    /// The name of the Solidity file of each tree, e.g. `{contract}.t.sol`.
+    ///
+    /// Can reference the `{tree}` file name, and the `{contract}` and
+    /// `{function}` (or `{Function}`, capitalized) names of the first tree
+    /// in the file. Defaults to `{tree}.t.sol`, or to `{function}.t.sol`
+    /// with `--split`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_name: Option<String>,
+    /// Whether to emit one file and contract per tree of a `.tree` file
+    /// whose trees are all of the form `Contract::function`.
+    ///
+    /// Modifiers used by more than one tree are moved to an abstract
+    /// contract in a `<tree>.shared.sol` file.
+    #[arg(long, default_value_t = false)]
+    pub split: bool,
+    /// The name of the contracts emitted with `--split`, e.g.
+    /// `{Function}_Unit_Concrete_Test`.
+    ///
+    /// Accepts the same placeholders as `--file-name`.
+    #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_SPLIT_CONTRACT_NAME)]
+    pub contract_name: String,
+    /// Emits the modifiers of every test contract once, in an abstract
+    /// `Modifiers` contract in this file, e.g. `test/Modifiers.sol`.
+    ///
+    /// Test contracts inherit from `Modifiers`, which inherits from the
+    /// contracts passed to `--base`, instead.
+    #[arg(long, value_name = "PATH")]
+    pub modifiers_file: Option<PathBuf>,
 }
 
 impl Default for Check {
@@ -50,7 +182,7 @@ impl Check {
     /// Entrypoint for `bulloak check`.
     ///
     /// Note that we don't deal with `solang_parser` errors at all.
-    pub(crate) fn run(&self, cfg: &Cli) {
+    pub(crate) fn run(&self, cfg: &Config) {
         let mut specs = Vec::new();
         for pattern in &self.files {
             match expand_glob(pattern.clone()) {
@@ -65,26 +197,35 @@ impl Check {
         }
 
         let mut violations = Vec::new();
-        let ctxs: Vec<Context> = specs
-            .iter()
-            .filter_map(|tree_path| {
-                Context::new(tree_path.clone(), &cfg.into())
-                    .map_err(|violation| violations.push(violation))
-                    .ok()
+        let ctxs: Vec<Context> = Context::for_trees(&specs, cfg)
+            .into_iter()
+            .chain(Context::for_modifiers(&specs, cfg))
+            .filter_map(|ctx| {
+                ctx.map_err(|violation| violations.push(violation)).ok()
             })
             .collect();
 
         if !self.fix {
             for ctx in ctxs {
-                violations.append(&mut rules::StructuralMatcher::check(&ctx));
+                violations.extend(
+                    check_rules(&ctx)
+                        .map(|violation| violation.with_tree(&ctx.tree)),
+                );
             }
 
             return exit(&violations);
         }
 
         let mut fixed_count = 0;
+        let mut changed = false;
         for mut ctx in ctxs {
-            let violations = rules::StructuralMatcher::check(&ctx);
+            // Orphans are only removed when asked to.
+            let violations: Vec<_> = check_rules(&ctx)
+                .filter(|v| {
+                    self.prune.is_some()
+                        || !matches!(v.kind, ViolationKind::OrphanedFunction(_))
+                })
+                .collect();
             let fixable_count =
                 violations.iter().filter(|v| v.is_fixable()).count();
 
@@ -93,7 +234,7 @@ impl Check {
                 !matches!(v.kind, ViolationKind::FunctionOrderMismatch(_, _, _))
             });
             for violation in violations {
-                ctx = match violation.kind.fix(ctx.clone()) {
+                ctx = match violation.fix(ctx.clone()) {
                     Ok(ctx) => ctx,
                     Err(e) => {
                         eprintln!(
@@ -105,40 +246,66 @@ impl Check {
                 };
             }
 
-            // Second pass fixing order violations.
-            let violations = rules::StructuralMatcher::check(&ctx);
-            let violations: Vec<Violation> = violations
-                .into_iter()
-                .filter(|v| {
-                    matches!(
-                        v.kind,
-                        ViolationKind::FunctionOrderMismatch(_, _, _)
-                    )
-                })
-                .collect();
-            if !violations.is_empty() {
-                if let Some(contract_sol) = find_contract(&ctx.pt) {
-                    if let Some(contract_hir) = ctx.hir.clone().find_contract()
-                    {
-                        ctx = fix_order(
-                            &violations,
-                            &contract_sol,
-                            contract_hir,
-                            ctx,
-                        );
-                    }
+            // Second pass fixing order violations, one contract at a time.
+            let mut violations: Vec<Violation> =
+                rules::StructuralMatcher::check(&ctx)
+                    .into_iter()
+                    .filter(|v| {
+                        matches!(
+                            v.kind,
+                            ViolationKind::FunctionOrderMismatch(_, _, _)
+                        )
+                    })
+                    .collect();
+            while let Some(contract) =
+                violations.first().map(|v| v.contract.clone())
+            {
+                let (contract_violations, rest): (Vec<_>, Vec<_>) = violations
+                    .into_iter()
+                    .partition(|v| v.contract == contract);
+                violations = rest;
+                // Offsets change with each fix, so the contracts are looked
+                // up again.
+                if let Some((contract_hir, contract_sol)) =
+                    ctx.contract_pair(contract.as_deref())
+                {
+                    ctx = fix_order(
+                        &contract_violations,
+                        &contract_sol,
+                        &contract_hir,
+                        ctx,
+                    );
                 }
             }
 
-            let sol = ctx.sol.clone();
-            let formatted =
-                ctx.fmt().expect("should format the emitted solidity code");
-            self.write(&formatted, sol);
+            // Fixes are text edits, so the rest of the file is kept as is.
+            changed |= self.write(&ctx.src, &ctx.sol);
 
             fixed_count += fixable_count;
         }
 
+        self.report(fixed_count, changed);
+    }
+
+    /// Prints the number of fixed issues.
+    ///
+    /// With `--diff`, nothing was written, so this exits with a non-zero
+    /// code if any file would change.
+    fn report(&self, fixed_count: usize, changed: bool) {
         let issue_literal = pluralize(fixed_count, "issue", "issues");
+        if self.diff {
+            // The diff goes to stdout, so that it can be applied as a patch.
+            if changed {
+                eprintln!(
+                    "\n{}: {} {} can be fixed.",
+                    "warn".bold().yellow(),
+                    fixed_count,
+                    issue_literal
+                );
+                std::process::exit(1);
+            }
+            return;
+        }
         println!(
             "\n{}: {} {} fixed.",
             "success".bold().green(),
@@ -150,18 +317,42 @@ impl Check {
     /// Handles writing the output of the `check` command.
     ///
     /// If the `--stdout` flag was passed, then the output is printed to
-    /// stdout, else it is written to the corresponding file.
-    fn write(&self, output: &str, sol: PathBuf) {
+    /// stdout. If the `--diff` flag was passed, then the changes to the
+    /// corresponding file are printed to stdout. Else it is written to the
+    /// corresponding file.
+    ///
+    /// Returns whether the corresponding file changes.
+    fn write(&self, output: &str, sol: &Path) -> bool {
         if self.stdout {
             println!("{} {}", "-->".blue(), sol.to_string_lossy());
             println!("{}", output.trim());
             println!("{}", "<--".blue());
-        } else if let Err(e) = fs::write(sol, output) {
+            return false;
+        }
+
+        let current = fs::read_to_string(sol).ok();
+        if self.diff {
+            let diff = diff::unified(sol, current.as_deref(), output);
+            if let Some(diff) = &diff {
+                print!("{diff}");
+            }
+            return diff.is_some();
+        }
+
+        if let Err(e) = fs::write(sol, output) {
             eprintln!("{}: {e}", "warn".yellow());
         }
+        current.as_deref() != Some(output)
     }
 }
 
+/// Checks every rule against the context.
+fn check_rules(ctx: &Context) -> impl Iterator<Item = Violation> {
+    rules::StructuralMatcher::check(ctx)
+        .into_iter()
+        .chain(rules::CommentMatcher::check(ctx))
+}
+
 fn exit(violations: &[Violation]) {
     if violations.is_empty() {
         println!(
@@ -184,8 +375,12 @@ fn exit(violations: &[Violation]) {
             violations.iter().filter(|v| v.is_fixable()).count();
         if fixable_count > 0 {
             let fix_literal = pluralize(fixable_count, "fix", "fixes");
+            let prune = violations
+                .iter()
+                .any(|v| matches!(v.kind, ViolationKind::OrphanedFunction(_)));
+            let flags = if prune { "--fix --prune" } else { "--fix" };
             eprintln!(
-                " (run `bulloak check --fix <.tree files>` to apply {fixable_count} {fix_literal})"
+                " (run `bulloak check {flags} <.tree files>` to apply {fixable_count} {fix_literal})"
             );
         } else {
             eprintln!();
@@ -1,7 +1,38 @@
 //! `bulloak`'s CLI config.
-use clap::{Parser, Subcommand};
-use figment::{providers::Serialized, Figment};
-use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use bulloak_foundry::config::{
+    is_name_template, is_version_req, Config, DirMapping, Import, Prune,
+    Remapping,
+};
+use clap::{
+    parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser,
+    Subcommand,
+};
+use figment::{
+    providers::{Env, Format, Serialized, Toml},
+    value::Value,
+    Figment,
+};
+use forge_fmt::FormatterConfig;
+use owo_colors::OwoColorize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The name of `bulloak`'s project config file.
+const CONFIG_FILE: &str = "bulloak.toml";
+/// The name of Foundry's config file, whose `[bulloak]` table is read as well.
+const FOUNDRY_CONFIG_FILE: &str = "foundry.toml";
+/// The prefix of the environment variables that configure `bulloak`.
+const ENV_PREFIX: &str = "BULLOAK_";
+/// The environment variable that selects the profile of `foundry.toml`.
+const FOUNDRY_PROFILE_ENV: &str = "FOUNDRY_PROFILE";
+/// The profile of `foundry.toml` used when none is selected.
+const FOUNDRY_DEFAULT_PROFILE: &str = "default";
+/// The name of the file listing the import remappings of a Foundry project.
+const REMAPPINGS_FILE: &str = "remappings.txt";
 
 /// `bulloak`'s configuration.
 #[derive(Parser, Debug, Clone, Default, Serialize, Deserialize)]
@@ -21,6 +52,9 @@ pub enum Commands {
     /// `bulloak check`.
     #[command(name = "check")]
     Check(crate::check::Check),
+    /// `bulloak status`.
+    #[command(name = "status")]
+    Status(crate::status::Status),
 }
 
 impl Default for Commands {
@@ -29,33 +63,323 @@ impl Default for Commands {
     }
 }
 
-impl From<&Cli> for bulloak_foundry::config::Config {
-    fn from(cli: &Cli) -> Self {
-        match &cli.command {
+impl TryFrom<&Cli> for Config {
+    type Error = anyhow::Error;
+
+    fn try_from(cli: &Cli) -> anyhow::Result<Self> {
+        let cfg = match &cli.command {
             Commands::Scaffold(cmd) => Self {
                 files: cmd.files.clone(),
-                solidity_version: cmd.solidity_version.clone(),
-                emit_vm_skip: cmd.with_vm_skip,
+                solidity_version: parse_solidity_version(
+                    &cmd.solidity_version,
+                )?,
+                license: cmd.license.trim().to_owned(),
+                emit_vm_skip: cmd.vm_skip,
+                emit_expect_emit: cmd.expect_emit,
                 skip_modifiers: cmd.skip_modifiers,
+                action_rules: cmd.action_rules.clone(),
+                base_contracts: cmd.base_contracts.clone(),
+                imports: parse_imports(&cmd.imports)?,
+                emit_set_up: cmd.set_up,
+                emit_anchors: cmd.anchors,
+                dir_mappings: parse_dir_mappings(&cmd.map_dirs)?,
+                file_name: parse_file_name(cmd.file_name.as_deref())?,
+                split_trees: cmd.split,
+                contract_name: parse_contract_name(&cmd.contract_name)?,
+                modifiers_file: cmd.modifiers_file.clone(),
                 ..Self::default()
             },
             Commands::Check(cmd) => Self {
                 files: cmd.files.clone(),
+                solidity_version: parse_solidity_version(
+                    &cmd.solidity_version,
+                )?,
+                license: cmd.license.trim().to_owned(),
+                emit_vm_skip: cmd.vm_skip,
+                emit_expect_emit: cmd.expect_emit,
                 skip_modifiers: cmd.skip_modifiers,
+                action_rules: cmd.action_rules.clone(),
+                base_contracts: cmd.base_contracts.clone(),
+                imports: parse_imports(&cmd.imports)?,
+                emit_set_up: cmd.set_up,
+                emit_anchors: cmd.anchors,
+                dir_mappings: parse_dir_mappings(&cmd.map_dirs)?,
+                file_name: parse_file_name(cmd.file_name.as_deref())?,
+                split_trees: cmd.split,
+                contract_name: parse_contract_name(&cmd.contract_name)?,
+                modifiers_file: cmd.modifiers_file.clone(),
+                check_orphans: cmd.orphans || cmd.prune.is_some(),
+                detect_renames: cmd.renames,
+                check_comments: !cmd.skip_comments,
+                prune: parse_prune(cmd.prune.as_deref())?,
                 ..Self::default()
             },
-        }
+            Commands::Status(cmd) => Self {
+                files: cmd.files.clone(),
+                emit_anchors: cmd.anchors,
+                dir_mappings: parse_dir_mappings(&cmd.map_dirs)?,
+                file_name: parse_file_name(cmd.file_name.as_deref())?,
+                split_trees: cmd.split,
+                contract_name: parse_contract_name(&cmd.contract_name)?,
+                ..Self::default()
+            },
+        };
+
+        Ok(cfg)
+    }
+}
+
+/// Validates the `--solidity-version` argument.
+fn parse_solidity_version(version: &str) -> anyhow::Result<String> {
+    if !is_version_req(version) {
+        anyhow::bail!(
+            "invalid solidity version: expected a version requirement like \
+             `0.8.0` or `>=0.8.22 <0.9.0`, found `{version}`"
+        );
+    }
+
+    Ok(version.trim().to_owned())
+}
+
+/// Parses the `--prune` argument.
+fn parse_prune(prune: Option<&str>) -> anyhow::Result<Prune> {
+    prune.map_or(Ok(Prune::Delete), |prune| {
+        prune.parse().map_err(|e| anyhow::anyhow!("invalid prune mode: {e}"))
+    })
+}
+
+/// Parses the `--import` arguments.
+fn parse_imports(imports: &[String]) -> anyhow::Result<Vec<Import>> {
+    imports
+        .iter()
+        .map(|import| {
+            import.parse().map_err(|e| anyhow::anyhow!("invalid import: {e}"))
+        })
+        .collect()
+}
+
+/// Parses the `--map-dir` arguments.
+fn parse_dir_mappings(mappings: &[String]) -> anyhow::Result<Vec<DirMapping>> {
+    mappings
+        .iter()
+        .map(|mapping| {
+            mapping
+                .parse()
+                .map_err(|e| anyhow::anyhow!("invalid directory mapping: {e}"))
+        })
+        .collect()
+}
+
+/// Validates the `--file-name` argument.
+fn parse_file_name(template: Option<&str>) -> anyhow::Result<Option<String>> {
+    template
+        .map(|template| parse_name_template("file name", template))
+        .transpose()
+}
+
+/// Validates the `--contract-name` argument.
+fn parse_contract_name(template: &str) -> anyhow::Result<String> {
+    parse_name_template("contract name", template)
+}
+
+/// Validates a file or contract name template.
+fn parse_name_template(what: &str, template: &str) -> anyhow::Result<String> {
+    if !is_name_template(template) {
+        anyhow::bail!(
+            "invalid {what}: expected a template using the `{{tree}}`, \
+             `{{contract}}`, `{{function}}` or `{{Function}}` placeholders, \
+             found `{template}`"
+        );
+    }
+
+    Ok(template.trim().to_owned())
+}
+
+/// Layers a command's settings, from lowest to highest priority:
+///
+/// 1. The command's default values.
+/// 2. The project's settings in `foundry.toml`, i.e. the solidity version.
+/// 3. The `[bulloak]` table of `foundry.toml`.
+/// 4. `bulloak.toml`.
+/// 5. `BULLOAK_*` environment variables, e.g. `BULLOAK_SOLIDITY_VERSION`.
+/// 6. The options passed on the command line.
+///
+/// Config files are searched for in the current directory and its parents.
+/// Their keys are the command's option names in snake case, e.g.
+/// `solidity_version = "0.8.0"` or `base_contracts = ["Base_Test"]`.
+fn layer<T: Serialize + DeserializeOwned>(
+    cmd: &T,
+    matches: &ArgMatches,
+) -> anyhow::Result<T> {
+    // Only the options that were explicitly passed take precedence over the
+    // config files. The rest hold their default values.
+    let Value::Dict(_, mut args) = Value::serialize(cmd)? else {
+        unreachable!("a command serializes to a dictionary");
+    };
+    args.retain(|id, _| {
+        matches!(matches.value_source(id), Some(ValueSource::CommandLine))
+    });
+
+    let mut figment = Figment::from(Serialized::defaults(cmd));
+    if let Some(version) = foundry_solidity_version() {
+        figment =
+            figment.merge(Serialized::default("solidity_version", version));
+    }
+    let figment = figment
+        .merge(Figment::from(Toml::file(FOUNDRY_CONFIG_FILE)).focus("bulloak"))
+        .merge(Toml::file(CONFIG_FILE))
+        .merge(Env::prefixed(ENV_PREFIX))
+        .merge(Serialized::defaults(args));
+
+    Ok(figment.extract()?)
+}
+
+/// Returns the active profile of `foundry.toml`, selected by the
+/// `FOUNDRY_PROFILE` environment variable.
+fn foundry_profile() -> Figment {
+    let profile = std::env::var(FOUNDRY_PROFILE_ENV)
+        .unwrap_or_else(|_| FOUNDRY_DEFAULT_PROFILE.to_owned());
+
+    Figment::from(Toml::file(FOUNDRY_CONFIG_FILE))
+        .focus(&format!("profile.{profile}"))
+}
+
+/// Infers the solidity version from the `solc_version` or `solc` setting of
+/// `foundry.toml`.
+///
+/// `solc` may also be a path to a compiler binary, in which case no version
+/// is inferred.
+fn foundry_solidity_version() -> Option<String> {
+    let profile = foundry_profile();
+    ["solc_version", "solc"]
+        .into_iter()
+        .find_map(|key| profile.extract_inner::<String>(key).ok())
+        .filter(|version| is_version_req(version))
+}
+
+/// Reads the `forge fmt` settings of the project from `foundry.toml`.
+///
+/// Both the `[fmt]` table and the `fmt` table of the active profile are read,
+/// the latter taking precedence. Missing settings keep their default values.
+fn formatter_config() -> anyhow::Result<FormatterConfig> {
+    let figment =
+        Figment::from(Serialized::defaults(FormatterConfig::default()))
+            .merge(Figment::from(Toml::file(FOUNDRY_CONFIG_FILE)).focus("fmt"))
+            .merge(foundry_profile().focus("fmt"));
+
+    Ok(figment.extract()?)
+}
+
+/// Returns the root directory of the project, i.e. the closest directory with
+/// a `foundry.toml`, relative to the current directory.
+///
+/// Falls back to the current directory if there is none.
+fn project_root() -> PathBuf {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| {
+            cwd.ancestors()
+                .position(|dir| dir.join(FOUNDRY_CONFIG_FILE).is_file())
+        })
+        .map(|depth| (0..depth).map(|_| Component::ParentDir).collect())
+        .unwrap_or_default()
+}
+
+/// Maps the `.tree` files of the project to Solidity files in the `test`
+/// directory of the active profile of `foundry.toml`, if it is set.
+///
+/// Trees keep their path relative to the project root, e.g. `specs/a.tree`
+/// maps to `test/specs/a.t.sol`, while the trees already in the `test`
+/// directory keep their Solidity file next to them.
+fn foundry_dir_mappings(root: &Path) -> Vec<DirMapping> {
+    let Ok(test) = foundry_profile().extract_inner::<PathBuf>("test") else {
+        return vec![];
+    };
+    let mut mappings = vec![
+        DirMapping { from: root.join(&test), to: root.join(&test) },
+        DirMapping { from: root.to_path_buf(), to: root.join(&test) },
+    ];
+    // Trees are usually passed relative to the current directory, which is
+    // below the root when running from a subdirectory of the project.
+    let depth = root.components().count();
+    if depth > 0 {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let count = cwd.components().count();
+        let subdir: PathBuf =
+            cwd.components().skip(count.saturating_sub(depth)).collect();
+        let to = if subdir.starts_with(&test) {
+            PathBuf::new()
+        } else {
+            root.join(&test).join(subdir)
+        };
+        mappings.push(DirMapping { from: PathBuf::new(), to });
+    }
+
+    mappings
+}
+
+/// Reads the import remappings of the project from the active profile of
+/// `foundry.toml` and from the `remappings.txt` at its `root`, the former
+/// taking precedence.
+///
+/// Invalid remappings are skipped with a warning, since they only matter to
+/// the imports that use them.
+fn remappings(root: &Path) -> Vec<Remapping> {
+    let mut remappings: Vec<String> =
+        foundry_profile().extract_inner("remappings").unwrap_or_default();
+    if let Ok(text) = fs::read_to_string(root.join(REMAPPINGS_FILE)) {
+        remappings.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_owned),
+        );
     }
+
+    remappings
+        .iter()
+        .filter_map(|remapping| match remapping.parse() {
+            Ok(remapping) => Some(remapping),
+            Err(e) => {
+                eprintln!(
+                    "{}: skipping invalid remapping: {e}",
+                    "warn".yellow()
+                );
+                None
+            }
+        })
+        .collect()
 }
 
 /// Main entrypoint of `bulloak`'s execution.
 pub(crate) fn run() -> anyhow::Result<()> {
-    let config: Cli =
-        Figment::new().merge(Serialized::defaults(Cli::parse())).extract()?;
+    let matches = Cli::command().get_matches();
+    let mut config =
+        Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    config.command = match (&config.command, matches.subcommand()) {
+        (Commands::Scaffold(cmd), Some((_, matches))) => {
+            Commands::Scaffold(layer(cmd, matches)?)
+        }
+        (Commands::Check(cmd), Some((_, matches))) => {
+            Commands::Check(layer(cmd, matches)?)
+        }
+        (Commands::Status(cmd), Some((_, matches))) => {
+            Commands::Status(layer(cmd, matches)?)
+        }
+        _ => unreachable!("a subcommand is required"),
+    };
+    let mut cfg = Config::try_from(&config)?;
+    cfg.formatter_config = formatter_config()?;
+    cfg.root = project_root();
+    cfg.remappings = remappings(&cfg.root);
+    if cfg.dir_mappings.is_empty() {
+        cfg.dir_mappings = foundry_dir_mappings(&cfg.root);
+    }
 
     match &config.command {
-        Commands::Scaffold(command) => command.run(&config),
-        Commands::Check(command) => command.run(&config),
+        Commands::Scaffold(command) => command.run(&cfg),
+        Commands::Check(command) => command.run(&cfg),
+        Commands::Status(command) => command.run(&cfg),
     };
 
     Ok(())
@@ -0,0 +1,163 @@
+//! Renders the changes to a file as a unified diff.
+
+use std::path::Path;
+
+/// The number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// Returns the unified diff between the current contents of the file at
+/// `path`, `None` if it doesn't exist, and its `new` contents.
+///
+/// Returns `None` if the contents don't change.
+pub(crate) fn unified(
+    path: &Path,
+    old: Option<&str>,
+    new: &str,
+) -> Option<String> {
+    if old == Some(new) {
+        return None;
+    }
+
+    let old_lines: Vec<_> = old.unwrap_or_default().lines().collect();
+    let new_lines: Vec<_> = new.lines().collect();
+    let lines = diff::slice(&old_lines, &new_lines);
+    let changes: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, diff::Result::Both(..)))
+        .map(|(idx, _)| idx)
+        .collect();
+    if changes.is_empty() {
+        // Only the trailing newline differs.
+        return None;
+    }
+
+    // The number of lines of each file before each diff line.
+    let mut positions = Vec::with_capacity(lines.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for line in &lines {
+        positions.push((old_pos, new_pos));
+        match line {
+            diff::Result::Left(_) => old_pos += 1,
+            diff::Result::Right(_) => new_pos += 1,
+            diff::Result::Both(..) => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let path = path.display();
+    let mut out = vec![
+        match old {
+            Some(_) => format!("--- {path}"),
+            None => "--- /dev/null".to_owned(),
+        },
+        format!("+++ {path}"),
+    ];
+
+    let mut idx = 0;
+    while idx < changes.len() {
+        // Changes close enough to share their context go in the same hunk.
+        let mut last = idx;
+        while last + 1 < changes.len()
+            && changes[last + 1] - changes[last] <= 2 * CONTEXT + 1
+        {
+            last += 1;
+        }
+        let start = changes[idx].saturating_sub(CONTEXT);
+        let end = (changes[last] + CONTEXT + 1).min(lines.len());
+        idx = last + 1;
+
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        out.push(format!(
+            "@@ -{} +{} @@",
+            range(old_start, old_end - old_start),
+            range(new_start, new_end - new_start)
+        ));
+        out.extend(lines[start..end].iter().map(|line| match line {
+            diff::Result::Left(line) => format!("-{line}"),
+            diff::Result::Right(line) => format!("+{line}"),
+            diff::Result::Both(line, _) => format!(" {line}"),
+        }));
+    }
+
+    Some(out.join("\n") + "\n")
+}
+
+/// Formats the range of a hunk, given the number of lines before it and its
+/// length.
+fn range(before: usize, len: usize) -> String {
+    match len {
+        // An empty range starts at the line before it.
+        0 => format!("{before},0"),
+        1 => format!("{}", before + 1),
+        _ => format!("{},{len}", before + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pretty_assertions::assert_eq;
+
+    use super::unified;
+
+    #[test]
+    fn skips_unchanged_files() {
+        let path = Path::new("foo.t.sol");
+        assert_eq!(unified(path, Some("a\nb\n"), "a\nb\n"), None);
+        assert_eq!(unified(path, Some("a\nb"), "a\nb\n"), None);
+    }
+
+    #[test]
+    fn diffs_new_files() {
+        let diff = unified(Path::new("foo.t.sol"), None, "a\nb\n").unwrap();
+        assert_eq!(
+            diff,
+            "--- /dev/null\n+++ foo.t.sol\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn groups_changes_in_hunks() {
+        let old: String = (1..=20).map(|n| n.to_string() + "\n").collect();
+        let new: String = (1..=20)
+            .filter(|n| *n != 5)
+            .map(|n| match n {
+                2 => "two\n".to_owned(),
+                18 => "18\neighteen\n".to_owned(),
+                n => format!("{n}\n"),
+            })
+            .collect();
+        let diff = unified(Path::new("foo.t.sol"), Some(&old), &new).unwrap();
+
+        assert_eq!(
+            diff,
+            "\
+--- foo.t.sol
++++ foo.t.sol
+@@ -1,8 +1,7 @@
+ 1
+-2
++two
+ 3
+ 4
+-5
+ 6
+ 7
+ 8
+@@ -16,5 +15,6 @@
+ 16
+ 17
+ 18
++eighteen
+ 19
+ 20
+"
+        );
+    }
+}
@@ -3,8 +3,10 @@ use std::process;
 
 mod check;
 mod cli;
+mod diff;
 mod glob;
 mod scaffold;
+mod status;
 
 fn main() {
     if let Err(e) = crate::cli::run() {
@@ -7,13 +7,20 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use bulloak_foundry::{constants::DEFAULT_SOL_VERSION, scaffold::scaffold};
+use bulloak_foundry::{
+    check::context::Context,
+    config::{ActionRule, Config},
+    constants::{
+        DEFAULT_LICENSE, DEFAULT_SOL_VERSION, DEFAULT_SPLIT_CONTRACT_NAME,
+    },
+    layout::{merged_test_files, modifiers_file, TestFile},
+    scaffold::{fmt, scaffold_file},
+};
 use clap::Parser;
-use forge_fmt::fmt;
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 
-use crate::{cli::Cli, glob::expand_glob};
+use crate::{diff, glob::expand_glob};
 
 /// Generate Solidity tests based on your spec.
 #[doc(hidden)]
@@ -41,15 +48,113 @@ pub struct Scaffold {
         default_value_t = false
     )]
     pub force_write: bool,
+    /// When `--write-files` is passed, use `--merge` to add the missing
+    /// modifiers and tests to the existing output files, in spec order.
+    ///
+    /// Everything already in the files is kept as is.
+    #[arg(
+        long,
+        requires = "file-handling",
+        conflicts_with = "force_write",
+        default_value_t = false
+    )]
+    pub merge: bool,
+    /// When `--write-files` is passed, use `--diff` to print a unified diff
+    /// of the changes to the output files instead of writing them.
+    ///
+    /// Exits with a non-zero code if any file would change.
+    #[arg(
+        long,
+        visible_alias = "dry-run",
+        requires = "file-handling",
+        default_value_t = false
+    )]
+    pub diff: bool,
     /// Sets a Solidity version for the test contracts.
+    ///
+    /// Accepts any version requirement supported by Solidity's `pragma`
+    /// directive, e.g. `'>=0.8.22 <0.9.0'`.
     #[arg(short = 's', long, default_value = DEFAULT_SOL_VERSION)]
     pub solidity_version: String,
+    /// Sets the SPDX license identifier of the test files.
+    #[arg(long, default_value = DEFAULT_LICENSE)]
+    pub license: String,
     /// Whether to add vm.skip(true) at the beginning of each test.
     #[arg(short = 'S', long = "vm-skip", default_value_t = false)]
-    pub with_vm_skip: bool,
+    pub vm_skip: bool,
+    /// Whether to add `vm.expectEmit()` skeletons for actions that emit
+    /// events, e.g. `it should emit a {Transfer} event`.
+    #[arg(short = 'E', long = "expect-emit", default_value_t = false)]
+    pub expect_emit: bool,
     /// Whether to emit modifiers.
     #[arg(short = 'm', long, default_value_t = false)]
     pub skip_modifiers: bool,
+    /// Adds a rule that inserts a Solidity snippet in tests whose actions
+    /// match a pattern, e.g. `--action-rule '^it should transfer
+    /// (\w+)=>token.transfer(bob, $1);'`.
+    ///
+    /// Capture groups are available in the snippet as `$1` or `${name}`
+    /// placeholders. Only the first matching rule is applied to an action.
+    #[arg(long = "action-rule", value_name = "PATTERN=>SNIPPET")]
+    pub action_rules: Vec<ActionRule>,
+    /// A contract the test contracts inherit from, e.g. `--base Base_Test`.
+    ///
+    /// Can be passed multiple times. Replaces forge-std's `Test`, which
+    /// is otherwise inherited when needed.
+    #[arg(long = "base", value_name = "CONTRACT")]
+    pub base_contracts: Vec<String>,
+    /// Adds an import directive to the test files, e.g. `--import
+    /// 'Base_Test=test/Base.t.sol'`.
+    ///
+    /// Several symbols can be imported from the same path by separating
+    /// them with commas. Without symbols, the whole file is imported.
+    #[arg(long = "import", value_name = "[SYMBOLS=]PATH")]
+    pub imports: Vec<String>,
+    /// Whether to add a `setUp` function to the test contracts.
+    ///
+    /// When `--base` is passed, it calls `super.setUp()`.
+    #[arg(long = "set-up", default_value_t = false)]
+    pub set_up: bool,
+    /// Whether to add an anchor to each test, i.e. a `/// @custom:bulloak`
+    /// comment that identifies the branch of the tree it was generated
+    /// from.
+    #[arg(long, default_value_t = false)]
+    pub anchors: bool,
+    /// Maps the directory of the tree files to the directory of their
+    /// Solidity files, e.g. `--map-dir specs=test/unit/concrete`.
+    ///
+    /// Can be passed multiple times, in which case the first mapping that
+    /// matches a tree applies. Subdirectories are preserved.
+    #[arg(long = "map-dir", value_name = "FROM=TO")]
+    pub map_dirs: Vec<String>,
+    /// The name of the Solidity file of each tree, e.g. `{contract}.t.sol`.
+    ///
+    /// Can reference the `{tree}` file name, and the `{contract}` and
+    /// `{function}` (or `{Function}`, capitalized) names of the first tree
+    /// in the file. Defaults to `{tree}.t.sol`, or to `{function}.t.sol`
+    /// with `--split`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_name: Option<String>,
+    /// Whether to emit one file and contract per tree of a `.tree` file
+    /// whose trees are all of the form `Contract::function`.
+    ///
+    /// Modifiers used by more than one tree are moved to an abstract
+    /// contract in a `<tree>.shared.sol` file.
+    #[arg(long, default_value_t = false)]
+    pub split: bool,
+    /// The name of the contracts emitted with `--split`, e.g.
+    /// `{Function}_Unit_Concrete_Test`.
+    ///
+    /// Accepts the same placeholders as `--file-name`.
+    #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_SPLIT_CONTRACT_NAME)]
+    pub contract_name: String,
+    /// Emits the modifiers of every test contract once, in an abstract
+    /// `Modifiers` contract in this file, e.g. `test/Modifiers.sol`.
+    ///
+    /// Test contracts inherit from `Modifiers`, which inherits from the
+    /// contracts passed to `--base`, instead.
+    #[arg(long, value_name = "PATH")]
+    pub modifiers_file: Option<PathBuf>,
 }
 
 impl Default for Scaffold {
@@ -65,7 +170,7 @@ impl Scaffold {
     /// writes the output to files or prints to stdout based on the config.
     ///
     /// If any errors occur during processing, they are collected and reported.
-    pub(crate) fn run(&self, cfg: &Cli) {
+    pub(crate) fn run(&self, cfg: &Config) {
         let mut files = Vec::with_capacity(self.files.len());
         for pattern in &self.files {
             match expand_glob(pattern.clone()) {
@@ -81,65 +186,158 @@ impl Scaffold {
             }
         }
 
-        let errors = files
-            .iter()
-            .filter_map(|file| {
-                self.process_file(file, cfg)
-                    .map_err(|e| (file.as_path(), e))
-                    .err()
-            })
-            .collect::<Vec<_>>();
+        let mut errors = Vec::new();
+        let mut trees = Vec::with_capacity(files.len());
+        for file in &files {
+            match fs::read_to_string(file) {
+                Ok(text) => trees.push((file.clone(), text)),
+                Err(e) => errors.push((file.as_path(), e.into())),
+            }
+        }
+
+        // Trees that map to the same Solidity file are merged, so they are
+        // processed together.
+        let mut test_files = Vec::with_capacity(trees.len());
+        let mut changed = false;
+        let results = merged_test_files(&trees, cfg);
+        for ((tree, _), result) in trees.iter().zip(results) {
+            let emitted = result.and_then(|emitted| {
+                for test_file in &emitted {
+                    changed |= self.emit(tree, test_file, cfg)?;
+                }
+                Ok(emitted)
+            });
+            match emitted {
+                Ok(emitted) => test_files.extend(emitted),
+                Err(e) => errors.push((tree.as_path(), e)),
+            }
+        }
+
+        // The modifiers file holds the modifiers of all the trees, so it is
+        // emitted last.
+        if let Some(file) = modifiers_file(&test_files, cfg) {
+            // The modifiers file is attributed to the first tree.
+            match self.emit(&trees[0].0, &file, cfg) {
+                Ok(file_changed) => changed |= file_changed,
+                Err(e) => eprintln!("{}: {e}", "error".red()),
+            }
+        }
 
         if !errors.is_empty() {
             Scaffold::report_errors(&errors);
             std::process::exit(1);
         }
+
+        // With `--diff`, any change means the files are out of date.
+        if self.diff && changed {
+            std::process::exit(1);
+        }
     }
 
-    /// Processes a single input file.
+    /// Scaffolds the Solidity code of a test file of `tree`, formats it, and
+    /// either writes it to a file or prints it to stdout.
     ///
-    /// This method reads the input file, scaffolds the Solidity code, formats
-    /// it, and either writes it to a file or prints it to stdout.
-    fn process_file(&self, file: &Path, cfg: &Cli) -> anyhow::Result<()> {
-        let text = fs::read_to_string(file)?;
-        let emitted = scaffold(&text, &cfg.into())?;
-        let formatted = fmt(&emitted).unwrap_or_else(|err| {
+    /// With `--merge`, existing files are merged with instead.
+    ///
+    /// Returns whether the file changes, which is only ever the case when
+    /// writing files.
+    fn emit(
+        &self,
+        tree: &Path,
+        test_file: &TestFile,
+        cfg: &Config,
+    ) -> anyhow::Result<bool> {
+        if self.write_files && self.merge && test_file.path.exists() {
+            let merged = Scaffold::merge_file(tree, test_file)?;
+            return Ok(merged.is_some_and(|merged| {
+                self.write_file(&merged, &test_file.path)
+            }));
+        }
+
+        let emitted = scaffold_file(test_file)?;
+        let formatted = fmt(&emitted, &test_file.cfg).unwrap_or_else(|err| {
             eprintln!("{}: {}", "WARN".yellow(), err);
             emitted
         });
 
         if self.write_files {
-            let file = file.with_extension("t.sol");
-            self.write_file(&formatted, &file);
+            return Ok(self.write_file(&formatted, &test_file.path));
+        } else if cfg.split_trees || cfg.shared_modifiers_file().is_some() {
+            // When a tree maps to several files, they are printed one after
+            // the other.
+            println!("{} {}", "-->".blue(), test_file.path.display());
+            println!("{}", formatted.trim());
+            println!("{}", "<--".blue());
         } else {
             println!("{formatted}");
         }
 
-        Ok(())
+        Ok(false)
+    }
+
+    /// Adds the missing contracts, modifiers and tests of a test file of
+    /// `tree` to its existing Solidity file.
+    ///
+    /// Returns the contents of the merged file, or `None` if nothing is
+    /// missing.
+    fn merge_file(
+        tree: &Path,
+        test_file: &TestFile,
+    ) -> anyhow::Result<Option<String>> {
+        let ctx = Context::for_test_file(tree.to_path_buf(), test_file.clone())
+            .map_err(|violation| anyhow::anyhow!("{violation}"))?;
+        let Some(ctx) = ctx.merge()? else {
+            return Ok(None);
+        };
+
+        Ok(Some(ctx.src))
     }
 
     /// Writes the provided `text` to `file`.
     ///
     /// If the file doesn't exist it will create it. If it exists,
     /// and `--force-write` was not passed, it will skip writing to the file.
-    fn write_file(&self, text: &str, file: &PathBuf) {
-        // Don't overwrite files unless `--force-write` was passed.
-        if file.exists() && !self.force_write {
+    /// With `--diff`, the changes are printed instead.
+    ///
+    /// Returns whether the file changes.
+    fn write_file(&self, text: &str, file: &Path) -> bool {
+        // Don't overwrite files unless `--force-write` or `--merge` was
+        // passed.
+        if file.exists() && !self.force_write && !self.merge {
             eprintln!(
                 "{}: Skipped emitting {:?}",
                 "warn".yellow(),
-                file.as_path().blue()
+                file.blue()
             );
             eprintln!(
                 "    {} The corresponding `.t.sol` file already exists",
                 "=".blue()
             );
-            return;
+            return false;
+        }
+
+        if self.diff {
+            let current = fs::read_to_string(file).ok();
+            let Some(diff) = diff::unified(file, current.as_deref(), text)
+            else {
+                return false;
+            };
+            print!("{diff}");
+            return true;
+        }
+
+        if let Some(dir) = file.parent() {
+            if let Err(err) = fs::create_dir_all(dir) {
+                eprintln!("{}: {err}", "error".red());
+                return false;
+            }
         }
 
         if let Err(err) = fs::write(file, text) {
             eprintln!("{}: {err}", "error".red());
         };
+
+        true
     }
 
     /// Reports errors that occurred during file processing.
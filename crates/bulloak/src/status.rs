@@ -0,0 +1,174 @@
+//! Defines the `bulloak status` command.
+//!
+//! This command reports how far the tests of a spec are implemented.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use bulloak_foundry::{
+    check::context::Context,
+    config::Config,
+    constants::DEFAULT_SPLIT_CONTRACT_NAME,
+    status::{test_statuses, TestStatus},
+};
+use bulloak_syntax::{front_matter, utils::pluralize};
+use clap::Parser;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::glob::expand_glob;
+
+/// Report which tests of the spec are implemented.
+#[doc(hidden)]
+#[derive(Debug, Parser, Clone, Serialize, Deserialize)]
+pub struct Status {
+    /// The set of tree files to use as spec.
+    ///
+    /// Solidity file names are inferred from the specs.
+    pub files: Vec<std::path::PathBuf>,
+    /// Whether the tests have anchors, i.e. `/// @custom:bulloak` comments
+    /// that identify the branch of the tree they were generated from.
+    ///
+    /// Tests are then matched by anchor before name.
+    #[arg(long, default_value_t = false)]
+    pub anchors: bool,
+    /// Maps the directory of the tree files to the directory of their
+    /// Solidity files, e.g. `--map-dir specs=test/unit/concrete`.
+    ///
+    /// Can be passed multiple times, in which case the first mapping that
+    /// matches a tree applies. Subdirectories are preserved.
+    #[arg(long = "map-dir", value_name = "FROM=TO")]
+    pub map_dirs: Vec<String>,
+    /// The name of the Solidity file of each tree, e.g. `{contract}.t.sol`.
+    ///
+    /// Accepts the same placeholders as the `--file-name` option of
+    /// `bulloak scaffold`.
+    #[arg(long, value_name = "TEMPLATE")]
+    pub file_name: Option<String>,
+    /// Whether the trees were scaffolded with `--split`, i.e. to one file and
+    /// contract per tree of a `.tree` file.
+    #[arg(long, default_value_t = false)]
+    pub split: bool,
+    /// The name of the contracts emitted with `--split`, e.g.
+    /// `{Function}_Unit_Concrete_Test`.
+    ///
+    /// Accepts the same placeholders as `--file-name`.
+    #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_SPLIT_CONTRACT_NAME)]
+    pub contract_name: String,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Status::parse_from(Vec::<String>::new())
+    }
+}
+
+impl Status {
+    /// Entrypoint for `bulloak status`.
+    pub(crate) fn run(&self, cfg: &Config) {
+        let mut specs = Vec::new();
+        for pattern in &self.files {
+            match expand_glob(pattern.clone()) {
+                Ok(iter) => specs.extend(iter),
+                Err(e) => eprintln!(
+                    "{}: could not expand {}: {}",
+                    "warn".yellow(),
+                    pattern.display(),
+                    e
+                ),
+            }
+        }
+
+        let mut failed = false;
+        for (idx, spec) in specs.iter().enumerate() {
+            if idx > 0 {
+                println!();
+            }
+            failed |= !report(spec, cfg);
+        }
+
+        if failed {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the tree at `spec`, with the status of the test of each branch, and
+/// how many of its tests are implemented.
+///
+/// Returns whether the status could be reported, i.e. whether the tree and
+/// its Solidity files could be read and parsed.
+fn report(spec: &Path, cfg: &Config) -> bool {
+    let text = match fs::read_to_string(spec) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!(
+                "{}: could not read {}: {e}",
+                "error".red(),
+                spec.display()
+            );
+            return false;
+        }
+    };
+    // The number of lines of the front matter, which are not reported.
+    let skipped = front_matter::split(&text)
+        .map_or(0, |(_, body)| text[..text.len() - body.len()].lines().count());
+
+    // The status of the tests, by the line of their branch in the tree.
+    let mut statuses: HashMap<usize, TestStatus> = HashMap::new();
+    let mut failed = false;
+    for ctx in Context::for_tree(spec.to_path_buf(), cfg) {
+        let ctx = match ctx {
+            Ok(ctx) => ctx,
+            Err(violation) => {
+                eprint!("{violation}");
+                failed = true;
+                continue;
+            }
+        };
+        for (fn_hir, status) in test_statuses(&ctx) {
+            let line = fn_hir.span.start.line;
+            // A branch with several tests is as far as its least implemented
+            // one.
+            statuses
+                .entry(line)
+                .and_modify(|s| *s = (*s).min(status))
+                .or_insert(status);
+        }
+    }
+    if failed {
+        eprintln!(
+            "{}: could not report the status of {}",
+            "error".red(),
+            spec.display()
+        );
+        return false;
+    }
+
+    println!("{}", spec.display().bold());
+    for (idx, line) in text.lines().enumerate().skip(skipped) {
+        let marker = match statuses.get(&(idx + 1)) {
+            Some(TestStatus::Implemented) => "[x]".green().to_string(),
+            Some(TestStatus::Skipped) => "[-]".yellow().to_string(),
+            Some(TestStatus::Stub) => "[ ]".to_string(),
+            Some(TestStatus::Missing) => "[!]".red().to_string(),
+            None => "   ".to_string(),
+        };
+        println!("{}", format!("{marker} {line}").trim_end());
+    }
+
+    let count = |status| statuses.values().filter(|&&s| s == status).count();
+    let total = statuses.len();
+    let implemented = count(TestStatus::Implemented);
+    let percentage = (implemented * 100).checked_div(total).unwrap_or(100);
+    println!(
+        "{implemented}/{total} {} implemented ({percentage}%), {} skipped, {} \
+         {}, {} missing",
+        pluralize(total, "test", "tests"),
+        count(TestStatus::Skipped),
+        count(TestStatus::Stub),
+        pluralize(count(TestStatus::Stub), "stub", "stubs"),
+        count(TestStatus::Missing),
+    );
+
+    true
+}
@@ -1,5 +1,5 @@
 #![allow(missing_docs)]
-use std::env;
+use std::{env, fs};
 
 use common::{cmd, get_binary_path};
 use owo_colors::OwoColorize;
@@ -78,6 +78,28 @@ fn checks_modifiers_skipped_issue_81() {
     ));
 }
 
+#[test]
+fn checks_fuzz_params_mismatch() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("fuzz_params_mismatch.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    let formatted_message = format!(
+        r#"{}: function "testFuzz_WhenTheCallerIsTheOwner" is missing in .sol"#,
+        "warn".yellow(),
+    );
+
+    assert!(
+        stderr.contains(&formatted_message),
+        "stderr: {stderr}\nmessage: {formatted_message}"
+    );
+    assert_eq!(1, stderr.matches("is missing in .sol").count());
+}
+
 #[test]
 fn checks_missing_sol_file() {
     let cwd = env::current_dir().unwrap();
@@ -166,7 +188,7 @@ fn checks_contract_name_mismatch() {
 
 #[cfg(not(target_os = "windows"))]
 #[test]
-fn checks_contract_name_mismatch_multiple_roots() {
+fn checks_missing_contract_multiple_roots() {
     let cwd = env::current_dir().unwrap();
     let binary_path = get_binary_path();
     let tree_path = cwd
@@ -177,14 +199,105 @@ fn checks_contract_name_mismatch_multiple_roots() {
     let output = cmd(&binary_path, "check", &tree_path, &[]);
     let stderr = String::from_utf8(output.stderr).unwrap();
 
-    let formatted_message = format!(
-        "{}: an error occurred while parsing the tree: contract name mismatch: expected 'ContractName', found 'MismatchedContractName'\n   {} {}",
-        "warn".yellow(),
-        "-->".blue(),
-        tree_path.display()
+    assert!(stderr
+        .contains(r#"contract "MismatchedContractName" is missing in .sol"#));
+    assert!(!stderr.contains(r#"contract "ContractName" is missing"#));
+}
+
+#[test]
+fn checks_multiple_contracts() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("multiple_contracts.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
     );
+}
+
+#[test]
+fn checks_split_trees() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("split").join("foo.tree");
+
+    let args = ["--split", "--contract-name", "{Function}_Unit_Concrete_Test"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(stdout.contains("All checks completed successfully"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--split"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains(
+        r#"contract "Foo_Bar_Test" is missing in .sol -- found "Bar_Unit_Concrete_Test" instead"#
+    ));
+}
 
-    assert!(stderr.contains(&formatted_message));
+#[test]
+fn checks_merged_trees() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("merge");
+    let tree_path = tests_path.join("foo.withdraw.tree");
+    let deposit_path = tests_path.join("foo.deposit.tree");
+
+    let args =
+        [deposit_path.to_str().unwrap(), "--file-name", "{contract}.t.sol"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(stdout.contains("All checks completed successfully"));
+
+    // On its own, the tree is checked against the whole contract, where its
+    // tests have the names of the merged trees.
+    let args = ["--file-name", "{contract}.t.sol", "--renames"];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains(
+        r#"function "test_Withdraw_RevertWhen_TheVaultIsPaused" is named "test_RevertWhen_TheVaultIsPaused" in the tree"#
+    ));
+}
+
+#[test]
+fn checks_modifiers_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("modifiers");
+    let tree_path = tests_path.join("foo.tree");
+    let qux_path = tests_path.join("qux.tree");
+    let qux_path = qux_path.to_string_lossy();
+
+    let modifiers_path = tests_path.join("Modifiers.sol");
+    let modifiers_path = modifiers_path.to_string_lossy();
+    let args = [qux_path.as_ref(), "--modifiers-file", modifiers_path.as_ref()];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    assert!(stdout.contains("All checks completed successfully"));
+
+    let modifiers_path = tests_path.join("Incomplete.sol");
+    let modifiers_path = modifiers_path.to_string_lossy();
+    let args = [qux_path.as_ref(), "--modifiers-file", modifiers_path.as_ref()];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr
+        .contains(r#"function "givenTheCallerIsTheOwner" is missing in .sol"#));
 }
 
 #[test]
@@ -201,6 +314,58 @@ fn checks_invalid_tree() {
     ));
 }
 
+#[test]
+fn fixes_empty_file_with_license_and_solidity_version() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("empty_file.tree");
+
+    let args = [
+        "--fix",
+        "--stdout",
+        "--license",
+        "MIT",
+        "--solidity-version",
+        ">=0.8.22 <0.9.0",
+    ];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let expected = "// SPDX-License-Identifier: MIT
+pragma solidity >=0.8.22 <0.9.0;
+
+contract EmptyFile {
+    function test_ShouldGetTheConfiguredHeader() external {
+        // It should get the configured header.
+    }
+}";
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains(expected));
+}
+
+#[test]
+fn fixes_with_vm_skip_and_action_rules() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("empty_file.tree");
+
+    let args = [
+        "--fix",
+        "--stdout",
+        "--vm-skip",
+        "--action-rule",
+        "configured header=>assertTrue(true);",
+    ];
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let expected = "    function test_ShouldGetTheConfiguredHeader() external {
+        // It should get the configured header.
+        assertTrue(true);
+        vm.skip(true);
+    }";
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains(expected), "{actual}");
+}
+
 #[test]
 fn fixes_non_matching_contract_names() {
     let cwd = env::current_dir().unwrap();
@@ -213,15 +378,59 @@ fn fixes_non_matching_contract_names() {
 pragma solidity 0.8.0;
 
 contract ContractName {
-    function test_ShouldHaveANameMismatchInTheContracts() external {
-        // It should match the result of `keccak256(abi.encodePacked(a,b))`.
-    }
+  function test_ShouldHaveANameMismatchInTheContracts() external {
+    // It should have a name mismatch in the contracts.
+  }
 }
 ";
 
     let actual = String::from_utf8(output.stdout).unwrap();
     assert!(actual.contains(expected));
-    assert!(actual.contains("1 issue fixed."));
+    assert!(actual.contains("2 issues fixed."));
+}
+
+#[test]
+fn fixes_base_contracts_and_imports() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("base_contracts_missing.tree");
+    let args = [
+        "--base",
+        "Base_Test",
+        "--import",
+        "Base_Test=test/Base.t.sol",
+        "--set-up",
+    ];
+
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(r#"contract doesn't inherit from "Base_Test""#));
+    assert!(stderr.contains(
+        r#"import directive `import {Base_Test} from "test/Base.t.sol";` is missing in .sol"#
+    ));
+    assert!(stderr.contains(r#"function "setUp" is missing in .sol"#));
+
+    let args = [&args[..], &["--fix", "--stdout"]].concat();
+    let output = cmd(&binary_path, "check", &tree_path, &args);
+    let expected = r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Base_Test} from "test/Base.t.sol";
+
+contract BaseContractsMissing is Base_Test {
+    function setUp() public override {
+        super.setUp();
+    }
+
+    function test_ShouldInheritFromTheBaseContract() external {
+        // it should inherit from the base contract
+    }
+}"#;
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains(expected), "actual: {actual}");
+    assert!(actual.contains("3 issues fixed."));
 }
 
 #[test]
@@ -246,6 +455,29 @@ contract MissingContract {
     assert!(actual.contains("1 issue fixed."));
 }
 
+#[test]
+fn fixes_contract_missing_keeps_header() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd
+        .join("tests")
+        .join("check")
+        .join("missing_contract_custom_header.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let expected = "// SPDX-License-Identifier: MIT
+pragma solidity >=0.8.22 <0.9.0;
+
+contract MissingContractCustomHeader {
+    function test_ShouldKeepTheFileHeader() external {
+        // It should keep the file header.
+    }
+}";
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains(expected), "actual: {actual}");
+}
+
 #[test]
 fn fixes_extra_codegen_tree() {
     let cwd = env::current_dir().unwrap();
@@ -260,6 +492,27 @@ fn fixes_extra_codegen_tree() {
     assert!(actual.contains("2 issues fixed."));
 }
 
+#[test]
+fn diffs_fixes_without_writing() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("check").join("extra_codegen_tree.tree");
+    let sol_path = tree_path.with_extension("t.sol");
+    let before = fs::read_to_string(&sol_path).unwrap();
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--diff"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(before, fs::read_to_string(&sol_path).unwrap());
+    let header = format!("--- {0}\n+++ {0}\n@@ ", sol_path.display());
+    assert!(actual.starts_with(&header), "actual: {actual}");
+    assert!(actual.contains("+  function test_ShouldNeverRevert() external {"));
+    assert!(stderr.contains("2 issues can be fixed."));
+}
+
 #[test]
 fn fixes_extra_fn_plus_wrong_order() {
     let cwd = env::current_dir().unwrap();
@@ -270,6 +523,8 @@ fn fixes_extra_fn_plus_wrong_order() {
     let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
     let actual = String::from_utf8(output.stdout).unwrap();
 
+    // Only the misplaced function moves. The extra function stays where it
+    // is.
     let expected = r"// SPDX-License-Identifier: UNLICENSED
 pragma solidity 0.8.0;
 
@@ -282,12 +537,6 @@ contract Foo {
         // it X
     }
 
-    // <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
-    // ==================== BULLOAK AUTOGENERATED SEPARATOR ====================
-    // >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
-    //    Code below this section could not be automatically moved by bulloak
-    // =========================================================================
-
     function test_WhenTheMethodIsCalledASecondTime() external {
         // it Z
     }
@@ -298,172 +547,395 @@ contract Foo {
 }
 
 #[test]
-fn fixes_invalid_structural_match() {
-    let binary_path = get_binary_path();
+fn fixes_renamed_functions() {
     let cwd = env::current_dir().unwrap();
-    let tree_path =
-        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
-
-    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
-    let actual = String::from_utf8(output.stdout).unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("renamed.tree");
 
-    let expected = r"// SPDX-License-Identifier: UNLICENSED
-pragma solidity 0.8.0;
+    // Without `--renames`, the renamed functions are reported as missing.
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("is missing in .sol"));
 
-contract CancelTest {
-    function test_RevertWhen_DelegateCalled() external {
-        // it should revert
-    }
+    let output = cmd(&binary_path, "check", &tree_path, &["--renames"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        r#"modifier "whenTheCallerIsAdmin" is named "whenTheCallerIsTheOwner" in the tree"#
+    ));
+    assert!(stderr.contains(
+        r#"function "test_RevertWhen_TheCallerIsNotTheOwner" is named "test_RevertWhen_TheCallerIsUnknown" in the tree"#
+    ));
+    assert!(!stderr.contains("is missing in .sol"));
 
-    modifier whenNotDelegateCalled() {
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--renames", "--fix", "--stdout"],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"contract Foo {
+    modifier whenTheCallerIsTheOwner() {
         _;
     }
 
-    function test_RevertGiven_TheIdReferencesANullStream() external whenNotDelegateCalled {
-        // it should revert
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsTheOwner {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(0);
     }
 
-    modifier givenTheIdDoesNotReferenceANullStream() {
-        _;
+    function test_WhenTheAmountIsNotZero() external whenTheCallerIsTheOwner {
+        // It should transfer.
+        foo.transfer(1);
     }
 
-    modifier givenTheStreamIsCold() {
-        _;
+    function test_RevertWhen_TheCallerIsUnknown() external {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(1);
     }
+}";
+    assert!(actual.contains(expected));
+    assert!(actual.contains("2 issues fixed."));
+}
 
-    function test_RevertGiven_TheStreamsStatusIsDEPLETED()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsCold
-    {
-        // it should revert
-    }
+#[test]
+fn fixes_mismatched_modifiers() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("modifiers.tree");
 
-    function test_RevertGiven_TheStreamsStatusIsCANCELED()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsCold
-    {
-        // it should revert
-    }
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        r#"modifiers of function "test_RevertWhen_TheAmountIsZero" don't match the tree"#
+    ));
+    assert!(stderr.contains(
+        r#"modifiers of function "test_WhenTheAmountIsNotZero" don't match the tree"#
+    ));
+    assert!(stderr.contains(
+        r#"consider applying "givenTheVaultIsOpen whenTheCallerIsTheOwner", in this order"#
+    ));
 
-    function test_RevertGiven_TheStreamsStatusIsSETTLED()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsCold
-    {
-        // it should revert
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"    function test_RevertWhen_TheAmountIsZero() external givenTheVaultIsOpen whenTheCallerIsTheOwner {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(0);
     }
 
-    modifier givenTheStreamIsWarm() {
-        _;
-    }
+    function test_WhenTheAmountIsNotZero() external givenTheVaultIsOpen whenTheCallerIsTheOwner {
+        // It should transfer.
+        foo.transfer(1);
+    }";
+    assert!(actual.contains(expected));
+    assert!(actual.contains("2 issues fixed."));
+}
 
-    modifier whenTheCallerIsAuthorized() {
-        _;
-    }
+#[test]
+fn fixes_drifted_comments() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("comments.tree");
 
-    function test_RevertGiven_TheStreamIsNotCancelable()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-    {
-        // it should revert
-    }
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        r#"comments of function "test_WhenTheCallerIsTheOwner" don't match the tree"#
+    ));
+    assert!(
+        stderr.contains(r#"the tree says "It should transfer the amount.""#)
+    );
 
-    function test_GivenTheSenderIsNotAContract()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-    {
-        // it should cancel the stream
-        // it should mark the stream as canceled
-    }
+    let output = cmd(&binary_path, "check", &tree_path, &["--skip-comments"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(
+        stdout.contains("All checks completed successfully! No issues found.")
+    );
 
-    modifier givenTheSenderIsAContract() {
-        _;
-    }
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"        foo.transfer(recipient, 1);
 
-    function test_GivenTheSenderDoesNotImplementTheHook()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-        givenTheSenderIsAContract
-    {
-        // it should cancel the stream
-        // it should mark the stream as canceled
-        // it should call the sender hook
-        // it should ignore the revert
-    }
+        // It should transfer the amount.
+        //     To the recipient.
+        // It should emit a {Transfer} event.
+        assertEq(foo.balanceOf(recipient), 1);";
+    assert!(actual.contains(expected));
+    assert!(actual.contains("1 issue fixed."));
+}
 
-    modifier givenTheSenderImplementsTheHook() {
-        _;
-    }
+#[test]
+fn matches_functions_by_anchor() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("anchors.tree");
 
-    function test_WhenTheSenderReverts()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-        givenTheSenderIsAContract
-        givenTheSenderImplementsTheHook
-    {
-        // it should cancel the stream
-        // it should mark the stream as canceled
-        // it should call the sender hook
-        // it should ignore the revert
-    }
+    // The renamed tests are matched by their anchor.
+    let output = cmd(&binary_path, "check", &tree_path, &["--anchors"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr
+        .contains(r#"function "test_RevertWhen_TheAmountIsZero" is missing"#));
+    assert!(stderr.contains("1 check failed"));
 
-    modifier whenTheSenderDoesNotRevert() {
-        _;
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(r#"function "test_WhenTheAmountIsNotZero" is missing"#)
+    );
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--anchors", "--fix", "--stdout"],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"    /// @custom:bulloak ab8881a2
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsTheOwner {
+        // It should revert.
     }
 
-    function test_WhenThereIsReentrancy()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-        givenTheSenderIsAContract
-        givenTheSenderImplementsTheHook
-        whenTheSenderDoesNotRevert
-    {
-        // it should cancel the stream
-        // it should mark the stream as canceled
-        // it should call the sender hook
-        // it should ignore the revert
+    /// @custom:bulloak 6d3924d5
+    function test_Transfers() external whenTheCallerIsTheOwner {";
+    assert!(actual.contains(expected));
+    assert!(actual.contains("1 issue fixed."));
+}
+
+#[test]
+fn reports_and_prunes_orphans() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("check").join("orphans.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &[]);
+    assert!(output.status.success());
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--orphans"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!output.status.success());
+    assert!(stderr.contains(r#"modifier "whenB" is missing in the tree"#));
+    assert!(stderr.contains(r#"function "test_WhenB" is missing in the tree"#));
+    assert!(stderr.contains("bulloak check --fix --prune <.tree files>"));
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(actual.contains("function test_WhenB() external whenB {"));
+    assert!(actual.contains("0 issues fixed."));
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--fix", "--prune", "--stdout"],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"contract Foo {
+    function test_WhenA() external {
+        // it X
     }
 
-    function test_WhenThereIsNoReentrancy()
-        external
-        whenNotDelegateCalled
-        givenTheIdDoesNotReferenceANullStream
-        givenTheStreamIsWarm
-        whenTheCallerIsAuthorized
-        givenTheSenderIsAContract
-        givenTheSenderImplementsTheHook
-        whenTheSenderDoesNotRevert
-    {
-        // it should cancel the stream
-        // it should mark the stream as canceled
-        // it should make the stream not cancelable
-        // it should update the refunded amount
-        // it should refund the sender
-        // it should call the sender hook
-        // it should emit a {MetadataUpdate} event
-        // it should emit a {CancelLockupStream} event
+    function helper() internal {}
+
+    function test_WhenC() external {
+        // it Z
     }
 }";
+    assert!(actual.contains(expected));
+    assert!(actual.contains("2 issues fixed."));
+
+    let output = cmd(
+        &binary_path,
+        "check",
+        &tree_path,
+        &["--fix", "--prune=comment", "--stdout"],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let expected = r"    // modifier whenB() {
+    //     _;
+    // }
+
+    // The B branch was removed from the tree.
+    // function test_WhenB() external whenB {
+    //     // it Y
+    // }";
+    assert!(actual.contains(expected));
+}
+
+#[test]
+fn fixes_invalid_structural_match() {
+    let binary_path = get_binary_path();
+    let cwd = env::current_dir().unwrap();
+    let tree_path =
+        cwd.join("tests").join("check").join("invalid_sol_structure.tree");
+
+    let output = cmd(&binary_path, "check", &tree_path, &["--fix", "--stdout"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    // The indentation of the file is kept.
+    let expected = r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract CancelTest {
+  function test_RevertWhen_DelegateCalled() external {
+    // it should revert
+  }
+
+  modifier whenNotDelegateCalled() {
+    _;
+  }
+
+  function test_RevertGiven_TheIdReferencesANullStream()
+    external
+    whenNotDelegateCalled
+  {
+    // it should revert
+  }
+
+  modifier givenTheIdDoesNotReferenceANullStream() {
+    _;
+  }
+
+  modifier givenTheStreamIsCold() {
+    _;
+  }
+
+  function test_RevertGiven_TheStreamsStatusIsDEPLETED()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsCold
+  {
+    // it should revert
+  }
+
+  function test_RevertGiven_TheStreamsStatusIsCANCELED()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsCold
+  {
+    // it should revert
+  }
+
+  function test_RevertGiven_TheStreamsStatusIsSETTLED()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsCold
+  {
+    // it should revert
+  }
+
+  modifier givenTheStreamIsWarm() {
+    _;
+  }
+
+  modifier whenTheCallerIsAuthorized() {
+    _;
+  }
+
+  function test_RevertGiven_TheStreamIsNotCancelable()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+  {
+    // it should revert
+  }
+
+  function test_GivenTheSenderIsNotAContract()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+  {
+    // it should cancel the stream
+    // it should mark the stream as canceled
+  }
+
+  modifier givenTheSenderIsAContract() {
+    _;
+  }
+
+  function test_GivenTheSenderDoesNotImplementTheHook()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+    givenTheSenderIsAContract
+  {
+    // it should cancel the stream
+    // it should mark the stream as canceled
+    // it should call the sender hook
+    // it should ignore the revert
+  }
+
+  modifier givenTheSenderImplementsTheHook() {
+    _;
+  }
+
+  function test_WhenTheSenderReverts()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+    givenTheSenderIsAContract
+    givenTheSenderImplementsTheHook
+  {
+    // it should cancel the stream
+    // it should mark the stream as canceled
+    // it should call the sender hook
+    // it should ignore the revert
+  }
+
+  modifier whenTheSenderDoesNotRevert() {
+    _;
+  }
+
+  function test_WhenThereIsReentrancy()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+    givenTheSenderIsAContract
+    givenTheSenderImplementsTheHook
+    whenTheSenderDoesNotRevert
+  {
+    // it should cancel the stream
+    // it should mark the stream as canceled
+    // it should call the sender hook
+    // it should ignore the revert
+  }
+
+  function test_WhenThereIsNoReentrancy()
+    external
+    whenNotDelegateCalled
+    givenTheIdDoesNotReferenceANullStream
+    givenTheStreamIsWarm
+    whenTheCallerIsAuthorized
+    givenTheSenderIsAContract
+    givenTheSenderImplementsTheHook
+    whenTheSenderDoesNotRevert
+  {
+    // it should cancel the stream
+    // it should mark the stream as canceled
+    // it should make the stream not cancelable
+    // it should update the refunded amount
+    // it should refund the sender
+    // it should call the sender hook
+    // it should emit a {MetadataUpdate} event
+    // it should emit a {CancelLockupStream} event
+  }
+}";
 
     assert!(actual.contains(expected));
     assert!(actual.contains("4 issues fixed."));
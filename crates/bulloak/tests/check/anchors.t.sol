@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract AnchorsTest {
+    /// @custom:bulloak 92be29b5
+    function test_NeverReverts() external {
+        // It should never revert.
+    }
+
+    modifier whenTheCallerIsTheOwner() {
+        _;
+    }
+
+    /// @custom:bulloak 6d3924d5
+    function test_Transfers() external whenTheCallerIsTheOwner {
+        // It should transfer.
+    }
+}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract BaseContractsMissing {
+    function test_ShouldInheritFromTheBaseContract() external {
+        // it should inherit from the base contract
+    }
+}
+
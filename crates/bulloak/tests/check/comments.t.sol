@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+    function test_WhenTheCallerIsTheOwner() external {
+        vm.expectEmit();
+        emit Transfer(owner, recipient, 1);
+        foo.transfer(recipient, 1);
+
+        // It should transfer the tokens.
+        //     To the recipient.
+        assertEq(foo.balanceOf(recipient), 1);
+    }
+}
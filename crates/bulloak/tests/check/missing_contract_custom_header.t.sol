@@ -0,0 +1,2 @@
+// SPDX-License-Identifier: MIT
+pragma solidity >=0.8.22 <0.9.0;
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+    modifier givenTheVaultIsOpen() {
+        _;
+    }
+
+    modifier whenTheCallerIsTheOwner() {
+        _;
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsTheOwner givenTheVaultIsOpen {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(0);
+    }
+
+    function test_WhenTheAmountIsNotZero() external givenTheVaultIsOpen {
+        // It should transfer.
+        foo.transfer(1);
+    }
+}
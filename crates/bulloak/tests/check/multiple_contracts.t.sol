@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+interface IVault {
+    function deposit() external;
+}
+
+library Amounts {
+    function double(uint256 amount) internal pure returns (uint256) {
+        return amount * 2;
+    }
+}
+
+contract Handler {
+    function deposit(IVault vault) external {
+        vault.deposit();
+    }
+}
+
+contract Token {
+    function test_Transfer_ShouldTransfer() external {
+        // It should transfer.
+    }
+}
+
+contract Vault {
+    function test_Deposit_ShouldDeposit() external {
+        // It should deposit.
+    }
+
+    function test_Withdraw_RevertWhen_TheVaultIsPaused() external {
+        // It should revert.
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+    function test_WhenA() external {
+        // it X
+    }
+
+    modifier whenB() {
+        _;
+    }
+
+    // The B branch was removed from the tree.
+    function test_WhenB() external whenB {
+        // it Y
+    }
+
+    function helper() internal {}
+
+    function test_WhenC() external {
+        // it Z
+    }
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+    modifier whenTheCallerIsAdmin() {
+        _;
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsAdmin {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(0);
+    }
+
+    function test_WhenTheAmountIsNotZero() external whenTheCallerIsAdmin {
+        // It should transfer.
+        foo.transfer(1);
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        // It should revert.
+        vm.expectRevert();
+        foo.transfer(1);
+    }
+}
@@ -0,0 +1,260 @@
+#![allow(missing_docs)]
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use common::get_binary_path;
+
+mod common;
+
+/// Runs a command from the `tests/<dir>` directory.
+///
+/// `tests/config` holds a `bulloak.toml` and a `foundry.toml`, while
+/// `tests/foundry` only holds a `foundry.toml` without a `[bulloak]` table,
+/// whose `mapped` profile sets the `test` directory.
+/// `tests/paths` holds a `bulloak.toml` that maps the trees in `specs` to
+/// Solidity files in `test/unit/concrete`. `tests/remappings` holds a Foundry
+/// project whose tests inherit modifiers from remapped libraries.
+fn cmd_in_dir(
+    dir: &str,
+    command: &str,
+    args: &[&str],
+    envs: &[(&str, &str)],
+) -> Output {
+    Command::new(get_binary_path())
+        .current_dir(fixture_dir(dir))
+        .arg(command)
+        .args(args)
+        .env_remove("FOUNDRY_PROFILE")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("should execute the command")
+}
+
+fn fixture_dir(dir: &str) -> PathBuf {
+    env::current_dir().unwrap().join("tests").join(dir)
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffold_reads_config_files() {
+    let output = cmd_in_dir("config", "scaffold", &["config.tree"], &[]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(fixture_dir("config").join("config.t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn env_vars_override_config_files() {
+    let output = cmd_in_dir(
+        "config",
+        "scaffold",
+        &["config.tree"],
+        &[("BULLOAK_LICENSE", "MIT")],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains("// SPDX-License-Identifier: MIT"));
+    assert!(actual.contains("pragma solidity >=0.8.22 <0.9.0;"));
+}
+
+#[test]
+fn cli_flags_override_config_files() {
+    let output = cmd_in_dir(
+        "config",
+        "scaffold",
+        &["config.tree", "-s", "0.8.25"],
+        &[("BULLOAK_SOLIDITY_VERSION", "0.8.24")],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains("// SPDX-License-Identifier: GPL-3.0-or-later"));
+    assert!(actual.contains("pragma solidity 0.8.25;"));
+}
+
+#[test]
+fn check_reads_config_files() {
+    let output = cmd_in_dir("config", "check", &["config.tree"], &[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+
+    let output = cmd_in_dir(
+        "config",
+        "check",
+        &["config.tree"],
+        &[("BULLOAK_BASE_CONTRACTS", "[Other]")],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(r#"contract doesn't inherit from "Other""#));
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffold_reads_foundry_settings() {
+    let output =
+        cmd_in_dir("foundry", "scaffold", &["foundry.tree", "-S"], &[]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(fixture_dir("foundry").join("foundry.t.sol"))
+            .unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn scaffold_reads_foundry_profile() {
+    let output = cmd_in_dir(
+        "foundry",
+        "scaffold",
+        &["foundry.tree"],
+        &[("FOUNDRY_PROFILE", "ci")],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains("pragma solidity 0.8.25;"));
+    assert!(actual.contains("\n  function test_WhenTheCallerIsTheOwner()"));
+}
+
+#[test]
+fn config_files_override_foundry_settings() {
+    let output = cmd_in_dir(
+        "foundry",
+        "scaffold",
+        &["foundry.tree"],
+        &[("BULLOAK_SOLIDITY_VERSION", "0.8.24")],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains("pragma solidity 0.8.24;"));
+}
+
+#[test]
+fn check_fixes_with_foundry_settings() {
+    let output = cmd_in_dir(
+        "foundry",
+        "check",
+        &["missing_function.tree", "--fix", "--stdout"],
+        &[],
+    );
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    assert!(actual.contains(
+        "  }\n\n  function test_WhenTheCallerIsTheOwner() external {\n    // it should work\n  }"
+    ));
+}
+
+#[test]
+fn check_resolves_inherited_modifiers() {
+    let output = cmd_in_dir("remappings", "check", &["test/vault.tree"], &[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("All checks completed successfully"));
+
+    // The project root is found from its subdirectories too.
+    let output = cmd_in_dir("remappings/test", "check", &["vault.tree"], &[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+}
+
+#[test]
+fn scaffold_skips_invalid_remappings() {
+    let output = cmd_in_dir(
+        "remappings",
+        "scaffold",
+        &["test/vault.tree"],
+        &[("FOUNDRY_PROFILE", "broken")],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("skipping invalid remapping"), "{stderr}");
+}
+
+#[test]
+fn scaffold_maps_sol_paths() {
+    let output =
+        cmd_in_dir("paths", "scaffold", &["-w", "specs/token/token.tree"], &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(stderr.contains("Skipped emitting"), "{stderr}");
+    assert!(stderr.contains(r#""test/unit/concrete/token/Token.t.sol""#));
+}
+
+#[test]
+fn check_maps_sol_paths() {
+    let output = cmd_in_dir("paths", "check", &["specs/token/token.tree"], &[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+
+    let output = cmd_in_dir("paths", "check", &["specs/token/vault.tree"], &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(
+        r#"function "test_WhenTheCallerIsTheOwner" is missing in .sol"#
+    ));
+    assert!(stderr.contains("`bulloak check --fix specs/token/vault.tree`"));
+
+    let output = cmd_in_dir(
+        "paths",
+        "check",
+        &["--fix", "--stdout", "specs/token/vault.tree"],
+        &[],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test/unit/concrete/token/Vault.t.sol"));
+    assert!(stdout.contains("function test_WhenTheCallerIsTheOwner()"));
+
+    let output = cmd_in_dir(
+        "paths",
+        "check",
+        &["--file-name", "{tree}.t.sol", "specs/token/token.tree"],
+        &[],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the tree is missing its matching Solidity file"));
+}
+
+#[test]
+fn check_maps_sol_paths_to_foundry_test_dir() {
+    let envs = [("FOUNDRY_PROFILE", "mapped")];
+    let output = cmd_in_dir("foundry", "check", &["foundry.tree"], &envs);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+
+    // `missing_function.t.sol` is only found next to its tree.
+    let output =
+        cmd_in_dir("foundry", "check", &["missing_function.tree"], &envs);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the tree is missing its matching Solidity file"));
+
+    // A configured mapping takes precedence.
+    let output = cmd_in_dir(
+        "foundry",
+        "check",
+        &["--map-dir", "test=test", "missing_function.tree"],
+        &envs,
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(r#"function "test_WhenTheCallerIsTheOwner""#));
+}
+
+#[test]
+fn errors_when_file_name_is_invalid() {
+    let output = cmd_in_dir(
+        "paths",
+        "scaffold",
+        &["--file-name", "{name}.t.sol", "specs/token/token.tree"],
+        &[],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("invalid file name"));
+}
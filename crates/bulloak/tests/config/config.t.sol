@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+pragma solidity >=0.8.22 <0.9.0;
+
+import {Base_Test} from "test/Base.t.sol";
+
+contract ConfigTest is Base_Test {
+    function setUp() public override {
+        super.setUp();
+    }
+
+    function test_WhenTheCallerIsTheOwner() external {
+        // it should work
+    }
+
+    function test_ShouldNeverRevert() external {
+        // it should never revert
+    }
+}
+
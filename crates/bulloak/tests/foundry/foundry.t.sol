@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.20;
+
+import { Test } from 'forge-std/Test.sol';
+
+contract FoundryTest is Test {
+  function test_RevertWhen_TheCallerIsNotTheOwner() external {
+    // it should revert
+    vm.skip(true);
+  }
+
+  function test_WhenTheCallerIsTheOwner() external {
+    // it should emit a {Transfer} event
+    vm.skip(true);
+  }
+}
+
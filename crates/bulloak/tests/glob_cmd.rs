@@ -31,6 +31,6 @@ fn check_expands_glob_internally() {
     assert!(!out.status.success());
 
     let stderr = String::from_utf8_lossy(&out.stderr);
-    assert!(stderr.contains("contract name mismatch"));
+    assert!(stderr.contains(r#"contract "MismatchedContractName" is missing"#));
     assert!(stderr.contains("contract name missing at tree root"));
 }
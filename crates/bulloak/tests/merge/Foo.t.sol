@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Foo {
+    function test_Withdraw_RevertWhen_TheVaultIsPaused() external {
+        // It should revert.
+    }
+
+    modifier whenTheVaultIsNotPaused() {
+        _;
+    }
+
+    function test_Withdraw_RevertWhen_TheCallerIsNotTheOwner() external whenTheVaultIsNotPaused {
+        // It should revert.
+    }
+
+    function test_Withdraw_WhenTheCallerIsTheOwner() external whenTheVaultIsNotPaused {
+        // It should withdraw.
+    }
+
+    function test_Deposit_RevertWhen_TheVaultIsPaused() external {
+        // It should revert.
+    }
+
+    function test_Deposit_WhenTheVaultIsNotPaused() external {
+        // It should deposit.
+    }
+}
+
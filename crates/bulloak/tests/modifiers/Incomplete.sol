@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+abstract contract Modifiers is Test {
+    modifier whenTheVaultIsPaused() {
+        _;
+    }
+}
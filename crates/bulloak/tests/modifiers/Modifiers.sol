@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+abstract contract Modifiers {
+    modifier givenTheCallerIsTheOwner() {
+        _;
+    }
+
+    modifier whenTheVaultIsPaused() {
+        _;
+    }
+}
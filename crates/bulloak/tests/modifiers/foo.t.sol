@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Modifiers} from "./Modifiers.sol";
+
+contract Foo is Modifiers {
+    function test_Bar_GivenTheCallerIsTheOwner() external givenTheCallerIsTheOwner {
+        // it should work
+    }
+
+    function test_Bar_RevertWhen_TheAmountIsZero() external givenTheCallerIsTheOwner {
+        // it should revert
+    }
+
+    function test_Bar_RevertWhen_TheCallerIsNotTheOwner() external {
+        // it should revert
+    }
+
+    function test_Baz_GivenTheCallerIsTheOwner() external givenTheCallerIsTheOwner {
+        // it should work
+    }
+
+    function test_Baz_RevertWhen_TheAmountIsZero() external givenTheCallerIsTheOwner {
+        // it should revert
+    }
+}
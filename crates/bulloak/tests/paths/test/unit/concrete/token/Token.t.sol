@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Token {
+    function test_Transfer_WhenTheSenderHasEnoughTokens() external {
+        // it should transfer the tokens
+    }
+
+    function test_Transfer_RevertWhen_TheSenderDoesNotHaveEnoughTokens() external {
+        // it should revert
+    }
+
+    function test_Approve_ShouldSetTheAllowance() external {
+        // it should set the allowance
+    }
+}
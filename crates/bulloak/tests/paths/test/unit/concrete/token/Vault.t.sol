@@ -0,0 +1,8 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Vault {
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        // it should revert
+    }
+}
@@ -0,0 +1,10 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Utils} from "utils/Utils.sol";
+
+abstract contract Base is Utils {
+    modifier whenTheVaultIsPaused() {
+        _;
+    }
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Base} from "base/Base.sol";
+
+contract VaultTest is Base {
+    function test_GivenTheCallerIsTheOwner() external givenTheCallerIsTheOwner {
+        // it should work
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external givenTheCallerIsTheOwner {
+        // it should revert
+    }
+
+    function test_RevertGiven_TheCallerIsTheOwner() external whenTheVaultIsPaused {
+        // it should revert
+    }
+}
@@ -7,6 +7,161 @@ use pretty_assertions::assert_eq;
 
 mod common;
 
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_split_trees() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("split");
+    let tree_path = tests_path.join("foo.tree");
+    let args =
+        ["--split", "-S", "--contract-name", "{Function}_Unit_Concrete_Test"];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    for file_name in ["bar.t.sol", "baz.t.sol", "foo.shared.sol"] {
+        let file_path = tests_path.join(file_name);
+        let expected = fs::read_to_string(&file_path).unwrap();
+        let marker = format!("{} {}", "-->".blue(), file_path.display());
+
+        assert!(actual.contains(&marker), "missing marker for {file_name}");
+        assert!(actual.contains(expected.trim()), "unexpected {file_name}");
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_merged_trees() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("merge");
+    let tree_path = tests_path.join("foo.withdraw.tree");
+    let deposit_path = tests_path.join("foo.deposit.tree");
+    let args =
+        [deposit_path.to_str().unwrap(), "--file-name", "{contract}.t.sol"];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected = fs::read_to_string(tests_path.join("Foo.t.sol")).unwrap();
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn merges_into_existing_files() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("merge");
+    let dir = env::temp_dir().join("bulloak_merges_into_existing_files");
+    fs::create_dir_all(&dir).unwrap();
+    for file_name in ["foo.withdraw.tree", "foo.deposit.tree"] {
+        fs::copy(tests_path.join(file_name), dir.join(file_name)).unwrap();
+    }
+
+    // An implemented test and a helper, without the deposit tests.
+    let expected = fs::read_to_string(tests_path.join("Foo.t.sol")).unwrap();
+    let (withdraw, deposit) =
+        expected.split_once("    function test_Deposit").unwrap();
+    let existing = withdraw.trim_end().replace(
+        "        // It should withdraw.\n",
+        "        // It should withdraw.\n        foo.withdraw();\n",
+    ) + "\n\n    function helper() internal {}\n}\n";
+    let sol_path = dir.join("Foo.t.sol");
+    fs::write(&sol_path, &existing).unwrap();
+
+    let deposit_path = dir.join("foo.deposit.tree");
+    let args = [
+        deposit_path.to_str().unwrap(),
+        "-w",
+        "--merge",
+        "--file-name",
+        "{contract}.t.sol",
+    ];
+    cmd(&binary_path, "scaffold", &dir.join("foo.withdraw.tree"), &args);
+    let actual = fs::read_to_string(&sol_path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // The existing code is kept as is, and the deposit tests are added after
+    // the withdraw tests.
+    let (kept, _) = existing.split_once("\n\n    function helper").unwrap();
+    let deposit = deposit.trim_end().trim_end_matches('}').trim_end();
+    let merged = format!(
+        "{kept}\n\n    function test_Deposit{deposit}\n\n    function \
+         helper() internal {{}}\n}}"
+    );
+    assert_eq!(merged, actual.trim_end());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn diffs_without_writing() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("merge");
+    let dir = env::temp_dir().join("bulloak_diffs_without_writing");
+    fs::create_dir_all(&dir).unwrap();
+    let tree_path = dir.join("foo.withdraw.tree");
+    fs::copy(tests_path.join("foo.withdraw.tree"), &tree_path).unwrap();
+
+    let args = ["-w", "--diff", "--file-name", "{contract}.t.sol"];
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let sol_path = dir.join("Foo.t.sol");
+    let written = sol_path.exists();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    assert!(!written);
+    assert!(!output.status.success());
+    let header =
+        format!("--- /dev/null\n+++ {}\n@@ -0,0 +1,", sol_path.display());
+    assert!(actual.starts_with(&header), "actual: {actual}");
+    assert!(actual.contains("+    function test_RevertWhen_TheVaultIsPaused()"));
+
+    // Nothing is missing from an up to date file.
+    let deposit_path = tests_path.join("foo.deposit.tree");
+    let args = [
+        deposit_path.to_str().unwrap(),
+        "-w",
+        "--merge",
+        "--dry-run",
+        "--file-name",
+        "{contract}.t.sol",
+    ];
+    let tree_path = tests_path.join("foo.withdraw.tree");
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    assert!(output.status.success());
+    assert_eq!("", String::from_utf8(output.stdout).unwrap());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_modifiers_file() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("modifiers");
+    let tree_path = tests_path.join("foo.tree");
+    let modifiers_path = tests_path.join("Modifiers.sol");
+    let args = [
+        tests_path.join("qux.tree").to_string_lossy().into_owned(),
+        "--modifiers-file".to_owned(),
+        modifiers_path.to_string_lossy().into_owned(),
+    ];
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    for file_name in ["foo.t.sol", "qux.t.sol", "Modifiers.sol"] {
+        let file_path = tests_path.join(file_name);
+        let expected = fs::read_to_string(&file_path).unwrap();
+        let marker = format!("{} {}", "-->".blue(), file_path.display());
+
+        assert!(actual.contains(&marker), "missing marker for {file_name}");
+        assert!(actual.contains(expected.trim()), "unexpected {file_name}");
+    }
+    // The modifiers are only emitted once.
+    assert_eq!(actual.matches("modifier givenTheCallerIsTheOwner").count(), 1);
+}
+
 #[cfg(not(target_os = "windows"))]
 #[test]
 fn scaffolds_trees() {
@@ -17,10 +172,15 @@ fn scaffolds_trees() {
         "basic.tree",
         "complex.tree",
         "multiple_roots.tree",
+        "multiple_contracts.tree",
         "removes_invalid_title_chars.tree",
         "hash_pair.tree",
         "revert_when.tree",
         "spurious_comments.tree",
+        "fuzz.tree",
+        "invariant.tree",
+        "invariant_multiple_roots.tree",
+        "front_matter.tree",
     ];
 
     for tree_name in trees {
@@ -94,6 +254,193 @@ fn scaffolds_trees_with_skip_modifiers() {
     }
 }
 
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_with_expect_emit() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tests_path = cwd.join("tests").join("scaffold");
+    let trees = ["complex.tree", "emit_events.tree"];
+
+    for tree_name in trees {
+        let tree_path = tests_path.join(tree_name);
+        let output = cmd(&binary_path, "scaffold", &tree_path, &["-E"]);
+        let actual = String::from_utf8(output.stdout).unwrap();
+
+        let mut trimmed_extension = tree_path.clone();
+        trimmed_extension.set_extension("");
+
+        let mut output_file_str = trimmed_extension.into_os_string();
+        output_file_str.push("_expect_emit");
+
+        let mut output_file: std::path::PathBuf = output_file_str.into();
+        output_file.set_extension("t.sol");
+
+        let expected = fs::read_to_string(output_file).unwrap();
+
+        // We trim here because we don't care about ending newlines.
+        assert_eq!(expected.trim(), actual.trim());
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_with_action_rules() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("action_rules.tree");
+    let args = [
+        "--action-rule",
+        r"^it should transfer (\d+) tokens=>token.transfer(users.recipient, $1);",
+        "--action-rule",
+        "^it should transfer the tokens=>token.transfer(users.recipient, amount);",
+        "--action-rule",
+        r#"^it should update the balance of the (?P<who>\w+)=>assertEq(token.balanceOf(users.${who}), amount, "balance");"#,
+    ];
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_with_custom_header() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("custom_header.tree");
+    let args = ["-s", ">=0.8.22 <0.9.0", "--license", "MIT"];
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn errors_when_solidity_version_is_invalid() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("custom_header.tree");
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["-s", "latest"]);
+    let actual = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(actual.contains("invalid solidity version"));
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn scaffolds_trees_with_anchors() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("scaffold").join("anchors.tree");
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &["--anchors"]);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn scaffolds_trees_with_base_contracts() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("base_contracts.tree");
+    let args = [
+        "--base",
+        "Base_Test",
+        "--import",
+        "Base_Test=test/Base.t.sol",
+        "--set-up",
+    ];
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn scaffolds_invariant_trees_with_several_base_contracts() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd
+        .join("tests")
+        .join("scaffold")
+        .join("base_contracts_invariant.tree");
+    let args = [
+        "--base",
+        "Base_Test",
+        "--base",
+        "Utils",
+        "--import",
+        "Base_Test,Utils=test/Base.t.sol",
+        "--set-up",
+    ];
+
+    let output = cmd(&binary_path, "scaffold", &tree_path, &args);
+    let actual = String::from_utf8(output.stdout).unwrap();
+
+    let expected =
+        fs::read_to_string(tree_path.with_extension("t.sol")).unwrap();
+
+    // We trim here because we don't care about ending newlines.
+    assert_eq!(expected.trim(), actual.trim());
+}
+
+#[test]
+fn errors_when_import_is_invalid() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("base_contracts.tree");
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["--import", "Test="]);
+    let actual = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(actual.contains("invalid import"));
+}
+
+#[test]
+fn errors_when_action_rule_is_invalid() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path =
+        cwd.join("tests").join("scaffold").join("action_rules.tree");
+
+    let output =
+        cmd(&binary_path, "scaffold", &tree_path, &["--action-rule", "(=>x"]);
+    let actual = String::from_utf8(output.stderr).unwrap();
+
+    assert!(!output.status.success());
+    assert!(actual.contains("invalid action rule"));
+}
+
 #[cfg(not(target_os = "windows"))]
 #[test]
 fn skips_trees_when_file_exists() {
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract ActionRulesTest {
+    function test_ShouldTransferTheTokens() external {
+        // it should transfer the tokens
+        token.transfer(users.recipient, amount);
+    }
+
+    function test_WhenTheRecipientIsAContract() external {
+        // it should transfer 100 tokens
+        token.transfer(users.recipient, 100);
+        // it should update the balance of the recipient
+        assertEq(token.balanceOf(users.recipient), amount, "balance");
+    }
+}
+
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract AnchorsTest {
+    /// @custom:bulloak 92be29b5
+    function test_ShouldNeverRevert() external {
+        // It should never revert.
+    }
+
+    modifier whenTheCallerIsTheOwner() {
+        _;
+    }
+
+    /// @custom:bulloak ab8881a2
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsTheOwner {
+        // It should revert.
+    }
+
+    /// @custom:bulloak 6d3924d5
+    function test_WhenTheAmountIsNotZero() external whenTheCallerIsTheOwner {
+        // It should transfer.
+    }
+}
+
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Base_Test} from "test/Base.t.sol";
+
+contract Vault is Base_Test {
+    function setUp() public override {
+        super.setUp();
+    }
+
+    function test_Deposit_RevertWhen_TheAmountIsZero() external {
+        // it should revert
+    }
+
+    function test_Deposit_ShouldMintShares() external {
+        // it should mint shares
+    }
+
+    function test_Withdraw_ShouldBurnShares() external {
+        // it should burn shares
+    }
+}
+
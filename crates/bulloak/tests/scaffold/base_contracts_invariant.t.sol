@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+import {Base_Test, Utils} from "test/Base.t.sol";
+
+contract Vault is Base_Test, Utils {
+    function setUp() public override(Base_Test, Utils) {
+        super.setUp();
+    }
+
+    function test_Deposit_ShouldWork() external {
+        // it should work
+    }
+}
+
+contract VaultHandler is Test {
+    function whenAUserDeposits(uint256 assets) external {
+        assets = uint256(bound(assets, type(uint256).min, type(uint256).max));
+        // it should mint shares
+    }
+}
+
+contract VaultInvariantTest is Base_Test, Utils {
+    VaultHandler internal handler;
+
+    function setUp() public override(Base_Test, Utils) {
+        super.setUp();
+        handler = new VaultHandler();
+        targetContract(address(handler));
+    }
+
+    function invariant_ShouldHold() external {
+        // it should hold
+    }
+}
+
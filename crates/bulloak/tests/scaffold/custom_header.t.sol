@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: MIT
+pragma solidity >=0.8.22 <0.9.0;
+
+contract CustomHeaderTest {
+    function test_WhenTheHeaderIsConfigured() external {
+        // it should use the configured license
+    }
+
+    function test_ShouldUseTheConfiguredPragma() external {
+        // it should use the configured pragma
+    }
+}
+
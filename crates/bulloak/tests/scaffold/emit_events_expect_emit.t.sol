@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract EmitEventsTest is Test {
+    function test_ShouldEmitATransferEvent() external {
+        // it should emit a {Transfer} event
+        // TODO: Set the `Transfer` event arguments.
+        vm.expectEmit();
+        emit Transfer();
+    }
+
+    function test_WhenTheStreamIsCanceled() external {
+        // it should cancel the stream
+        // it should emit a {CancelLockupStream} and a {MetadataUpdate} event
+        // TODO: Set the `CancelLockupStream` event arguments.
+        vm.expectEmit();
+        emit CancelLockupStream();
+        // TODO: Set the `MetadataUpdate` event arguments.
+        vm.expectEmit();
+        emit MetadataUpdate();
+    }
+}
+
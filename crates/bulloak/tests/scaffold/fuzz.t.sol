@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract FuzzTest is Test {
+    function testFuzz_ShouldAcceptAnyRecipient(address to) external {
+        // it should accept any recipient
+    }
+
+    modifier whenAmountIsWithinBounds() {
+        _;
+    }
+
+    function testFuzz_RevertWhen_TheCallerIsNotTheOwner(uint128 amount) external whenAmountIsWithinBounds {
+        amount = uint128(bound(amount, type(uint128).min, type(uint128).max));
+        // it should revert
+    }
+
+    function testFuzz_WhenTheCallerIsTheOwner(uint128 amount, address caller, string memory memo, uint40 deadline)
+        external
+        whenAmountIsWithinBounds
+    {
+        amount = uint128(bound(amount, type(uint128).min, type(uint128).max));
+        deadline = uint40(bound(deadline, type(uint40).min, type(uint40).max));
+        // it should transfer the amount
+        // it should record the deadline
+    }
+}
+
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract VaultHandler is Test {
+    function whenAUserDeposits(uint256 assets) external {
+        assets = uint256(bound(assets, type(uint256).min, type(uint256).max));
+        // it should mint shares
+    }
+
+    function whenAUserWithdraws(uint128 shares) external {
+        shares = uint128(bound(shares, type(uint128).min, type(uint128).max));
+        // it should burn shares
+    }
+
+    function whenTheUserIsTheOwner(uint128 shares) external {
+        shares = uint128(bound(shares, type(uint128).min, type(uint128).max));
+        // it should not charge a fee
+    }
+}
+
+contract VaultInvariantTest is Test {
+    VaultHandler internal handler;
+
+    function setUp() external {
+        handler = new VaultHandler();
+        targetContract(address(handler));
+    }
+
+    function invariant_ShouldNeverHaveMoreSharesThanAssets() external {
+        // it should never have more shares than assets
+    }
+
+    function invariant_ShouldKeepTheTotalSupplyEqualToTheSumOfBalances() external {
+        // it should keep the total supply equal to the sum of balances
+    }
+}
+
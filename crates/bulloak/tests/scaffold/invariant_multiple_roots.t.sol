@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract Vault is Test {
+    function test_Deposit_RevertWhen_TheAmountIsZero() external {
+        // it should revert
+    }
+
+    function test_Withdraw_ShouldBurnShares() external {
+        // it should burn shares
+    }
+}
+
+contract VaultHandler is Test {
+    function whenAUserDeposits() external {
+        // it should mint shares
+    }
+}
+
+contract VaultInvariantTest is Test {
+    VaultHandler internal handler;
+
+    function setUp() external {
+        handler = new VaultHandler();
+        targetContract(address(handler));
+    }
+
+    function invariant_ShouldNeverHaveMoreSharesThanAssets() external {
+        // it should never have more shares than assets
+    }
+}
+
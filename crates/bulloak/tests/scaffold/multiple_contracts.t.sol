@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract Vault {
+    function test_Deposit_ShouldDeposit() external {
+        // It should deposit.
+    }
+
+    function test_Withdraw_RevertWhen_TheVaultIsPaused() external {
+        // It should revert.
+    }
+}
+
+contract Token {
+    function test_Transfer_ShouldTransfer() external {
+        // It should transfer.
+    }
+}
+
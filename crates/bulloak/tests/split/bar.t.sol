@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Foo_Shared_Test} from "./foo.shared.sol";
+
+contract Bar_Unit_Concrete_Test is Foo_Shared_Test {
+    function test_GivenTheCallerIsTheOwner() external givenTheCallerIsTheOwner {
+        // it should work
+        vm.skip(true);
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external givenTheCallerIsTheOwner {
+        // it should revert
+        vm.skip(true);
+    }
+
+    function test_RevertWhen_TheCallerIsNotTheOwner() external {
+        // it should revert
+        vm.skip(true);
+    }
+}
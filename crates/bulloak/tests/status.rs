@@ -0,0 +1,60 @@
+#![allow(missing_docs)]
+use std::env;
+
+use common::{cmd, get_binary_path};
+use owo_colors::OwoColorize;
+use pretty_assertions::assert_eq;
+
+mod common;
+
+#[test]
+fn reports_implementation_status() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("status").join("status.tree");
+
+    let output = cmd(&binary_path, "status", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!("", stderr);
+    let expected = format!(
+        r"    Status::bar
+[ ] ├── When a is zero
+    │   └── It should revert.
+    │       └── Because reasons.
+    └── When a is not zero
+{}     ├── When b is zero
+        │   └── It should skip.
+{}     └── When b is one
+            └── It should work.
+
+    Status::baz
+{} ├── It should be implemented.
+{} └── It should be missing.
+2/5 tests implemented (40%), 1 skipped, 1 stub, 1 missing
+",
+        "[-]".yellow(),
+        "[x]".green(),
+        "[x]".green(),
+        "[!]".red(),
+    );
+    let actual = stdout.split_once('\n').unwrap().1;
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn fails_when_the_status_cannot_be_reported() {
+    let cwd = env::current_dir().unwrap();
+    let binary_path = get_binary_path();
+    let tree_path = cwd.join("tests").join("status").join("no_tests.tree");
+
+    let output = cmd(&binary_path, "status", &tree_path, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr.contains("the tree is missing its matching Solidity file"));
+    assert!(stderr.contains("could not report the status of"));
+    assert_eq!("", stdout);
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract Status is Test {
+    function test_Bar_RevertWhen_AIsZero() external {
+        // It should revert.
+        //     Because reasons.
+    }
+
+    modifier whenAIsNotZero() {
+        _;
+    }
+
+    function test_Bar_WhenBIsZero() external whenAIsNotZero {
+        // It should skip.
+        vm.skip(true);
+    }
+
+    function test_Bar_WhenBIsOne() external whenAIsNotZero {
+        // It should work.
+        status.bar(1);
+    }
+
+    function test_Baz_ShouldBeImplemented() external {
+        // It should be implemented.
+        assertTrue(status.baz());
+    }
+}
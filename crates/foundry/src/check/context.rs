@@ -2,25 +2,37 @@
 
 use std::{
     collections::HashSet,
-    ffi::OsStr,
     fs,
+    ops::Range,
     path::{Path, PathBuf},
 };
 
 use forge_fmt::{
-    format, parse,
+    format,
     solang_ext::{CodeLocationExt, SafeUnwrap},
-    Comments, FormatterConfig, FormatterError, InlineConfig, Parsed,
+    Comments, FormatterError, InlineConfig,
 };
 use solang_parser::pt::{self, ContractDefinition, ContractPart, SourceUnit};
 
-use super::{location::Location, violation::ViolationKind};
+use super::{
+    location::Location,
+    rules::{
+        comments::{action_matches, expected_comments},
+        Checker, StructuralMatcher,
+    },
+    violation::ViolationKind,
+};
 use crate::{
     check::{pretty::Pretty, violation::Violation},
-    config::Config,
+    config::{self, Config, Prune},
+    constants::INTERNAL_DEFAULT_INDENTATION,
     hir::{self, Hir},
-    scaffold::emitter::Emitter,
-    sol::{self, find_contract, find_matching_fn},
+    layout::{self, TestFile},
+    scaffold::{self, emitter::Emitter},
+    sol::{
+        self, action_comments, applied_modifiers, match_contracts, parse,
+        FnIndex, Parsed,
+    },
 };
 
 /// The context in which rule-checking happens.
@@ -44,7 +56,7 @@ pub struct Context {
     /// The abstract syntax tree of the Solidity file.
     pub pt: SourceUnit,
     /// The comments present in the Solidity file.
-    pub comments: Comments,
+    pub comments: Vec<pt::Comment>,
     /// The config passed to `bulloak check`.
     pub cfg: Config,
 }
@@ -54,20 +66,148 @@ impl Context {
     ///
     /// This structure contains everything necessary to perform checks between
     /// trees and Solidity files.
+    ///
+    /// If the tree maps to several Solidity files, e.g. when splitting trees,
+    /// this is the context of the first one. See [`Context::for_tree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a violation if the tree or its Solidity file can't be read or
+    /// parsed.
+    ///
+    /// # Panics
+    ///
+    /// Never panics, since a tree always maps to a Solidity file.
     pub fn new(tree: PathBuf, cfg: &Config) -> Result<Self, Violation> {
-        let tree_path_cow = tree.to_string_lossy();
-        let tree_contents = try_read_to_string(&tree)?;
-        let hir = crate::hir::translate(&tree_contents, &Config::default())
-            .map_err(|e| {
-                Violation::new(
+        Self::for_tree(tree, cfg)
+            .into_iter()
+            .next()
+            .expect("a tree maps to at least one Solidity file")
+    }
+
+    /// Creates a `Context` for each Solidity file of a tree.
+    ///
+    /// Settings in the front matter of the tree override `cfg`. See
+    /// [`crate::layout::test_files`].
+    #[must_use]
+    pub fn for_tree(
+        tree: PathBuf,
+        cfg: &Config,
+    ) -> Vec<Result<Self, Violation>> {
+        Self::for_trees(&[tree], cfg)
+    }
+
+    /// Creates a `Context` for each Solidity file of a set of trees.
+    ///
+    /// Trees that map to the same Solidity file are checked against it
+    /// together, and their violations are reported against the first of
+    /// them. See [`layout::merged_test_files`].
+    #[must_use]
+    pub fn for_trees(
+        trees: &[PathBuf],
+        cfg: &Config,
+    ) -> Vec<Result<Self, Violation>> {
+        let mut ctxs = vec![];
+        let mut contents = Vec::with_capacity(trees.len());
+        for tree in trees {
+            match try_read_to_string(tree) {
+                Ok(text) => contents.push((tree.clone(), text)),
+                Err(violation) => ctxs.push(Err(violation)),
+            }
+        }
+
+        let results = layout::merged_test_files(&contents, cfg);
+        for ((tree, _), result) in contents.iter().zip(results) {
+            match result {
+                Ok(files) => ctxs.extend(
+                    files
+                        .into_iter()
+                        .map(|file| Self::for_test_file(tree.clone(), file)),
+                ),
+                Err(e) => ctxs.push(Err(Violation::new(
                     ViolationKind::ParsingFailed(e),
-                    Location::File(tree_path_cow.into_owned()),
-                )
-            })?;
+                    Location::File(tree.to_string_lossy().into_owned()),
+                ))),
+            }
+        }
+
+        ctxs
+    }
+
+    /// Creates the `Context` of the project-wide modifiers file of a set of
+    /// trees, if modifiers are emitted to one. See
+    /// [`Config::modifiers_file`].
+    ///
+    /// The order of the modifiers in the file is up to the user, so missing
+    /// modifiers are only expected after the existing ones. Trees that can't
+    /// be read or parsed are skipped, since their own contexts report them.
+    pub fn for_modifiers(
+        trees: &[PathBuf],
+        cfg: &Config,
+    ) -> Option<Result<Self, Violation>> {
+        let mut first_tree = None;
+        let mut files = vec![];
+        for tree in trees {
+            let Ok(text) = fs::read_to_string(tree) else { continue };
+            let Ok(tree_files) = layout::test_files(tree, &text, cfg) else {
+                continue;
+            };
+            let first = files.len();
+            files.extend(tree_files);
+            if layout::modifiers_file(&files[first..], cfg).is_some() {
+                first_tree.get_or_insert(tree);
+            }
+        }
+
+        let file = layout::modifiers_file(&files, cfg)?;
+        let tree = first_tree?.clone();
+        Some(Self::for_test_file(tree, file).map(Self::with_sol_order))
+    }
+
+    /// Orders the functions of the HIR after their matches in the Solidity
+    /// file. Functions without a match come last.
+    fn with_sol_order(mut self) -> Self {
+        let Some((_, contract_sol)) = self.contract_pair(None) else {
+            return self;
+        };
+
+        let fn_index = FnIndex::new(&contract_sol, &self.src);
+        if let Hir::Root(root) = &mut self.hir {
+            for child in &mut root.children {
+                let Hir::Contract(contract) = child else { continue };
+                contract.children.sort_by_cached_key(|child| match child {
+                    Hir::Function(f) => fn_index
+                        .find(f)
+                        .map_or(usize::MAX, |(sol_idx, _)| sol_idx),
+                    _ => usize::MAX,
+                });
+            }
+        }
+
+        self
+    }
+
+    /// Creates the `Context` of one of the Solidity files of a tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns a violation if the Solidity file is missing or can't be
+    /// parsed.
+    pub fn for_test_file(
+        tree: PathBuf,
+        file: TestFile,
+    ) -> Result<Self, Violation> {
+        let TestFile { path: sol, hir, cfg, .. } = file;
+        if !sol.exists() {
+            let filename = tree.to_string_lossy().into_owned();
+            return Err(Violation::new(
+                ViolationKind::SolidityFileMissing(filename.clone()),
+                Location::File(filename),
+            ));
+        }
 
-        let sol = get_path_with_ext(&tree, "t.sol")?;
         let src = try_read_to_string(&sol)?;
-        let parsed = forge_fmt::parse(&src).map_err(|_| {
+        let parsed = parse(&src).map_err(|_| {
             let sol_filename = sol.to_string_lossy().into_owned();
             Violation::new(
                 ViolationKind::ParsingFailed(anyhow::anyhow!(
@@ -79,7 +219,45 @@ impl Context {
 
         let pt = parsed.pt.clone();
         let comments = parsed.comments;
-        Ok(Context { tree, hir, sol, src, pt, comments, cfg: cfg.clone() })
+        Ok(Context { tree, hir, sol, src, pt, comments, cfg })
+    }
+
+    /// Updates this `Context` with `source`, the fixed contents of the
+    /// Solidity file.
+    ///
+    /// Returns an error with the pretty-printed diagnostics if `source` isn't
+    /// valid Solidity.
+    fn reparse(self, source: &str) -> anyhow::Result<Context> {
+        let filename = self.sol.to_string_lossy();
+        let parsed = parse(source).map_err(|diagnostics| {
+            let full = diagnostics
+                .into_iter()
+                .map(|d| Pretty::new(&d, &filename, source).to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::anyhow!(full)
+        })?;
+        Ok(self.update_from_parsed(parsed))
+    }
+
+    /// Returns the contract of the tree named `contract`, or its first
+    /// contract if `None`, along with its matching Solidity contract.
+    ///
+    /// See [`sol::match_contracts`].
+    #[must_use]
+    pub fn contract_pair(
+        &self,
+        contract: Option<&str>,
+    ) -> Option<(hir::ContractDefinition, Box<ContractDefinition>)> {
+        match_contracts(&self.hir, &self.pt).into_iter().find_map(
+            |(contract_hir, contract_sol)| {
+                if contract.is_some_and(|name| contract_hir.identifier != name)
+                {
+                    return None;
+                }
+                Some((contract_hir.clone(), contract_sol?))
+            },
+        )
     }
 
     /// Updates this `Context` with the result of parsing a Solidity file.
@@ -91,6 +269,47 @@ impl Context {
         self
     }
 
+    /// Adds the contracts, modifiers and tests of the tree that are missing
+    /// from the Solidity file, in spec order, leaving everything else as is.
+    ///
+    /// Returns `None` if nothing is missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the resulting Solidity code can't be parsed.
+    pub fn merge(self) -> anyhow::Result<Option<Self>> {
+        let violations: Vec<_> = StructuralMatcher::check(&self)
+            .into_iter()
+            .filter_map(|violation| match violation.kind {
+                ViolationKind::ContractMissing(_)
+                | ViolationKind::MatchingFunctionMissing(_, _) => {
+                    Some(violation)
+                }
+                // Existing code is left as is, so the function is added
+                // under its new name.
+                ViolationKind::FunctionRenamed(_, fn_hir, index) => {
+                    Some(Violation {
+                        kind: ViolationKind::MatchingFunctionMissing(
+                            fn_hir, index,
+                        ),
+                        ..violation
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        if violations.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ctx = self;
+        for violation in violations {
+            ctx = violation.fix(ctx)?;
+        }
+
+        Ok(Some(ctx))
+    }
+
     /// Updates the context with a formatted representation of the Solidity
     /// file.
     pub fn fmt(self) -> anyhow::Result<String, FormatterError> {
@@ -100,11 +319,11 @@ impl Context {
             forge_fmt::Parsed {
                 src: &self.src,
                 pt: self.pt,
-                comments: self.comments,
+                comments: Comments::new(self.comments, &self.src),
                 inline_config: InlineConfig::default(),
                 invalid_inline_config_items: Vec::default(),
             },
-            FormatterConfig::default(),
+            self.cfg.formatter_config,
         )?;
 
         Ok(formatted)
@@ -118,6 +337,10 @@ impl Context {
     /// function definition string using an `Emitter`, and then inserts this
     /// string into the specified source code at a given offset.
     ///
+    /// The function is formatted with the `forge fmt` settings of the config
+    /// and indented like the other members of the contract it is inserted
+    /// into. The rest of the source is left as is.
+    ///
     /// # Arguments
     /// * `function` - A reference to the HIR `FunctionDefinition` to be
     ///   inserted.
@@ -128,35 +351,37 @@ impl Context {
         function: &hir::FunctionDefinition,
         offset: usize,
     ) {
-        let cfg = &Config::default();
-        let f = &Hir::Function(function.clone());
-        let function = Emitter::new(cfg).emit(f);
-        self.src = format!(
-            "{}\n\n{}{}",
-            &self.src[..offset],
-            function.trim_end(),
-            &self.src[offset..]
-        );
-    }
-}
-
-fn get_path_with_ext(
-    path: impl AsRef<Path>,
-    ext: impl AsRef<OsStr>,
-) -> Result<PathBuf, Violation> {
-    let path = path.as_ref();
-    let mut sol = path.to_path_buf();
-    sol.set_extension(ext);
+        let contract = self.pt.0.iter().find_map(|part| match part {
+            pt::SourceUnitPart::ContractDefinition(contract)
+                if contract.loc.start() < offset
+                    && offset < contract.loc.end() =>
+            {
+                Some(contract)
+            }
+            _ => None,
+        });
+        let indentation = if let Some(contract) = contract {
+            Indentation::of(contract, &self.src, &self.cfg)
+        } else {
+            let unit = " ".repeat(self.cfg.formatter_config.tab_width);
+            Indentation { contract: String::new(), member: unit.clone(), unit }
+        };
+        let function = emit_member(function, &indentation, &self.cfg);
 
-    if !sol.exists() {
-        let filename = path.to_string_lossy().into_owned();
-        return Err(Violation::new(
-            ViolationKind::SolidityFileMissing(filename.clone()),
-            Location::File(filename),
-        ));
+        let (before, after) = self.src.split_at(offset);
+        self.src = if !before.ends_with('{') {
+            format!("{before}\n\n{function}{after}")
+        } else if after.trim_start().starts_with('}') {
+            // The contract is empty.
+            format!(
+                "{before}\n{function}\n{}{}",
+                indentation.contract,
+                after.trim_start()
+            )
+        } else {
+            format!("{before}\n{function}\n{after}")
+        };
     }
-
-    Ok(sol)
 }
 
 fn try_read_to_string(path: impl AsRef<Path>) -> Result<String, Violation> {
@@ -170,19 +395,35 @@ fn try_read_to_string(path: impl AsRef<Path>) -> Result<String, Violation> {
 }
 
 impl Context {
-    pub(crate) fn fix_contract_missing(self) -> anyhow::Result<Context> {
-        let pt = sol::Translator::new(&Config::default()).translate(&self.hir);
-        let source = sol::Formatter::new().emit(pt.clone());
-        let filename = self.sol.to_string_lossy();
-        let parsed = parse(&source).map_err(|diagnostics| {
-            let full = diagnostics
-                .into_iter()
-                .map(|d| Pretty::new(&d, &filename, &source).to_string())
-                .collect::<Vec<_>>()
-                .join("\n");
-            anyhow::anyhow!(full)
-        })?;
-        Ok(self.update_from_parsed(parsed))
+    pub(crate) fn fix_contract_missing(
+        self,
+        name: &str,
+    ) -> anyhow::Result<Context> {
+        // Only the missing contract is emitted.
+        let children = self
+            .hir
+            .contracts()
+            .filter(|contract| contract.identifier == name)
+            .cloned()
+            .map(Hir::Contract)
+            .collect();
+        let hir = Hir::Root(hir::Root { children });
+        let pt = sol::Translator::new(&self.cfg).translate(&hir);
+        let source = sol::Formatter::new(&self.cfg).emit(pt);
+        let source = scaffold::fmt(&source, &self.cfg).unwrap_or(source);
+        // Keep the existing file, e.g. its license, pragma directive and other
+        // contracts, and append the missing contract to it.
+        let source = append_contracts(&self.src, &self.pt, &source)
+            .unwrap_or_else(|| {
+                // A file without a header is kept only if it is valid
+                // Solidity.
+                if self.src.trim().is_empty() || parse(&self.src).is_err() {
+                    source
+                } else {
+                    format!("{}\n\n{}", source.trim_end(), self.src)
+                }
+            });
+        self.reparse(&source)
     }
 
     pub(crate) fn fix_contract_rename(
@@ -194,48 +435,391 @@ impl Context {
             &format!("contract {old_name}"),
             &format!("contract {new_name}"),
         );
-        let filename = self.sol.to_string_lossy();
-        let parsed = parse(&source).map_err(|diagnostics| {
-            let full = diagnostics
-                .into_iter()
-                .map(|d| Pretty::new(&d, &filename, &source).to_string())
-                .collect::<Vec<_>>()
-                .join("\n");
-            anyhow::anyhow!(full)
-        })?;
-        Ok(self.update_from_parsed(parsed))
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_base_contract_missing(
+        mut self,
+        contract: Option<&str>,
+        name: &str,
+    ) -> anyhow::Result<Context> {
+        let contract_sol = match self.contract_pair(contract) {
+            Some((_, c)) => c,
+            None => return Ok(self),
+        };
+
+        // Append the base after the last one, or right after the contract's
+        // name if it doesn't inherit from anything yet.
+        let (offset, base) = match contract_sol.base.last() {
+            Some(last) => (last.loc.end(), format!(", {name}")),
+            None => match contract_sol.name {
+                Some(ref identifier) => {
+                    (identifier.loc.end(), format!(" is {name}"))
+                }
+                None => return Ok(self),
+            },
+        };
+        self.src.insert_str(offset, &base);
+
+        let source = self.src.clone();
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_import_missing(
+        mut self,
+        import: &config::Import,
+    ) -> anyhow::Result<Context> {
+        // Insert the import after the last pragma or import directive. The
+        // first import is separated from the pragma directive by a blank line.
+        let offset = find_header_end(&self.pt, &self.src).unwrap_or(0);
+        let has_imports =
+            self.pt.0.iter().any(|part| {
+                matches!(part, pt::SourceUnitPart::ImportDirective(_))
+            });
+        let separator = if has_imports { "\n" } else { "\n\n" };
+        self.src.insert_str(offset, &format!("{separator}{import}"));
+
+        let source = self.src.clone();
+        self.reparse(&source)
     }
 
     pub(crate) fn fix_matching_fn_missing(
         mut self,
+        contract: Option<&str>,
         fn_hir: &hir::FunctionDefinition,
         index: usize,
     ) -> anyhow::Result<Context> {
-        let contract_hir = match self.hir.find_contract() {
-            Some(c) => c,
-            None => return Ok(self),
-        };
-        let contract_sol = match find_contract(&self.pt) {
-            Some(c) => c,
-            None => return Ok(self),
+        let Some((contract_hir, contract_sol)) = self.contract_pair(contract)
+        else {
+            return Ok(self);
         };
 
-        let offset =
-            get_insertion_offset(&contract_sol, contract_hir, index, &self.src);
+        let offset = get_insertion_offset(
+            &contract_sol,
+            &contract_hir,
+            index,
+            &self.src,
+        );
         self.insert_function_at(fn_hir, offset);
 
         let source = self.src.clone();
-        let filename = self.sol.to_string_lossy();
-        let parsed = parse(&source).map_err(|diagnostics| {
-            let full = diagnostics
-                .into_iter()
-                .map(|d| Pretty::new(&d, &filename, &source).to_string())
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_fn_renamed(
+        mut self,
+        contract: Option<&str>,
+        fn_sol: &pt::FunctionDefinition,
+        fn_hir: &hir::FunctionDefinition,
+    ) -> anyhow::Result<Context> {
+        let Some((_, contract_sol)) = self.contract_pair(contract) else {
+            return Ok(self);
+        };
+        // Offsets change with each fix, so the function is looked up again.
+        let old_name = fn_sol.name.safe_unwrap().name.as_str();
+        let fns: Vec<_> = contract_sol
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                ContractPart::FunctionDefinition(f) => Some(f),
+                _ => None,
+            })
+            .collect();
+        let Some(name) = fns.iter().find_map(|f| {
+            f.name.as_ref().filter(|n| f.ty == fn_sol.ty && n.name == old_name)
+        }) else {
+            return Ok(self);
+        };
+
+        let new_name = &fn_hir.identifier;
+        let mut edits = vec![TextEdit {
+            range: name.loc.start()..name.loc.end(),
+            text: new_name.clone(),
+        }];
+        // Functions that apply a renamed modifier apply it under its new
+        // name.
+        if fn_sol.ty == pt::FunctionTy::Modifier {
+            edits.extend(
+                fns.iter()
+                    .flat_map(|f| &f.attributes)
+                    .filter_map(|attribute| match attribute {
+                        pt::FunctionAttribute::BaseOrModifier(_, base) => {
+                            match base.name.identifiers.as_slice() {
+                                [identifier] if identifier.name == old_name => {
+                                    Some(identifier.loc)
+                                }
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    })
+                    .map(|loc| TextEdit {
+                        range: loc.start()..loc.end(),
+                        text: new_name.clone(),
+                    }),
+            );
+        }
+        self.src = apply_edits(&self.src, edits);
+
+        let source = self.src.clone();
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_modifiers_mismatch(
+        mut self,
+        contract: Option<&str>,
+        fn_sol: &pt::FunctionDefinition,
+        fn_hir: &hir::FunctionDefinition,
+        applied: &[String],
+    ) -> anyhow::Result<Context> {
+        let Some((_, contract_sol)) = self.contract_pair(contract) else {
+            return Ok(self);
+        };
+        // Offsets change with each fix, so the function is looked up again.
+        let name = fn_sol.name.safe_unwrap().name.as_str();
+        let Some(f) = contract_sol.parts.iter().find_map(|part| match part {
+            ContractPart::FunctionDefinition(f)
+                if f.ty == fn_sol.ty
+                    && f.name.as_ref().is_some_and(|n| n.name == name) =>
+            {
+                Some(f)
+            }
+            _ => None,
+        }) else {
+            return Ok(self);
+        };
+
+        // The modifiers of the tree that the function applies are replaced
+        // with the expected ones, where the first of them was. A modifier
+        // may have been renamed to an expected one by an earlier fix.
+        let expected = fn_hir.modifiers.as_deref().unwrap_or_default();
+        let locs: Vec<pt::Loc> = applied_modifiers(f)
+            .filter(|(_, identifier)| {
+                applied.contains(&identifier.name)
+                    || expected.contains(&identifier.name)
+            })
+            .map(|(loc, _)| loc)
+            .collect();
+        let text = expected.join(" ");
+        let mut edits: Vec<TextEdit> = locs
+            .iter()
+            .map(|loc| {
+                TextEdit::delete(trimmed_end(&self.src, loc.start())..loc.end())
+            })
+            .collect();
+        match edits.first_mut() {
+            Some(edit) if !text.is_empty() => {
+                edit.range.start = locs[0].start();
+                edit.text = text;
+            }
+            Some(_) => {}
+            None => {
+                // Right after the other attributes, or the parameters.
+                let offset = match f.attributes.last() {
+                    Some(attribute) => attribute.loc().end(),
+                    None => params_end(&self.src, f),
+                };
+                edits.push(TextEdit {
+                    range: offset..offset,
+                    text: format!(" {text}"),
+                });
+            }
+        }
+        self.src = apply_edits(&self.src, edits);
+
+        let source = self.src.clone();
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_comments_mismatch(
+        mut self,
+        contract: Option<&str>,
+        fn_sol: &pt::FunctionDefinition,
+        fn_hir: &hir::FunctionDefinition,
+    ) -> anyhow::Result<Context> {
+        let Some((_, contract_sol)) = self.contract_pair(contract) else {
+            return Ok(self);
+        };
+        // Offsets change with each fix, so the function is looked up again.
+        let name = fn_sol.name.safe_unwrap().name.as_str();
+        let Some(f) = contract_sol.parts.iter().find_map(|part| match part {
+            ContractPart::FunctionDefinition(f)
+                if f.ty == fn_sol.ty
+                    && f.name.as_ref().is_some_and(|n| n.name == name) =>
+            {
+                Some(f)
+            }
+            _ => None,
+        }) else {
+            return Ok(self);
+        };
+        // The comments of each action that doesn't match the tree are
+        // replaced, and statements are left as they are. The comments of
+        // extra actions are removed, and missing actions are added after the
+        // last one.
+        let actions = action_comments(&self.src, &self.comments, f);
+        let expected = expected_comments(fn_hir);
+        let Some(((last_range, _), _)) =
+            actions.last().and_then(|action| action.split_last())
+        else {
+            return Ok(self);
+        };
+        let mut edits = vec![];
+        for (action, comments) in actions.iter().zip(&expected) {
+            if action_matches(action, comments) {
+                continue;
+            }
+
+            let start = action[0].0.start;
+            let end = action[action.len() - 1].0.end;
+            let indentation = line_indentation(&self.src, start);
+            let text = comments
+                .iter()
+                .map(|comment| format!("// {comment}"))
                 .collect::<Vec<_>>()
-                .join("\n");
-            anyhow::anyhow!(full)
-        })?;
-        Ok(self.update_from_parsed(parsed))
+                .join(&format!("\n{indentation}"));
+            edits.push(TextEdit { range: start..end, text });
+        }
+        for action in actions.iter().skip(expected.len()) {
+            let start = self.src[..action[0].0.start].rfind('\n').unwrap_or(0);
+            let end = action[action.len() - 1].0.end;
+            edits.push(TextEdit::delete(start..end));
+        }
+        let indentation = line_indentation(&self.src, last_range.start);
+        let missing: Vec<_> = expected
+            .iter()
+            .skip(actions.len())
+            .flatten()
+            .map(|comment| format!("// {comment}"))
+            .collect();
+        if !missing.is_empty() {
+            let offset = last_range.end;
+            let text = format!(
+                "\n{indentation}{}",
+                missing.join(&format!("\n{indentation}"))
+            );
+            edits.push(TextEdit { range: offset..offset, text });
+        }
+        self.src = apply_edits(&self.src, edits);
+
+        let source = self.src.clone();
+        self.reparse(&source)
+    }
+
+    pub(crate) fn fix_orphaned_fn(
+        mut self,
+        contract: Option<&str>,
+        fn_sol: &pt::FunctionDefinition,
+    ) -> anyhow::Result<Context> {
+        let Some((_, contract_sol)) = self.contract_pair(contract) else {
+            return Ok(self);
+        };
+        // Offsets change with each fix, so the function is looked up again.
+        let name = fn_sol.name.safe_unwrap().name.as_str();
+        let Some(f) = contract_sol.parts.iter().find_map(|part| match part {
+            ContractPart::FunctionDefinition(f)
+                if f.ty == fn_sol.ty
+                    && f.name.as_ref().is_some_and(|n| n.name == name) =>
+            {
+                Some(f)
+            }
+            _ => None,
+        }) else {
+            return Ok(self);
+        };
+
+        let end = f.loc().end();
+        let edit = match self.cfg.prune {
+            Prune::Delete => {
+                // The comments right above the function go with it.
+                let start = trimmed_end(
+                    &self.src,
+                    chunk_start(&self.src, f.loc.start()),
+                );
+                // Don't leave a blank line after the contract's opening
+                // brace.
+                let rest = &self.src[end..];
+                let whitespace = &rest[..rest.len() - rest.trim_start().len()];
+                let end = match whitespace.rfind('\n') {
+                    Some(idx) if self.src[..start].ends_with('{') => end + idx,
+                    _ => end,
+                };
+                TextEdit::delete(start..end)
+            }
+            Prune::Comment => {
+                let start = self.src[..f.loc.start()]
+                    .rfind('\n')
+                    .map_or(0, |idx| idx + 1);
+                let indentation = line_indentation(&self.src, start);
+                let text = self.src[start..end]
+                    .lines()
+                    .map(|line| match line.trim_start() {
+                        "" => String::new(),
+                        content => format!(
+                            "{indentation}// {}",
+                            line.strip_prefix(indentation).unwrap_or(content)
+                        ),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                TextEdit { range: start..end, text }
+            }
+        };
+        self.src = apply_edits(&self.src, vec![edit]);
+
+        let source = self.src.clone();
+        self.reparse(&source)
+    }
+}
+
+/// Finds the end of a Solidity file's header, i.e. the offset right after the
+/// last pragma or import directive.
+fn find_header_end(pt: &SourceUnit, src: &str) -> Option<usize> {
+    let start =
+        pt.0.iter()
+            .rfind(|part| {
+                matches!(
+                    part,
+                    pt::SourceUnitPart::PragmaDirective(..)
+                        | pt::SourceUnitPart::ImportDirective(_)
+                )
+            })?
+            .loc()
+            .start();
+
+    src.get(start..)?.find(';').map(|end| start + end + 1)
+}
+
+/// Appends the contracts of the `generated` source to `src`.
+///
+/// The license, pragma and import directives of `src` are kept as is. Imports
+/// of `generated` that are missing from `src` are appended to them.
+fn append_contracts(
+    src: &str,
+    pt: &SourceUnit,
+    generated: &str,
+) -> Option<String> {
+    let header_end = find_header_end(pt, src)?;
+    let header = &src[..header_end];
+    let generated_pt = parse(generated).ok()?.pt;
+    let body_start = find_header_end(&generated_pt, generated)?;
+
+    let mut source = header.to_owned();
+    for part in &generated_pt.0 {
+        if let pt::SourceUnitPart::ImportDirective(_) = part {
+            let start = part.loc().start();
+            let end = start + generated[start..].find(';')? + 1;
+            let import = &generated[start..end];
+            if !header.contains(import) {
+                source.push('\n');
+                source.push_str(import);
+            }
+        }
     }
+    source.push_str(src[header_end..].trim_end());
+    source.push_str(&generated[body_start..]);
+
+    Some(source)
 }
 
 /// Calculates the insertion offset for a new function in a contract's source
@@ -264,13 +848,12 @@ fn get_insertion_offset(
     }
 
     match &contract_hir.children[index - 1] {
-        Hir::Function(prev_fn_hir) => {
-            find_matching_fn(contract_sol, prev_fn_hir)
-                .expect("matching function should exist")
-                .1
-                .loc()
-                .end()
-        }
+        Hir::Function(prev_fn_hir) => FnIndex::new(contract_sol, src.as_ref())
+            .find(prev_fn_hir)
+            .expect("matching function should exist")
+            .1
+            .loc()
+            .end(),
         _ => unreachable!("previous child should be a function definition"),
     }
 }
@@ -298,19 +881,20 @@ fn find_contract_body_start(
 
 /// Rearranges functions in a Solidity contract to match the order in the HIR.
 ///
+/// Only the functions flagged by `violations` move. The rest of the contract,
+/// including functions that are not part of the tree, stays where it is.
+///
 /// The algorithm goes like this:
-/// 1. It first creates a set of function names present in `contract_hir` to
-///    identify the functions that need to be sorted.
-/// 2. It then iterates over the `violations` to correct the order of functions
-///    in `contract_sol`, matching them with `contract_hir`.
-/// 3. Functions not part of `contract_hir` are removed, as their correct
-///    position is unknown.
-/// 4. The sorted functions are then compiled into a string (`source`) and
-///    simultaneously removed from a temporary string (`scratch`) that mirrors
-///    the original source code.
-/// 5. Finally, the function reconstructs the contract's body by combining the
-///    sorted functions and any remaining parts of the contract (preserved in
-///    `scratch`), ensuring all components are included in the output.
+/// 1. It goes over the functions of `contract_hir` in order, skipping the ones
+///    that don't appear in `contract_sol`.
+/// 2. Each flagged function is cut from its current position, along with the
+///    comments right above it.
+/// 3. It is then pasted right after the function that precedes it in the tree,
+///    or before the first function of the tree if there is none. Since the
+///    functions are visited in order, the function that precedes it is either
+///    in place or was already moved.
+/// 4. Finally, all cuts and pastes are applied to the original source as text
+///    edits, so the rest of the file is left untouched.
 ///
 /// # Arguments
 /// * `violations` - Order discrepancies between Solidity and HIR
@@ -322,7 +906,7 @@ fn find_contract_body_start(
 /// Updated Context with reordered functions in Solidity source code
 ///
 /// # Panics
-/// If the reconstructed Solidity string fails to parse
+/// If the rearranged Solidity string fails to parse
 #[must_use]
 pub fn fix_order(
     violations: &[Violation],
@@ -330,100 +914,261 @@ pub fn fix_order(
     contract_hir: &hir::ContractDefinition,
     ctx: Context,
 ) -> Context {
-    // 1. Create a set containing the functions that appear in the tree.
-    //
-    // These are the functions that we know how to sort.
-    let fn_names: HashSet<String> = contract_hir
-        .children
+    let flagged: HashSet<&str> = violations
         .iter()
-        .filter_map(|child| {
-            if let hir::Hir::Function(f) = &child {
-                Some(f.identifier.clone())
-            } else {
-                None
+        .filter_map(|violation| match &violation.kind {
+            ViolationKind::FunctionOrderMismatch(f, _, _) => {
+                Some(f.name.safe_unwrap().name.as_str())
             }
+            _ => None,
         })
         .collect();
 
-    // 2. Properly sort functions in a new vec.
-    let mut fns = contract_sol.parts.clone();
-    for violation in violations {
-        if let ViolationKind::FunctionOrderMismatch(f, sol_idx, hir_idx) =
-            &violation.kind
+    // 1. The functions of the tree that appear in the Solidity contract, in
+    // tree order.
+    let fn_index = FnIndex::new(contract_sol, &ctx.src);
+    let fns: Vec<&pt::FunctionDefinition> = contract_hir
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Hir::Function(f) => fn_index.find(f),
+            _ => None,
+        })
+        .map(|(_, f)| f)
+        .collect();
+    let Some(first) = contract_sol.parts.iter().find_map(|part| match part {
+        ContractPart::FunctionDefinition(f)
+            if fns.iter().any(|tree_fn| tree_fn.loc == f.loc) =>
         {
-            fns.remove(*sol_idx);
-            fns.insert(
-                *hir_idx,
-                ContractPart::FunctionDefinition(Box::new(f.clone())),
-            );
+            Some(f)
         }
-    }
+        _ => None,
+    }) else {
+        return ctx;
+    };
 
-    // 3. Remove functions that are not part of the hir since
-    // we don't know where they go.
-    let fns: Vec<&ContractPart> = fns
-        .iter()
-        .filter(|f| {
-            if let ContractPart::FunctionDefinition(f) = f {
-                fn_names.contains(&f.name.safe_unwrap().name)
-            } else {
-                false
+    // 2. Cut the flagged functions and 3. paste them in order. Functions
+    // moved to the same place are pasted together.
+    let src = &ctx.src;
+    let first_chunk = chunk_start(src, first.loc.start());
+    let start = trimmed_end(src, first_chunk);
+    let mut edits = vec![];
+    let mut pastes: Vec<(usize, String)> = vec![];
+    let mut previous: Option<usize> = None;
+    for f in fns {
+        let name = f.name.safe_unwrap().name.as_str();
+        if !flagged.contains(name) {
+            previous = Some(f.loc().end());
+            continue;
+        }
+
+        let chunk = chunk_start(src, f.loc.start());
+        let indentation = line_indentation(src, chunk);
+        let text = &src[chunk..f.loc().end()];
+        edits.push(TextEdit::delete(trimmed_end(src, chunk)..f.loc().end()));
+
+        let offset = previous.unwrap_or(start);
+        match pastes.last_mut() {
+            Some((last_offset, last_text)) if *last_offset == offset => {
+                last_text.push_str(&format!("\n\n{indentation}{text}"));
             }
-        })
-        .collect();
+            _ => {
+                // Don't leave a blank line after the contract's opening brace.
+                let separator =
+                    if src[..offset].ends_with('{') { "\n" } else { "\n\n" };
+                pastes
+                    .push((offset, format!("{separator}{indentation}{text}")));
+            }
+        }
+        // The functions that follow are pasted after this one.
+        previous = Some(offset);
+    }
+    // Functions pasted before the first function of the tree are separated
+    // from it by a blank line.
+    if let Some((offset, text)) = pastes.first_mut() {
+        let first_name = first.name.safe_unwrap().name.as_str();
+        if *offset == start
+            && !flagged.contains(first_name)
+            && src[start..first_chunk].matches('\n').count() < 2
+        {
+            text.push('\n');
+        }
+    }
+    edits.extend(
+        pastes
+            .into_iter()
+            .map(|(offset, text)| TextEdit { range: offset..offset, text }),
+    );
 
-    // 4. a - Add the functions that appear in the tree to the blank
-    // string `source`.
-    //    b - Replace them with whitespace in `scratch`.
-    //
-    // Since we sorted in a previous step, they'll appear sorted in
-    // the string. We do 4.b because we want to append the remaining
-    // functions after the sorted functions.
-    let mut scratch = ctx.src.clone();
-    let mut source = Vec::with_capacity(fns.len());
-    for f in &fns {
-        let loc = f.loc();
-        let f = ctx.src[loc.start()..loc.end()].to_owned();
-        scratch = scratch.replace(&f, " ".repeat(f.len()).as_str());
-        source.push(f);
-    }
-
-    // 5. Replace the contract's body with the sorted functions and
-    // the extra functions contained in the scratch string.
-    // We know there is at least two parts because we found order violations.
-    let first_part_loc = contract_sol.parts[0].loc();
-    // If the functions in the solidity file are exactly the functions in the
-    // tree file, then we just print them. We still need to include the scratch
-    // because it might contain comments or other constructs that we need to
-    // keep.
-    let source = if fns.len() == contract_sol.parts.len() {
-        format!(
-            "{}{}{}{}",
-            &ctx.src[..first_part_loc.start()],
-            source.join("\n\n"),
-            &scratch[first_part_loc.start()..contract_sol.loc.end() - 1],
-            &ctx.src[contract_sol.loc().end() - 1..]
-        )
-    } else {
-        const SEPARATOR: &str = r"
-
-      // <<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
-      // ==================== BULLOAK AUTOGENERATED SEPARATOR ====================
-      // >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
-      //    Code below this section could not be automatically moved by bulloak
-      // =========================================================================
-            ";
+    // 4. Apply the edits to the original source.
+    let source = apply_edits(src, edits);
+    let parsed = parse(&source).expect("should parse solidity string");
+    ctx.update_from_parsed(parsed)
+}
+
+/// A replacement of a range of a Solidity file with some text.
+struct TextEdit {
+    /// The byte range of the original source that is replaced.
+    range: Range<usize>,
+    /// The replacement text.
+    text: String,
+}
+
+impl TextEdit {
+    /// Creates an edit that deletes `range`.
+    fn delete(range: Range<usize>) -> Self {
+        Self { range, text: String::new() }
+    }
+}
+
+/// Applies non-overlapping `edits` to `src`.
+///
+/// The ranges of the edits are relative to `src`. An insertion at the start of
+/// a deleted range ends up where the deleted text was.
+fn apply_edits(src: &str, mut edits: Vec<TextEdit>) -> String {
+    // Apply the edits back to front, so that the offsets of the ones that are
+    // left stay valid.
+    edits.sort_by_key(|edit| {
+        std::cmp::Reverse((edit.range.start, edit.range.end))
+    });
+    let mut source = src.to_owned();
+    for edit in edits {
+        source.replace_range(edit.range, &edit.text);
+    }
+
+    source
+}
+
+/// Returns the offset of the start of a contract part, including the comments
+/// on the lines right above it, but not its indentation.
+fn chunk_start(src: &str, offset: usize) -> usize {
+    let mut start = offset;
+    let mut line_start = src[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    while line_start > 0 {
+        let prev_start =
+            src[..line_start - 1].rfind('\n').map_or(0, |idx| idx + 1);
+        let prev = src[prev_start..line_start - 1].trim();
+        if !(prev.starts_with("//")
+            || prev.starts_with("/*")
+            || prev.starts_with('*'))
+        {
+            break;
+        }
+        start = prev_start + line_indentation(src, prev_start).len();
+        line_start = prev_start;
+    }
+
+    start
+}
+
+/// Returns the offset right after the last non-whitespace character before
+/// `offset`.
+fn trimmed_end(src: &str, offset: usize) -> usize {
+    src[..offset].trim_end().len()
+}
+
+/// Returns the offset right after the closing parenthesis of the parameters
+/// of a function.
+fn params_end(src: &str, f: &pt::FunctionDefinition) -> usize {
+    let start = f.name.as_ref().map_or(f.loc.start(), |name| name.loc.end());
+    let mut depth = 0usize;
+    for (idx, c) in src[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 1 => return start + idx + 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    f.loc.end()
+}
+
+/// Returns the indentation of the line `offset` is in.
+fn line_indentation(src: &str, offset: usize) -> &str {
+    let line_start = src[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = &src[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// The indentation of a contract in a Solidity file.
+struct Indentation {
+    /// The indentation of the contract definition.
+    contract: String,
+    /// The indentation of the contract's members.
+    member: String,
+    /// The indentation of each nesting level.
+    unit: String,
+}
+
+impl Indentation {
+    /// Infers the indentation of `contract` from `src`.
+    ///
+    /// Empty contracts are indented with the `tab_width` of the `forge fmt`
+    /// settings in `cfg`.
+    fn of(contract: &ContractDefinition, src: &str, cfg: &Config) -> Self {
+        let contract_indentation = line_indentation(src, contract.loc.start());
+        let default_unit = " ".repeat(cfg.formatter_config.tab_width);
+        let unit = contract
+            .parts
+            .first()
+            .map(|part| line_indentation(src, part.loc().start()))
+            .and_then(|member| member.strip_prefix(contract_indentation))
+            .filter(|unit| !unit.is_empty())
+            .unwrap_or(&default_unit);
+
+        Self {
+            contract: contract_indentation.to_owned(),
+            member: format!("{contract_indentation}{unit}"),
+            unit: unit.to_owned(),
+        }
+    }
+
+    /// Re-indents a line formatted with `width` spaces per nesting level, in
+    /// which the members of the contract are at nesting level `level`.
+    fn indent(&self, line: &str, width: usize, level: usize) -> String {
+        let line = line.trim_end();
+        if line.is_empty() {
+            return String::new();
+        }
+
+        let content = line.trim_start();
+        let spaces = line.len() - content.len();
+        let nesting = (spaces / width).saturating_sub(level);
         format!(
-            "{}{}{SEPARATOR}{}{}",
-            &ctx.src[..first_part_loc.start()],
-            source.join("\n\n"),
-            &scratch[first_part_loc.start()..contract_sol.loc.end() - 1],
-            &ctx.src[contract_sol.loc().end() - 1..]
+            "{}{}{}{content}",
+            self.member,
+            self.unit.repeat(nesting),
+            " ".repeat(spaces % width)
         )
+    }
+}
+
+/// Emits a function of the tree, formatted with the `forge fmt` settings in
+/// `cfg` and indented like the other members of its contract.
+fn emit_member(
+    function: &hir::FunctionDefinition,
+    indentation: &Indentation,
+    cfg: &Config,
+) -> String {
+    let function = Emitter::new(cfg).emit(&Hir::Function(function.clone()));
+    // Wrap the function in a contract, since only whole files are formatted.
+    let wrapped = format!("contract C {{\n{function}}}\n");
+    let (formatted, width) = match scaffold::fmt(&wrapped, cfg) {
+        Ok(formatted) => (formatted, cfg.formatter_config.tab_width),
+        // User-defined snippets may contain invalid Solidity, in which case
+        // the function is inserted as is.
+        Err(_) => (wrapped, INTERNAL_DEFAULT_INDENTATION),
     };
 
-    let parsed = parse(&source).expect("should parse solidity string");
-    ctx.update_from_parsed(parsed)
+    let lines: Vec<&str> = formatted.trim_end().lines().collect();
+    lines[1..lines.len() - 1]
+        .iter()
+        .map(|line| indentation.indent(line, width, 1))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_owned()
 }
 
 #[cfg(test)]
@@ -433,7 +1178,7 @@ mod tests {
     use tempfile::tempdir;
 
     use super::*;
-    use crate::check::rules::{Checker, StructuralMatcher};
+    use crate::check::rules::CommentMatcher;
 
     fn write_file(
         dir: &std::path::Path,
@@ -494,7 +1239,7 @@ mod tests {
             pragma solidity 0.8.0;\n\
             contract Foo {}\n";
         let ctx0 = make_ctx(tree, sol);
-        let ctx1 = ctx0.clone().fix_contract_missing().unwrap();
+        let ctx1 = ctx0.clone().fix_contract_missing("Foo").unwrap();
         assert!(
             ctx1.src.contains("function test_One()"),
             "should scaffold the missing test"
@@ -532,6 +1277,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fix_matching_fn_missing_keeps_indentation() {
+        let tree = "\
+            Foo\n\
+            ├── It A.\n\
+            ├── It B.\n\
+            └── It C.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+  function test_A() external {
+    uint256 a = 1;   // Not formatted.
+  }
+
+  function test_C() external {}
+}
+";
+        let ctx0 = make_ctx(tree, sol);
+        let mut vs = StructuralMatcher::check(&ctx0);
+        assert_eq!(1, vs.len());
+        let ctx1 = vs.pop().unwrap().fix(ctx0).unwrap();
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+  function test_A() external {
+    uint256 a = 1;   // Not formatted.
+  }
+
+  function test_B() external {
+    // It B.
+  }
+
+  function test_C() external {}
+}
+";
+        assert_eq!(expected, ctx1.src);
+    }
+
+    #[test]
+    fn fix_order_moves_only_flagged_functions() {
+        let tree = "\
+            Foo\n\
+            ├── It A.\n\
+            ├── It B.\n\
+            └── It C.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+    uint256 internal x;
+
+    function test_B() external {}
+
+    function helper() internal {}
+
+    /// Tests C.
+    function test_C() external {}
+
+    // Tests A.
+    function test_A() external {}
+}
+";
+        let ctx0 = make_ctx(tree, sol);
+        let vs: Vec<_> = StructuralMatcher::check(&ctx0)
+            .into_iter()
+            .filter(|v| {
+                matches!(
+                    &v.kind,
+                    ViolationKind::FunctionOrderMismatch(f, _, _)
+                        if f.name.safe_unwrap().name == "test_A"
+                )
+            })
+            .collect();
+        let (contract_hir, contract_sol) = ctx0.contract_pair(None).unwrap();
+        let ctx1 = fix_order(&vs, &contract_sol, &contract_hir, ctx0);
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    uint256 internal x;
+
+    // Tests A.
+    function test_A() external {}
+
+    function test_B() external {}
+
+    function helper() internal {}
+
+    /// Tests C.
+    function test_C() external {}
+}
+";
+        assert_eq!(expected, ctx1.src);
+    }
+
+    #[test]
+    fn merge_adds_missing_functions_only() {
+        let tree = "\
+Foo
+├── It one.
+├── When two
+│   └── It two.
+└── It three.
+";
+        let sol = "\
+            // SPDX-License-Identifier: UNLICENSED\n\
+            pragma solidity 0.8.0;\n\
+            contract Bar {\n\
+              function test_Three() external { assert(true); }\n\
+              function helper() internal {}\n\
+              function test_One() external {}\n\
+            }\n";
+        let ctx0 = make_ctx(tree, sol);
+        let ctx1 = ctx0.merge().unwrap().unwrap();
+
+        let src = &ctx1.src;
+        // Existing code is kept as is, in its order.
+        assert!(src.contains("contract Bar"));
+        assert!(
+            src.contains("function test_Three() external { assert(true); }")
+        );
+        let idx_three = src.find("test_Three").unwrap();
+        let idx_one = src.find("test_One").unwrap();
+        assert!(idx_three < idx_one);
+        // Missing functions are added.
+        assert!(src.contains("function test_WhenTwo()"));
+
+        assert!(ctx1.merge().unwrap().is_none(), "nothing left to merge");
+    }
+
     #[test]
     fn fix_rename_parse_error() {
         let tree = "Foo\n└── It one.\n";
@@ -554,7 +1427,7 @@ mod tests {
             contract Foo {}\n";
         let mut ctx = make_ctx(tree, sol);
         ctx.src = "not a valid solidity file".to_string();
-        let ctx1 = ctx.clone().fix_contract_missing().unwrap();
+        let ctx1 = ctx.clone().fix_contract_missing("Foo").unwrap();
         assert!(ctx1.src.contains("function test_One()"));
     }
 
@@ -566,7 +1439,7 @@ mod tests {
         let contract_hir = ctx0.hir.find_contract().unwrap();
         if let Hir::Function(fn_hir) = &contract_hir.children[0] {
             let ctx1 =
-                ctx0.clone().fix_matching_fn_missing(&fn_hir, 0).unwrap();
+                ctx0.clone().fix_matching_fn_missing(None, &fn_hir, 0).unwrap();
             assert_eq!(ctx0.src, ctx1.src);
         } else {
             unreachable!()
@@ -583,7 +1456,7 @@ mod tests {
               function test_One() external {} \n\
             }\n";
         let ctx0 = make_ctx(tree, sol);
-        let contract_sol = find_contract(&ctx0.pt).unwrap();
+        let contract_sol = crate::sol::find_contract(&ctx0.pt).unwrap();
         let fn_sol = contract_sol
             .parts
             .iter()
@@ -624,4 +1497,239 @@ mod tests {
         assert!(s.contains("contract \"Foo\" is missing in .sol"));
         assert!(s.contains("consider adding a contract with name \"Foo\""));
     }
+
+    #[test]
+    fn fix_fn_renamed_keeps_bodies() {
+        let tree = "\
+            Foo\n\
+            └── When the caller is the owner\n    \
+                ├── When the amount is zero\n    \
+                │   └── It should revert.\n    \
+                └── It should transfer.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+    modifier whenTheCallerIsAdmin() {
+        _;
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsAdmin {
+        foo.transfer(0);
+    }
+
+    function test_WhenTheCallerIsAdmin() external whenTheCallerIsAdmin {
+        foo.transfer(1);
+    }
+}
+";
+        let mut ctx = make_ctx(tree, sol);
+        ctx.cfg.detect_renames = true;
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(2, vs.len());
+        for v in vs {
+            assert!(matches!(v.kind, ViolationKind::FunctionRenamed(_, _, _)));
+            ctx = v.fix(ctx).unwrap();
+        }
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    modifier whenTheCallerIsTheOwner() {
+        _;
+    }
+
+    function test_RevertWhen_TheAmountIsZero() external whenTheCallerIsTheOwner {
+        foo.transfer(0);
+    }
+
+    function test_WhenTheCallerIsTheOwner() external whenTheCallerIsTheOwner {
+        foo.transfer(1);
+    }
+}
+";
+        assert_eq!(expected, ctx.src);
+    }
+
+    #[test]
+    fn fix_modifiers_mismatch_rewrites_modifiers() {
+        let tree = "\
+            Foo\n\
+            └── When a\n    \
+                └── When b\n        \
+                    ├── When c\n        \
+                    │   └── It should do one thing.\n        \
+                    └── It should do another thing.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+    modifier whenA() {
+        _;
+    }
+
+    modifier whenB() {
+        _;
+    }
+
+    function test_WhenB() public {
+        foo.baz();
+    }
+
+    function test_WhenC() external whenB prank(alice) whenA {
+        foo.bar();
+    }
+}
+";
+        let mut ctx = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(2, vs.len());
+        for v in vs {
+            assert!(matches!(
+                v.kind,
+                ViolationKind::ModifiersMismatch(_, _, _)
+            ));
+            ctx = v.fix(ctx).unwrap();
+        }
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    modifier whenA() {
+        _;
+    }
+
+    modifier whenB() {
+        _;
+    }
+
+    function test_WhenB() public whenA whenB {
+        foo.baz();
+    }
+
+    function test_WhenC() external whenA whenB prank(alice) {
+        foo.bar();
+    }
+}
+";
+        assert_eq!(expected, ctx.src);
+        assert!(StructuralMatcher::check(&ctx).is_empty());
+    }
+
+    #[test]
+    fn fix_comments_mismatch_keeps_statements() {
+        let tree = "\
+            Foo\n\
+            ├── When a\n\
+            │   ├── It should do one thing.\n\
+            │   │   └── Because it can.\n\
+            │   └── It should do another thing.\n\
+            ├── When b\n\
+            │   └── It should do a third thing.\n\
+            └── When c\n    \
+                ├── It should do a fourth thing.\n    \
+                └── It should do a fifth thing.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+    function test_WhenA() external {
+        // It should do one thing.
+        //     Because it could.
+        foo.bar();
+
+        // It should do some other thing.
+        assertTrue(foo.done());
+    }
+
+    function test_WhenB() external {
+        // It should do a thing.
+        foo.baz();
+        // It should do a stale thing.
+    }
+
+    function test_WhenC() external {
+        // it should do a fourth thing
+        foo.qux();
+    }
+}
+";
+        let mut ctx = make_ctx(tree, sol);
+        let vs = CommentMatcher::check(&ctx);
+        assert_eq!(3, vs.len());
+        for v in vs {
+            ctx = v.fix(ctx).unwrap();
+        }
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    function test_WhenA() external {
+        // It should do one thing.
+        //     Because it can.
+        foo.bar();
+
+        // It should do another thing.
+        assertTrue(foo.done());
+    }
+
+    function test_WhenB() external {
+        // It should do a third thing.
+        foo.baz();
+    }
+
+    function test_WhenC() external {
+        // it should do a fourth thing
+        // It should do a fifth thing.
+        foo.qux();
+    }
+}
+";
+        assert_eq!(expected, ctx.src);
+        assert!(CommentMatcher::check(&ctx).is_empty());
+    }
+
+    #[test]
+    fn fix_orphaned_fn_deletes_or_comments_out() {
+        let tree = "Foo\n└── It A.\n";
+        let sol = r"pragma solidity 0.8.0;
+
+contract Foo {
+    // Tests B.
+    function test_B() external {
+        // It B.
+    }
+
+    function test_A() external {}
+}
+";
+        let fix = |prune| {
+            let td = tempdir().unwrap();
+            let tree_path = write_file(td.path(), "X.tree", tree);
+            fs::write(td.path().join("X.t.sol"), sol).unwrap();
+            let mut cfg = Config::default();
+            cfg.files = vec![tree_path.clone()];
+            cfg.check_orphans = true;
+            cfg.prune = prune;
+            let ctx = Context::new(tree_path, &cfg).unwrap();
+            let mut vs = StructuralMatcher::check(&ctx);
+            assert_eq!(1, vs.len());
+            vs.pop().unwrap().fix(ctx).unwrap().src
+        };
+
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    function test_A() external {}
+}
+";
+        assert_eq!(expected, fix(Prune::Delete));
+
+        let expected = r"pragma solidity 0.8.0;
+
+contract Foo {
+    // Tests B.
+    // function test_B() external {
+    //     // It B.
+    // }
+
+    function test_A() external {}
+}
+";
+        assert_eq!(expected, fix(Prune::Comment));
+    }
 }
@@ -0,0 +1,328 @@
+//! Defines comment matching as a rule.
+//!
+//! This rule enforces that the comments of the actions in every
+//! spec-generated test match the titles and descriptions of the actions in
+//! the spec, so that rewording an action in the tree doesn't leave stale
+//! comments behind.
+//!
+//! The comments of an action are a comment that starts with `it`, followed by
+//! the comments right below it. They may be anywhere in the test, e.g. next
+//! to the assertions they describe, and are compared regardless of case, of
+//! trailing periods and of fuzz parameters. Tests without any such comments
+//! aren't checked, and neither is any test if
+//! [`crate::config::Config::check_comments`] is unset.
+
+use std::ops::Range;
+
+use solang_parser::pt;
+
+use super::{Checker, Context};
+use crate::{
+    check::{
+        location::Location,
+        utils::offset_to_line,
+        violation::{Violation, ViolationKind},
+    },
+    hir::{self, translator::split_params, Hir},
+    sol::{action_comments, match_contracts, FnIndex},
+};
+
+/// An implementation of a comment matching rule.
+///
+/// Read more at the [module-level documentation][self].
+pub struct CommentMatcher;
+
+impl Checker for CommentMatcher {
+    fn check(ctx: &Context) -> Vec<Violation> {
+        let mut violations = vec![];
+        if !ctx.cfg.check_comments {
+            return violations;
+        }
+
+        // Missing contracts and functions are reported by the structural
+        // matching rule.
+        for (contract_hir, contract_sol) in match_contracts(&ctx.hir, &ctx.pt) {
+            let Some(contract_sol) = contract_sol else {
+                continue;
+            };
+
+            let identifier = &contract_hir.identifier;
+            violations.extend(
+                check_fns_comments(contract_hir, &contract_sol, ctx)
+                    .into_iter()
+                    .map(|v| v.with_contract(identifier)),
+            );
+        }
+
+        violations
+    }
+}
+
+/// Checks that the comments at the start of every function of the contract
+/// match the actions of its counterpart in the tree.
+fn check_fns_comments(
+    contract_hir: &hir::ContractDefinition,
+    contract_sol: &pt::ContractDefinition,
+    ctx: &Context,
+) -> Vec<Violation> {
+    let fn_index = FnIndex::new(contract_sol, &ctx.src);
+    contract_hir
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Hir::Function(f) if !f.is_modifier() => Some(f),
+            _ => None,
+        })
+        .filter_map(|fn_hir| {
+            let (_, fn_sol) = fn_index.find(fn_hir)?;
+            let actions = action_comments(&ctx.src, &ctx.comments, fn_sol);
+            if actions.is_empty() || actions_match(&actions, fn_hir) {
+                return None;
+            }
+
+            let comments = actions
+                .into_iter()
+                .flatten()
+                .map(|(_, comment)| comment.to_owned())
+                .collect();
+            Some(Violation::new(
+                ViolationKind::CommentsMismatch(
+                    fn_sol.clone(),
+                    fn_hir.clone(),
+                    comments,
+                ),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the comments of the actions of a function of the tree, i.e. the
+/// title and descriptions of each action.
+///
+/// Descriptions are told apart from titles by their indentation, which they
+/// keep from the tree. `TODO:` comments are left out, like in
+/// [`action_comments`].
+pub(crate) fn expected_comments(
+    fn_hir: &hir::FunctionDefinition,
+) -> Vec<Vec<&str>> {
+    let mut actions: Vec<Vec<&str>> = vec![];
+    let comments =
+        fn_hir.children.iter().flatten().filter_map(|child| match child {
+            Hir::Comment(comment) => Some(comment.lexeme.as_str()),
+            _ => None,
+        });
+    for comment in comments {
+        let trimmed = comment.trim();
+        if trimmed.starts_with("TODO:") {
+            continue;
+        }
+
+        let is_title = !comment.starts_with(char::is_whitespace);
+        match actions.last_mut() {
+            Some(action) if !is_title => action.push(comment),
+            _ => actions.push(vec![comment]),
+        }
+    }
+
+    actions
+}
+
+/// Whether the comments of the actions of a Solidity function match the ones
+/// of its counterpart in the tree.
+fn actions_match(
+    actions: &[Vec<(Range<usize>, &str)>],
+    fn_hir: &hir::FunctionDefinition,
+) -> bool {
+    let expected = expected_comments(fn_hir);
+    actions.len() == expected.len()
+        && actions
+            .iter()
+            .zip(&expected)
+            .all(|(action, expected)| action_matches(action, expected))
+}
+
+/// Whether the comments of an action match the title and descriptions of an
+/// action of the tree, regardless of case and of trailing periods.
+pub(crate) fn action_matches(
+    action: &[(Range<usize>, &str)],
+    expected: &[&str],
+) -> bool {
+    action.len() == expected.len()
+        && action.iter().zip(expected).all(|((_, comment), expected)| {
+            normalize(comment) == normalize(expected)
+        })
+}
+
+/// Normalizes a comment for comparison, ignoring case, trailing periods and
+/// fuzz parameters.
+pub(crate) fn normalize(comment: &str) -> String {
+    let (comment, _) = split_params(comment.trim());
+    comment.trim_end_matches('.').to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::config::Config;
+
+    /// Build a Context from given .tree and .t.sol text.
+    fn make_ctx(tree_src: &str, sol_src: &str) -> Context {
+        make_ctx_with(tree_src, sol_src, &Config::default())
+    }
+
+    /// Build a Context from given .tree and .t.sol text and config.
+    fn make_ctx_with(tree_src: &str, sol_src: &str, cfg: &Config) -> Context {
+        let td = tempdir().unwrap();
+        let tree = td.path().join("X.tree");
+        fs::write(&tree, tree_src).unwrap();
+        fs::write(td.path().join("X.t.sol"), sol_src).unwrap();
+        Context::new(tree, cfg).unwrap()
+    }
+
+    #[test]
+    fn comments_mismatch_detected() {
+        let tree = r"Foo
+├── It should do one thing.
+│   └── Because it can.
+├── It should do another thing.
+├── It should do a third thing.
+└── It should do a fourth thing.
+";
+        let sol = r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_ShouldDoOneThing() external {
+    foo.bar();
+
+    // it should do one thing
+    //     Because it can.
+    assertTrue(foo.done());
+  }
+
+  function test_ShouldDoAnotherThing() external {
+    // It should do some other thing.
+
+    // A comment of my own.
+  }
+
+  function test_ShouldDoAThirdThing() external {
+    // It should do a third thing.
+
+    // A comment of my own.
+  }
+
+  function test_ShouldDoAFourthThing() external {
+    // A comment of my own.
+  }
+}
+";
+        let ctx = make_ctx(tree, sol);
+        let vs = CommentMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        match &vs[0].kind {
+            ViolationKind::CommentsMismatch(fn_sol, _, comments) => {
+                assert_eq!(
+                    "test_ShouldDoAnotherThing",
+                    fn_sol.name.as_ref().unwrap().name
+                );
+                assert_eq!(vec!["It should do some other thing."], *comments);
+            }
+            other => panic!("expected CommentsMismatch, got {other:?}"),
+        }
+
+        let cfg = Config { check_comments: false, ..Config::default() };
+        assert!(
+            CommentMatcher::check(&make_ctx_with(tree, sol, &cfg)).is_empty()
+        );
+    }
+
+    #[test]
+    fn fuzz_parameters_are_ignored() {
+        let tree = r"Foo
+├── It should accept any recipient (address to)
+└── It should record the deadline (uint40 deadline)
+";
+        let sol = r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function testFuzz_ShouldAcceptAnyRecipient(address to) external {
+    // it should accept any recipient
+  }
+
+  function testFuzz_ShouldRecordTheDeadline(uint40 deadline) external {
+    // it should record the deadline (uint40 deadline)
+  }
+}
+";
+        assert!(CommentMatcher::check(&make_ctx(tree, sol)).is_empty());
+    }
+
+    #[test]
+    fn descriptions_are_grouped_by_indentation() {
+        let tree = r"Foo
+├── It should do one thing.
+│   └── Because it can.
+└── It should do another thing.
+";
+        let sol = r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_ShouldDoOneThing() external {
+    // It should do one thing.
+    //     It can.
+  }
+
+  function test_ShouldDoAnotherThing() external {
+    // It should do another thing.
+    // A comment of my own.
+  }
+}
+";
+        let vs = CommentMatcher::check(&make_ctx(tree, sol));
+        assert_eq!(1, vs.len());
+        match &vs[0].kind {
+            ViolationKind::CommentsMismatch(fn_sol, _, comments) => {
+                assert_eq!(
+                    "test_ShouldDoOneThing",
+                    fn_sol.name.as_ref().unwrap().name
+                );
+                assert_eq!(
+                    vec!["It should do one thing.", "It can."],
+                    *comments
+                );
+            }
+            other => panic!("expected CommentsMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn expected_comments_group_actions() {
+        let comment = |lexeme: &str| {
+            Hir::Comment(hir::Comment { lexeme: lexeme.to_owned() })
+        };
+        let fn_hir = hir::FunctionDefinition {
+            children: Some(vec![
+                comment("It should emit {Transfer}."),
+                comment("    Because it must."),
+                comment("TODO: Set the `Transfer` event arguments."),
+                comment("It should transfer."),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![
+                vec!["It should emit {Transfer}.", "    Because it must."],
+                vec!["It should transfer."]
+            ],
+            expected_comments(&fn_hir)
+        );
+    }
+}
@@ -5,7 +5,9 @@
 
 use super::{context::Context, violation::Violation};
 
+pub mod comments;
 pub mod structural_match;
+pub use comments::CommentMatcher;
 pub use structural_match::StructuralMatcher;
 
 /// Trait definition for a rule checker object.
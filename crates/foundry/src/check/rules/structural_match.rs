@@ -2,8 +2,20 @@
 //!
 //! This rule enforces the following:
 //! - All spec-generated functions & modifiers are present in the output file.
+//!   With [`Config::detect_renames`], a missing one that most likely was
+//!   renamed, e.g. after a condition was reworded, is reported as such instead.
 //! - The order of the spec-generated functions & modifiers matches the output
 //!   file.
+//! - Every spec-generated function applies the modifiers of its conditions, in
+//!   the same order.
+//! - The configured base contracts and import directives are present in the
+//!   output file.
+//! - Optionally, every function & modifier of the output file that is named
+//!   like spec-generated code is part of the spec.
+//!
+//! Modifiers that the contract inherits from its base contracts, whether they
+//! are defined in the output file or in the files it imports, count as
+//! present.
 //!
 //! Matching is name-based, which means that two functions are considered the
 //! same if:
@@ -11,9 +23,13 @@
 //! - Their function type is exactly the same. Currently, only regular functions
 //! and modifiers are supported.
 
-use std::collections::BTreeSet;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
 
 use bulloak_syntax::utils::sanitize;
+use forge_fmt::solang_ext::SafeUnwrap;
 use solang_parser::pt::{self, ContractPart};
 
 use super::{Checker, Context};
@@ -23,8 +39,12 @@ use crate::{
         utils::offset_to_line,
         violation::{Violation, ViolationKind},
     },
+    config::Config,
     hir::{self, Hir},
-    sol::{find_contract, find_matching_fn},
+    sol::{
+        applied_modifiers, inheritance::inherited_modifiers, match_contracts,
+        FnIndex,
+    },
 };
 
 /// An implementation of a structural matching rule.
@@ -36,46 +56,234 @@ impl Checker for StructuralMatcher {
     fn check(ctx: &Context) -> Vec<Violation> {
         let mut violations = vec![];
 
-        // We support multiple trees per .tree file, but they are combined into
-        // a single HIR during the [`hir::translate`]  step when creating the
-        // context which means that there can only be one contract. This is
-        // reflected in the current tree hierarchy of the HIR.
-        let Some(contract_hir) = ctx.hir.find_contract() else {
-            // If there is no contract in the .tree file, then we don't check
-            // anything.
-            return violations;
-        };
+        // Each contract of the .tree file is matched with the Solidity
+        // contract of the same name, or with an unmatched one, which is then
+        // reported as renamed. Interfaces, libraries and helper contracts
+        // are ignored.
+        let mut any_found = false;
+        for (contract_hir, contract_sol) in match_contracts(&ctx.hir, &ctx.pt) {
+            let identifier = &contract_hir.identifier;
+            let Some(contract_sol) = contract_sol else {
+                let violation = Violation::new(
+                    ViolationKind::ContractMissing(identifier.clone()),
+                    Location::File(ctx.tree.to_string_lossy().into_owned()),
+                );
+                violations.push(violation.with_contract(identifier));
+                continue;
+            };
+            any_found = true;
 
-        // Find the first occurrence of a contract.
-        let Some(contract_sol) = find_contract(&ctx.pt) else {
-            // If we find no contract in the Solidity file, then there must
-            // be no contract in the HIR, else we found a violation.
-            let violation = Violation::new(
-                ViolationKind::ContractMissing(contract_hir.identifier.clone()),
-                Location::File(ctx.tree.to_string_lossy().into_owned()),
+            let fns_violations =
+                check_fns_structure(contract_hir, &contract_sol, ctx);
+            let modifiers = check_modifiers(
+                contract_hir,
+                &contract_sol,
+                &fns_violations,
+                ctx,
             );
-            violations.push(violation);
-
-            // The matching solidity contract is missing, so we're done.
-            return violations;
-        };
+            let orphans = check_orphans(
+                contract_hir,
+                &contract_sol,
+                &fns_violations,
+                ctx,
+            );
+            let contract_violations =
+                check_contract_names(contract_hir, &contract_sol, ctx)
+                    .into_iter()
+                    .chain(fns_violations)
+                    .chain(modifiers)
+                    .chain(check_base_contracts(&contract_sol, ctx))
+                    .chain(orphans);
+            violations.extend(
+                contract_violations.map(|v| v.with_contract(identifier)),
+            );
+        }
 
-        // We know a contract exists in both trees.
-        violations.append(&mut check_contract_names(
-            contract_hir,
-            &contract_sol,
-            ctx,
-        ));
-        violations.append(&mut check_fns_structure(
-            contract_hir,
-            &contract_sol,
-            ctx,
-        ));
+        // Imports only make sense once the file has one of the contracts.
+        if any_found {
+            violations.append(&mut check_imports(ctx));
+        }
 
         violations
     }
 }
 
+/// Checks that the contract inherits from the configured base contracts.
+fn check_base_contracts(
+    contract_sol: &pt::ContractDefinition,
+    ctx: &Context,
+) -> Vec<Violation> {
+    ctx.cfg
+        .base_contracts
+        .iter()
+        .filter(|name| {
+            !contract_sol.base.iter().any(|base| {
+                base.name.identifiers.last().is_some_and(|i| &i.name == *name)
+            })
+        })
+        .map(|name| {
+            Violation::new(
+                ViolationKind::BaseContractMissing(name.clone()),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, contract_sol.loc.start()),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Checks that the Solidity file contains the configured import directives.
+///
+/// An import directive is considered present if there is an import of the
+/// same path that brings all the configured symbols into scope.
+fn check_imports(ctx: &Context) -> Vec<Violation> {
+    ctx.cfg
+        .imports
+        .iter()
+        .filter(|import| {
+            !ctx.pt.0.iter().any(|part| {
+                let pt::SourceUnitPart::ImportDirective(directive) = part
+                else {
+                    return false;
+                };
+
+                match directive {
+                    pt::Import::Plain(pt::ImportPath::Filename(path), _) => {
+                        path.string == import.path && import.symbols.is_empty()
+                    }
+                    pt::Import::Rename(
+                        pt::ImportPath::Filename(path),
+                        symbols,
+                        _,
+                    ) => {
+                        path.string == import.path
+                            && import.symbols.iter().all(|symbol| {
+                                symbols.iter().any(|(s, _)| &s.name == symbol)
+                            })
+                    }
+                    _ => false,
+                }
+            })
+        })
+        .map(|import| {
+            Violation::new(
+                ViolationKind::ImportMissing(import.clone()),
+                Location::File(ctx.sol.to_string_lossy().into_owned()),
+            )
+        })
+        .collect()
+}
+
+/// Checks that every function and modifier named like generated code is part
+/// of the tree, if [`crate::config::Config::check_orphans`] is set.
+///
+/// The project-wide modifiers file is skipped, since trees that aren't being
+/// checked may use its modifiers. Functions already reported as renamed in
+/// `fns_violations` are skipped too.
+fn check_orphans(
+    contract_hir: &hir::ContractDefinition,
+    contract_sol: &pt::ContractDefinition,
+    fns_violations: &[Violation],
+    ctx: &Context,
+) -> Vec<Violation> {
+    if !ctx.cfg.check_orphans
+        || ctx.cfg.shared_modifiers_file() == Some(ctx.sol.as_path())
+    {
+        return vec![];
+    }
+
+    let renamed: Vec<_> = fns_violations
+        .iter()
+        .filter_map(|v| match &v.kind {
+            ViolationKind::FunctionRenamed(f, _, _) => Some((&f.name, f.ty)),
+            _ => None,
+        })
+        .collect();
+    orphaned_fns(contract_hir, contract_sol, ctx)
+        .into_iter()
+        .filter(|(_, f)| !renamed.contains(&(&f.name, f.ty)))
+        .map(|(_, f)| {
+            Violation::new(
+                ViolationKind::OrphanedFunction(f.clone()),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, f.loc.start()),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Returns the functions and modifiers of `contract_sol`, along with their
+/// index in the contract's parts, that are named like generated code but are
+/// not part of the tree, by name or by anchor.
+fn orphaned_fns<'a>(
+    contract_hir: &hir::ContractDefinition,
+    contract_sol: &'a pt::ContractDefinition,
+    ctx: &Context,
+) -> Vec<(usize, &'a pt::FunctionDefinition)> {
+    let fns_hir: Vec<&hir::FunctionDefinition> = contract_hir
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Hir::Function(f) => Some(f),
+            _ => None,
+        })
+        .collect();
+    let names: HashSet<&str> =
+        fns_hir.iter().map(|f| f.identifier.as_str()).collect();
+    let fn_index = FnIndex::new(contract_sol, &ctx.src);
+    let anchored: HashSet<usize> = fns_hir
+        .iter()
+        .filter(|f| f.anchor.is_some())
+        .filter_map(|f| fn_index.find(f))
+        .map(|(idx, _)| idx)
+        .collect();
+    contract_sol
+        .parts
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, part)| match part {
+            ContractPart::FunctionDefinition(f) => Some((idx, &**f)),
+            _ => None,
+        })
+        .filter(|(idx, f)| {
+            let Some(name) = &f.name else { return false };
+            looks_generated(f, &ctx.cfg)
+                && !names.contains(name.name.as_str())
+                && !anchored.contains(idx)
+        })
+        .collect()
+}
+
+/// The prefixes of the names of generated tests.
+const TEST_PREFIXES: [&str; 3] = ["test_", "testFuzz_", "invariant_"];
+
+/// Whether a Solidity function is named like the code `bulloak scaffold`
+/// generates, i.e. a test, fuzz test or invariant, or a modifier named after a
+/// `when` or `given` condition.
+///
+/// Modifiers don't count if `skip_modifiers` is set, since they aren't
+/// expected in the file then.
+fn looks_generated(f: &pt::FunctionDefinition, cfg: &Config) -> bool {
+    let Some(name) = &f.name else { return false };
+    let name = name.name.as_str();
+    match f.ty {
+        pt::FunctionTy::Function => {
+            TEST_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+        }
+        pt::FunctionTy::Modifier if !cfg.skip_modifiers => {
+            ["when", "given"].iter().any(|keyword| {
+                name.strip_prefix(keyword)
+                    .and_then(|rest| rest.chars().next())
+                    .is_some_and(char::is_uppercase)
+            })
+        }
+        _ => false,
+    }
+}
+
 /// Checks that contract names match.
 fn check_contract_names(
     contract_hir: &hir::ContractDefinition,
@@ -107,7 +315,7 @@ fn check_contract_names(
 
 /// Checks that function structures match between the HIR and the Solidity AST.
 /// i.e. that all the functions are present in the output file in the right
-/// order. This could be better, currently it is O(N^2).
+/// order.
 fn check_fns_structure(
     contract_hir: &hir::ContractDefinition,
     contract_sol: &pt::ContractDefinition,
@@ -119,12 +327,17 @@ fn check_fns_structure(
     // their indices for later processing.
     let mut present_fn_indices =
         Vec::with_capacity(contract_hir.children.len());
+    let mut missing_fns = Vec::new();
+    // Resolving base contracts means reading other files, so we only do it if
+    // a modifier is missing.
+    let mut inherited: Option<HashSet<String>> = None;
+    let fn_index = FnIndex::new(contract_sol, &ctx.src);
     for (hir_idx, fn_hir) in contract_hir.children.iter().enumerate() {
         let Hir::Function(fn_hir) = fn_hir else {
             continue;
         };
 
-        let maybe_matching_fn = find_matching_fn(contract_sol, fn_hir);
+        let maybe_matching_fn = fn_index.find(fn_hir);
         let Some((sol_idx, _)) = maybe_matching_fn else {
             // We didn't find a matching function, so this is a
             // violation.
@@ -135,14 +348,23 @@ fn check_fns_structure(
                 continue;
             }
 
-            violations.push(Violation::new(
-                ViolationKind::MatchingFunctionMissing(fn_hir.clone(), hir_idx),
-                Location::Code(
-                    ctx.tree.to_string_lossy().into_owned(),
-                    fn_hir.span.start.line,
-                ),
-            ));
+            // Modifiers may also be inherited from a base contract.
+            if fn_hir.is_modifier()
+                && inherited
+                    .get_or_insert_with(|| {
+                        inherited_modifiers(
+                            contract_sol,
+                            &ctx.pt,
+                            &ctx.sol,
+                            &ctx.cfg,
+                        )
+                    })
+                    .contains(&fn_hir.identifier)
+            {
+                continue;
+            }
 
+            missing_fns.push((hir_idx, fn_hir));
             continue;
         };
 
@@ -151,30 +373,57 @@ fn check_fns_structure(
         present_fn_indices.push((hir_idx, sol_idx));
     }
 
+    let renames = pair_renames(
+        &missing_fns,
+        &present_fn_indices,
+        contract_hir,
+        contract_sol,
+        ctx,
+    );
+    for ((hir_idx, fn_hir), renamed) in missing_fns.into_iter().zip(renames) {
+        let violation = match renamed {
+            Some(fn_sol) => Violation::new(
+                ViolationKind::FunctionRenamed(
+                    fn_sol.clone(),
+                    fn_hir.clone(),
+                    hir_idx,
+                ),
+                Location::Code(
+                    ctx.sol.to_string_lossy().into_owned(),
+                    offset_to_line(&ctx.src, fn_sol.loc.start()),
+                ),
+            ),
+            None => Violation::new(
+                ViolationKind::MatchingFunctionMissing(fn_hir.clone(), hir_idx),
+                Location::Code(
+                    ctx.tree.to_string_lossy().into_owned(),
+                    fn_hir.span.start.line,
+                ),
+            ),
+        };
+        violations.push(violation);
+    }
+
     // No matching constructs were found. We can just return since we already
     // processed violations in the prev step.
     if present_fn_indices.is_empty() {
         return violations;
     }
 
-    let mut unsorted_set: BTreeSet<(usize, usize)> = BTreeSet::new();
-    // We need to check for inversions in order to know if the order is wrong.
-    for i in 0..present_fn_indices.len() - 1 {
-        let (i_hir_idx, i_sol_idx) = present_fn_indices[i];
-        // Everything that's less than the ith item is unsorted.
-        // If there is at least one element that is less than the
-        // ith item, then, this element is also unsorted.
-        for j in i + 1..present_fn_indices.len() {
-            let (_, j_sol_idx) = present_fn_indices[j];
-            // We found an inversion.
-            if i_sol_idx > j_sol_idx {
-                unsorted_set.insert((i_hir_idx, i_sol_idx));
-            }
-        }
-    }
+    // Functions in the longest increasing subsequence of Solidity indices are
+    // in order relative to each other, so only the rest need to move.
+    let sol_indices: Vec<usize> =
+        present_fn_indices.iter().map(|(_, sol_idx)| *sol_idx).collect();
+    let in_order: HashSet<usize> =
+        longest_increasing_subsequence(&sol_indices).into_iter().collect();
 
     // Emit a violation per unsorted item.
-    for (hir_idx, sol_idx) in unsorted_set {
+    for (pos, (hir_idx, sol_idx)) in present_fn_indices.into_iter().enumerate()
+    {
+        if in_order.contains(&pos) {
+            continue;
+        }
+
         let Hir::Function(_) = contract_hir.children[hir_idx] else {
             continue;
         };
@@ -200,13 +449,232 @@ fn check_fns_structure(
     violations
 }
 
+/// Checks that every function of the tree that is present in the Solidity
+/// contract applies the modifiers of its conditions, in the same order.
+///
+/// Only the modifiers of the tree are compared, so a function may apply any
+/// number of other modifiers. Modifiers already reported as renamed in
+/// `fns_violations` are taken for the modifiers of the tree they were renamed
+/// to.
+fn check_modifiers(
+    contract_hir: &hir::ContractDefinition,
+    contract_sol: &pt::ContractDefinition,
+    fns_violations: &[Violation],
+    ctx: &Context,
+) -> Vec<Violation> {
+    let fns_hir: Vec<&hir::FunctionDefinition> = contract_hir
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Hir::Function(f) => Some(f),
+            _ => None,
+        })
+        .collect();
+    let mut modifiers: HashMap<&str, &str> = fns_hir
+        .iter()
+        .filter(|f| f.is_modifier())
+        .map(|f| f.identifier.as_str())
+        .chain(
+            fns_hir
+                .iter()
+                .flat_map(|f| f.modifiers.iter().flatten())
+                .map(String::as_str),
+        )
+        .map(|name| (name, name))
+        .collect();
+    for violation in fns_violations {
+        if let ViolationKind::FunctionRenamed(fn_sol, fn_hir, _) =
+            &violation.kind
+        {
+            if fn_hir.is_modifier() {
+                modifiers.insert(
+                    fn_sol.name.safe_unwrap().name.as_str(),
+                    fn_hir.identifier.as_str(),
+                );
+            }
+        }
+    }
+
+    let fn_index = FnIndex::new(contract_sol, &ctx.src);
+    let mut violations = Vec::new();
+    for fn_hir in fns_hir.into_iter().filter(|f| !f.is_modifier()) {
+        let Some((_, fn_sol)) = fn_index.find(fn_hir) else {
+            continue;
+        };
+
+        let applied: Vec<&str> = applied_modifiers(fn_sol)
+            .map(|(_, identifier)| identifier.name.as_str())
+            .filter(|name| modifiers.contains_key(name))
+            .collect();
+        let expected = fn_hir.modifiers.as_deref().unwrap_or_default();
+        if applied.iter().map(|name| modifiers[name]).eq(expected) {
+            continue;
+        }
+
+        violations.push(Violation::new(
+            ViolationKind::ModifiersMismatch(
+                fn_sol.clone(),
+                fn_hir.clone(),
+                applied.into_iter().map(str::to_owned).collect(),
+            ),
+            Location::Code(
+                ctx.sol.to_string_lossy().into_owned(),
+                offset_to_line(&ctx.src, fn_sol.loc.start()),
+            ),
+        ));
+    }
+
+    violations
+}
+
+/// The minimum score for an orphaned function to be taken for a renamed one.
+/// See [`pair_renames`].
+const RENAME_MIN_SCORE: usize = 60;
+
+/// The score an orphaned function gets for being where the renamed one is
+/// expected.
+const RENAME_POSITION_SCORE: usize = 25;
+
+/// Pairs the functions of the tree missing from the Solidity contract with
+/// the orphaned functions that most likely are them under their old names.
+///
+/// `missing` holds the missing functions, and `present` the indices in the
+/// tree and in the contract of the functions that are present, both in tree
+/// order.
+///
+/// An orphaned function of the same type scores its [`similarity`] with the
+/// missing function, plus [`RENAME_POSITION_SCORE`] if it is between the
+/// present functions around the missing one. The best scoring pairs above
+/// [`RENAME_MIN_SCORE`] are picked first, and every function is paired at
+/// most once.
+fn pair_renames<'a>(
+    missing: &[(usize, &hir::FunctionDefinition)],
+    present: &[(usize, usize)],
+    contract_hir: &hir::ContractDefinition,
+    contract_sol: &'a pt::ContractDefinition,
+    ctx: &Context,
+) -> Vec<Option<&'a pt::FunctionDefinition>> {
+    let mut paired = vec![None; missing.len()];
+    if missing.is_empty() || !ctx.cfg.detect_renames {
+        return paired;
+    }
+
+    let orphans = orphaned_fns(contract_hir, contract_sol, ctx);
+    let mut pairs = Vec::new();
+    for (missing_idx, (hir_idx, fn_hir)) in missing.iter().enumerate() {
+        let prev = present
+            .iter()
+            .rev()
+            .find(|(idx, _)| idx < hir_idx)
+            .map(|(_, sol_idx)| *sol_idx);
+        let next = present
+            .iter()
+            .find(|(idx, _)| idx > hir_idx)
+            .map(|(_, sol_idx)| *sol_idx);
+        for (orphan_idx, (sol_idx, fn_sol)) in orphans.iter().enumerate() {
+            if fn_hir.is_modifier() != (fn_sol.ty == pt::FunctionTy::Modifier) {
+                continue;
+            }
+
+            let in_place = prev.is_none_or(|prev| prev < *sol_idx)
+                && next.is_none_or(|next| *sol_idx < next);
+            let score =
+                similarity(&fn_hir.identifier, &fn_sol.name.safe_unwrap().name)
+                    + if in_place { RENAME_POSITION_SCORE } else { 0 };
+            if score >= RENAME_MIN_SCORE {
+                pairs.push((score, missing_idx, orphan_idx));
+            }
+        }
+    }
+
+    // Ties go to the functions that come first.
+    pairs.sort_by_key(|&(score, missing_idx, orphan_idx)| {
+        (Reverse(score), missing_idx, orphan_idx)
+    });
+    let mut taken = vec![false; orphans.len()];
+    for (_, missing_idx, orphan_idx) in pairs {
+        if paired[missing_idx].is_none() && !taken[orphan_idx] {
+            paired[missing_idx] = Some(orphans[orphan_idx].1);
+            taken[orphan_idx] = true;
+        }
+    }
+
+    paired
+}
+
+/// Returns how similar two function names are, as a percentage.
+///
+/// This is the Sørensen–Dice coefficient of the pairs of adjacent characters
+/// of both names, ignoring case and the prefixes of generated tests.
+fn similarity(a: &str, b: &str) -> usize {
+    let bigrams = |name: &str| {
+        let name = TEST_PREFIXES
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+            .unwrap_or(name)
+            .to_lowercase();
+        let chars: Vec<char> = name.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect::<Vec<_>>()
+    };
+    let a = bigrams(a);
+    let mut b = bigrams(b);
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0;
+    }
+
+    let mut common = 0;
+    for bigram in &a {
+        if let Some(pos) = b.iter().position(|other| other == bigram) {
+            b.swap_remove(pos);
+            common += 1;
+        }
+    }
+
+    200 * common / total
+}
+
+/// Returns the positions in `xs` of one of its longest strictly increasing
+/// subsequences, in order.
+///
+/// This is the patience sorting algorithm, which runs in `O(n log n)`.
+fn longest_increasing_subsequence(xs: &[usize]) -> Vec<usize> {
+    // `tails[k]` is the position of the smallest element that ends an
+    // increasing subsequence of length `k + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    // The position of the element before each element in the longest
+    // increasing subsequence that ends with it.
+    let mut prev: Vec<Option<usize>> = vec![None; xs.len()];
+    for (pos, x) in xs.iter().enumerate() {
+        let len = tails.partition_point(|&tail| xs[tail] < *x);
+        prev[pos] = len.checked_sub(1).map(|k| tails[k]);
+        if len == tails.len() {
+            tails.push(pos);
+        } else {
+            tails[len] = pos;
+        }
+    }
+
+    let mut subsequence = Vec::with_capacity(tails.len());
+    let mut pos = tails.last().copied();
+    while let Some(p) = pos {
+        subsequence.push(p);
+        pos = prev[p];
+    }
+    subsequence.reverse();
+
+    subsequence
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, io::Write, path::PathBuf};
 
     use tempfile::tempdir;
 
-    use super::StructuralMatcher;
+    use super::{
+        longest_increasing_subsequence, similarity, StructuralMatcher,
+    };
     use crate::{
         check::{context::Context, rules::Checker, violation::ViolationKind},
         config::Config,
@@ -226,12 +694,15 @@ mod tests {
 
     /// Build a Context from given .tree and .t.sol text.
     fn make_ctx(tree_src: &str, sol_src: &str) -> Context {
+        make_ctx_with(tree_src, sol_src, Config::default())
+    }
+
+    fn make_ctx_with(tree_src: &str, sol_src: &str, cfg: Config) -> Context {
         let td = tempdir().unwrap();
         let tree = write_file(td.path(), "X.tree", tree_src);
         let sol = td.path().join("X.t.sol");
         fs::write(&sol, sol_src).unwrap();
-        let mut cfg = Config::default();
-        cfg.files = vec![tree.clone()];
+        let cfg = Config { files: vec![tree.clone()], ..cfg };
         Context::new(tree, &cfg).unwrap()
     }
 
@@ -295,6 +766,53 @@ contract Foo {
         }
     }
 
+    #[test]
+    fn longest_increasing_subsequence_positions() {
+        assert_eq!(Vec::<usize>::new(), longest_increasing_subsequence(&[]));
+        assert_eq!(vec![0, 1, 2], longest_increasing_subsequence(&[0, 1, 2]));
+        assert_eq!(vec![2], longest_increasing_subsequence(&[2, 1, 0]));
+        // Moving 9 to the front only takes it out of the subsequence.
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            longest_increasing_subsequence(&[9, 0, 1, 2, 3])
+        );
+        assert_eq!(
+            vec![1, 2, 4, 5],
+            longest_increasing_subsequence(&[3, 1, 4, 0, 5, 9])
+        );
+    }
+
+    #[test]
+    fn function_order_violation_flags_moved_function_only() {
+        let tree = r#"
+Foo
+├── It one.
+├── It two.
+├── It three.
+└── It four.
+"#;
+        let sol = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_Four() external {}
+  function test_One() external {}
+  function test_Two() external {}
+  function test_Three() external {}
+}
+"#;
+        let ctx = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        match &vs[0].kind {
+            ViolationKind::FunctionOrderMismatch(fn_sol, sol_idx, hir_idx) => {
+                assert_eq!("test_Four", fn_sol.name.as_ref().unwrap().name);
+                assert_eq!((0, 3), (*sol_idx, *hir_idx));
+            }
+            other => panic!("expected FunctionOrderMismatch, got {other:?}"),
+        }
+    }
+
     #[test]
     fn function_order_violation() {
         let tree = r#"
@@ -362,6 +880,178 @@ contract Foo {
         );
     }
 
+    #[test]
+    fn orphaned_functions_are_opt_in() {
+        let tree = r#"
+Foo
+└── When a
+    └── It does stuff.
+"#;
+        let sol = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_WhenA() external {}
+  modifier whenB() {
+    _;
+  }
+  function test_WhenB() external whenB {}
+  function testFuzz_WhenC(uint256 x) external {}
+  modifier onlyOwner() {
+    _;
+  }
+  function helper() internal {}
+}
+"#;
+        let td = tempdir().unwrap();
+        let tree = write_file(td.path(), "A.tree", tree);
+        fs::write(td.path().join("A.t.sol"), sol).unwrap();
+
+        let mut cfg = Config::default();
+        cfg.files = vec![tree.clone()];
+        let ctx = Context::new(tree.clone(), &cfg).unwrap();
+        assert!(StructuralMatcher::check(&ctx).is_empty());
+
+        cfg.check_orphans = true;
+        let ctx = Context::new(tree, &cfg).unwrap();
+        let names: Vec<_> = StructuralMatcher::check(&ctx)
+            .into_iter()
+            .map(|v| match v.kind {
+                ViolationKind::OrphanedFunction(f) => f.name.unwrap().name,
+                other => panic!("expected OrphanedFunction, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(vec!["whenB", "test_WhenB", "testFuzz_WhenC"], names);
+    }
+
+    #[test]
+    fn renamed_functions_detected() {
+        let tree = r#"
+Foo
+├── It should do one thing.
+├── It should do a second thing.
+└── It should do a third thing.
+"#;
+        let sol = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_ShouldDoOneThing() external {}
+  function test_ShouldDoTheSecondThing() external {}
+  function test_ShouldDoAThirdThing() external {}
+  function test_SomethingElse() external {}
+}
+"#;
+        // Renames are only detected when enabled.
+        let ctx = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        assert!(matches!(
+            vs[0].kind,
+            ViolationKind::MatchingFunctionMissing(_, 1)
+        ));
+
+        let cfg = Config { detect_renames: true, ..Config::default() };
+        let ctx = make_ctx_with(tree, sol, cfg);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        match &vs[0].kind {
+            ViolationKind::FunctionRenamed(fn_sol, fn_hir, hir_idx) => {
+                assert_eq!(
+                    "test_ShouldDoTheSecondThing",
+                    fn_sol.name.as_ref().unwrap().name
+                );
+                assert_eq!("test_ShouldDoASecondThing", fn_hir.identifier);
+                assert_eq!(1, *hir_idx);
+            }
+            other => panic!("expected FunctionRenamed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn dissimilar_functions_are_not_renames() {
+        let tree = r#"
+Foo
+├── It should do one thing.
+└── It should revert.
+"#;
+        let sol = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_ShouldDoOneThing() external {}
+  function test_WhenTheBalanceIsZero() external {}
+}
+"#;
+        let cfg = Config { detect_renames: true, ..Config::default() };
+        let ctx = make_ctx_with(tree, sol, cfg);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        assert!(matches!(
+            vs[0].kind,
+            ViolationKind::MatchingFunctionMissing(_, 1)
+        ));
+    }
+
+    #[test]
+    fn modifiers_mismatch_detected() {
+        let tree = r#"
+Foo
+└── When a
+    └── When b
+        ├── When c
+        │   └── It should do one thing.
+        └── It should do another thing.
+"#;
+        let sol = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  modifier whenA() { _; }
+  modifier whenB() { _; }
+  function test_WhenB() external whenA whenB prank(alice) {}
+  function test_WhenC() external whenB whenA {}
+}
+"#;
+        let ctx = make_ctx(tree, sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        match &vs[0].kind {
+            ViolationKind::ModifiersMismatch(fn_sol, fn_hir, applied) => {
+                assert_eq!("test_WhenC", fn_sol.name.as_ref().unwrap().name);
+                assert_eq!(
+                    Some(vec!["whenA".to_owned(), "whenB".to_owned()]),
+                    fn_hir.modifiers
+                );
+                assert_eq!(vec!["whenB", "whenA"], *applied);
+            }
+            other => panic!("expected ModifiersMismatch, got {other:?}"),
+        }
+
+        let sol = sol.replace(
+            "test_WhenC() external whenB whenA",
+            "test_WhenC() external whenA",
+        );
+        let ctx = make_ctx(tree, &sol);
+        let vs = StructuralMatcher::check(&ctx);
+        assert_eq!(1, vs.len());
+        assert!(matches!(
+            &vs[0].kind,
+            ViolationKind::ModifiersMismatch(_, _, applied) if applied == &["whenA"]
+        ));
+    }
+
+    #[test]
+    fn similarity_of_names() {
+        assert_eq!(100, similarity("test_WhenA", "test_WhenA"));
+        assert_eq!(100, similarity("test_WhenA", "testFuzz_WhenA"));
+        assert_eq!(75, similarity("whenA", "whenB"));
+        assert_eq!(0, similarity("test_", "test_A"));
+        assert!(
+            similarity("test_ShouldRevert", "test_WhenTheBalanceIsZero") < 30
+        );
+    }
+
     #[test]
     fn green_path() {
         let tree = r#"Foo
@@ -400,7 +1090,7 @@ contract Foo {
 pragma solidity 0.8.0;
 
 contract Foo {
-    function test_WhenM() {
+    function test_WhenM() whenA {
         // It x.
     }
 }
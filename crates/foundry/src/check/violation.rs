@@ -1,5 +1,9 @@
 //! Defines a rule-checking error object.
-use std::{borrow::Cow, fmt};
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 use bulloak_syntax::FrontendError;
 use forge_fmt::solang_ext::SafeUnwrap;
@@ -7,8 +11,12 @@ use owo_colors::OwoColorize;
 use solang_parser::pt;
 use thiserror::Error;
 
-use super::{context::Context, location::Location};
-use crate::hir;
+use super::{
+    context::Context,
+    location::Location,
+    rules::comments::{expected_comments, normalize},
+};
+use crate::{config, hir};
 
 /// An error that occurred while checking specification rules between
 /// a tree and a Solidity contract.
@@ -19,18 +27,46 @@ pub struct Violation {
     pub kind: ViolationKind,
     /// The location information about this violation.
     pub location: Location,
+    /// The tree file this violation was found for, if known.
+    pub tree: Option<PathBuf>,
+    /// The contract of the tree this violation was found in, if it concerns
+    /// a single contract.
+    pub contract: Option<String>,
 }
 
 impl Violation {
     /// Create a new violation.
     pub fn new(kind: ViolationKind, location: Location) -> Self {
-        Self { kind, location }
+        Self { kind, location, tree: None, contract: None }
+    }
+
+    /// Sets the tree file this violation was found for.
+    ///
+    /// The tree is suggested in the fix hint of fixable violations, since its
+    /// path can't be derived from the Solidity file when paths are mapped.
+    #[must_use]
+    pub fn with_tree(mut self, tree: &Path) -> Self {
+        self.tree = Some(tree.to_path_buf());
+        self
+    }
+
+    /// Sets the contract this violation was found in.
+    #[must_use]
+    pub fn with_contract(mut self, contract: &str) -> Self {
+        self.contract = Some(contract.to_owned());
+        self
     }
 
     /// Determines whether a given violation is fixable.
     pub fn is_fixable(&self) -> bool {
         self.kind.is_fixable()
     }
+
+    /// Returns a new context with this violation fixed in the contract it
+    /// was found in.
+    pub fn fix(&self, ctx: Context) -> anyhow::Result<Context> {
+        self.kind.fix_in(ctx, self.contract.as_deref())
+    }
 }
 
 /// The type of an error that occurred while checking specification rules
@@ -74,6 +110,62 @@ pub enum ViolationKind {
     #[error("function \"{}\" is missing in .sol", .0.identifier.clone())]
     MatchingFunctionMissing(hir::FunctionDefinition, usize),
 
+    /// Found a function or modifier that is not part of the tree, but most
+    /// likely is a function of the tree under its old name, e.g. after a
+    /// condition was reworded.
+    ///
+    /// (pt function, hir function, insertion position)
+    #[error(
+        "{} \"{}\" is named \"{}\" in the tree",
+        if .0.ty == pt::FunctionTy::Modifier { "modifier" } else { "function" },
+        .0.name.safe_unwrap(),
+        .1.identifier
+    )]
+    FunctionRenamed(pt::FunctionDefinition, hir::FunctionDefinition, usize),
+
+    /// Found a function that doesn't apply the modifiers of its conditions,
+    /// or applies them in another order.
+    ///
+    /// (pt function, hir function, names of the tree modifiers it applies)
+    #[error("modifiers of function \"{}\" don't match the tree", .0.name.safe_unwrap())]
+    ModifiersMismatch(
+        pt::FunctionDefinition,
+        hir::FunctionDefinition,
+        Vec<String>,
+    ),
+
+    /// Found a function whose comments don't match the actions of its
+    /// counterpart in the tree, e.g. after an action was reworded.
+    ///
+    /// (pt function, hir function, comments of the function)
+    #[error("comments of function \"{}\" don't match the tree", .0.name.safe_unwrap())]
+    CommentsMismatch(
+        pt::FunctionDefinition,
+        hir::FunctionDefinition,
+        Vec<String>,
+    ),
+
+    /// The contract doesn't inherit from a configured base contract.
+    ///
+    /// (base contract name)
+    #[error("contract doesn't inherit from \"{0}\"")]
+    BaseContractMissing(String),
+
+    /// Found a function or modifier named like generated code that is not
+    /// part of the tree, e.g. the test of a branch that was removed.
+    ///
+    /// (pt function)
+    #[error(
+        "{} \"{}\" is missing in the tree",
+        if .0.ty == pt::FunctionTy::Modifier { "modifier" } else { "function" },
+        .0.name.safe_unwrap()
+    )]
+    OrphanedFunction(pt::FunctionDefinition),
+
+    /// The Solidity file is missing a configured import directive.
+    #[error("import directive `{0}` is missing in .sol")]
+    ImportMissing(config::Import),
+
     /// The parsing of a tree or a Solidity file failed.
     #[error("{}", format_frontend_error(.0))]
     ParsingFailed(#[from] anyhow::Error),
@@ -88,6 +180,12 @@ impl ViolationKind {
                 | ViolationKind::ContractNameNotMatches(_, _)
                 | ViolationKind::FunctionOrderMismatch(_, _, _)
                 | ViolationKind::MatchingFunctionMissing(_, _)
+                | ViolationKind::FunctionRenamed(_, _, _)
+                | ViolationKind::ModifiersMismatch(_, _, _)
+                | ViolationKind::CommentsMismatch(_, _, _)
+                | ViolationKind::BaseContractMissing(_)
+                | ViolationKind::ImportMissing(_)
+                | ViolationKind::OrphanedFunction(_)
         )
     }
 
@@ -109,6 +207,46 @@ impl ViolationKind {
             ViolationKind::FunctionOrderMismatch(_, _, _) => {
                 "consider reordering the function in the file".into()
             }
+            ViolationKind::BaseContractMissing(name) => {
+                format!(r#"consider inheriting from "{name}""#).into()
+            }
+            ViolationKind::FunctionRenamed(_, fn_hir, _) => {
+                format!(r#"consider renaming it to "{}""#, fn_hir.identifier)
+                    .into()
+            }
+            ViolationKind::ModifiersMismatch(_, fn_hir, applied) => {
+                match fn_hir.modifiers.as_deref().unwrap_or_default() {
+                    [] => {
+                        format!(r#"consider removing "{}""#, applied.join(" "))
+                    }
+                    expected => format!(
+                        r#"consider applying "{}", in this order"#,
+                        expected.join(" ")
+                    ),
+                }
+                .into()
+            }
+            ViolationKind::CommentsMismatch(_, fn_hir, comments) => {
+                // Point at the first comment that differs.
+                let expected = expected_comments(fn_hir);
+                let mut comments = comments.iter().map(|c| normalize(c));
+                match expected
+                    .iter()
+                    .flatten()
+                    .find(|e| comments.next() != Some(normalize(e)))
+                {
+                    Some(e) => {
+                        format!(r#"the tree says "{}""#, e.trim()).into()
+                    }
+                    None => "consider removing the comments of actions that \
+                             aren't in the tree"
+                        .into(),
+                }
+            }
+            ViolationKind::OrphanedFunction(_) => {
+                "consider removing it, or adding its branch back to the tree"
+                    .into()
+            }
             _ => return None,
         };
 
@@ -116,15 +254,48 @@ impl ViolationKind {
     }
 
     /// Returns a new context with this violation fixed.
+    ///
+    /// Violations that concern a single contract are fixed in the first
+    /// contract of the tree. See [`Violation::fix`].
     pub fn fix(&self, ctx: Context) -> anyhow::Result<Context> {
+        self.fix_in(ctx, None)
+    }
+
+    /// Returns a new context with this violation fixed in `contract`, or in
+    /// the first contract of the tree if `None`.
+    fn fix_in(
+        &self,
+        ctx: Context,
+        contract: Option<&str>,
+    ) -> anyhow::Result<Context> {
         match self {
-            ViolationKind::ContractMissing(_) => ctx.fix_contract_missing(),
+            ViolationKind::ContractMissing(name) => {
+                ctx.fix_contract_missing(name)
+            }
             ViolationKind::ContractNameNotMatches(new_name, old_name) => {
                 ctx.fix_contract_rename(new_name, old_name)
             }
             // Assume order violations have been taken care of first.
             ViolationKind::MatchingFunctionMissing(fn_hir, index) => {
-                ctx.fix_matching_fn_missing(fn_hir, *index)
+                ctx.fix_matching_fn_missing(contract, fn_hir, *index)
+            }
+            ViolationKind::FunctionRenamed(fn_sol, fn_hir, _) => {
+                ctx.fix_fn_renamed(contract, fn_sol, fn_hir)
+            }
+            ViolationKind::ModifiersMismatch(fn_sol, fn_hir, applied) => {
+                ctx.fix_modifiers_mismatch(contract, fn_sol, fn_hir, applied)
+            }
+            ViolationKind::CommentsMismatch(fn_sol, fn_hir, _) => {
+                ctx.fix_comments_mismatch(contract, fn_sol, fn_hir)
+            }
+            ViolationKind::BaseContractMissing(name) => {
+                ctx.fix_base_contract_missing(contract, name)
+            }
+            ViolationKind::ImportMissing(import) => {
+                ctx.fix_import_missing(import)
+            }
+            ViolationKind::OrphanedFunction(fn_sol) => {
+                ctx.fix_orphaned_fn(contract, fn_sol)
             }
             _ => Ok(ctx),
         }
@@ -138,9 +309,17 @@ impl fmt::Display for Violation {
             writeln!(f, "     {} help: {}", "=".blue(), help_text)?;
         }
         if self.kind.is_fixable() {
-            let file = self.location.file().replace(".t.sol", ".tree");
+            let file = self.tree.as_ref().map_or_else(
+                || self.location.file().replace(".t.sol", ".tree"),
+                |tree| tree.to_string_lossy().into_owned(),
+            );
+            // Orphans are only removed on request.
+            let prune = match self.kind {
+                ViolationKind::OrphanedFunction(_) => " --prune",
+                _ => "",
+            };
             write!(f, "     {} fix: run ", "+".blue())?;
-            writeln!(f, "`bulloak check --fix {file}`")?;
+            writeln!(f, "`bulloak check --fix{prune} {file}`")?;
         }
         writeln!(f, "   {} {}", "-->".blue(), self.location)?;
 
@@ -176,6 +355,23 @@ impl PartialEq for ViolationKind {
             {
                 f1.identifier == f2.identifier && pos1 == pos2
             }
+            (FunctionRenamed(f1, h1, pos1), FunctionRenamed(f2, h2, pos2)) => {
+                f1.name == f2.name
+                    && f1.ty == f2.ty
+                    && h1.identifier == h2.identifier
+                    && pos1 == pos2
+            }
+            (ModifiersMismatch(f1, h1, a1), ModifiersMismatch(f2, h2, a2)) => {
+                f1.name == f2.name && h1.modifiers == h2.modifiers && a1 == a2
+            }
+            (CommentsMismatch(f1, h1, c1), CommentsMismatch(f2, h2, c2)) => {
+                f1.name == f2.name && h1.identifier == h2.identifier && c1 == c2
+            }
+            (BaseContractMissing(a), BaseContractMissing(b)) => a == b,
+            (ImportMissing(a), ImportMissing(b)) => a == b,
+            (OrphanedFunction(f1), OrphanedFunction(f2)) => {
+                f1.name == f2.name && f1.ty == f2.ty
+            }
             (ParsingFailed(e1), ParsingFailed(e2)) =>
             // Compare on the formatted error message.
             {
@@ -208,6 +404,14 @@ fn format_frontend_error(error: &anyhow::Error) -> String {
         error.downcast_ref::<crate::hir::combiner::Error>()
     {
         format!("an error occurred while parsing the tree: {}", error.kind())
+    } else if let Some(error) =
+        error.downcast_ref::<bulloak_syntax::front_matter::Error>()
+    {
+        format!("an error occurred while parsing the tree: {}", error.kind())
+    } else if let Some(error) =
+        error.downcast_ref::<crate::config::FrontMatterError>()
+    {
+        format!("an error occurred while parsing the tree: {error}")
     } else if error.downcast_ref::<bulloak_syntax::semantics::Error>().is_some()
     {
         "at least one semantic error occurred while parsing the tree".to_owned()
@@ -1,8 +1,23 @@
 //! `bulloak-core`'s configuration.
 
-use std::path::PathBuf;
+use std::{
+    fmt,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
 
-use crate::constants::DEFAULT_SOL_VERSION;
+use bulloak_syntax::{utils::upper_first_letter, Ast, FrontMatter};
+use forge_fmt::FormatterConfig;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::constants::{
+    CONTRACT_IDENTIFIER_SEPARATOR, DEFAULT_FILE_NAME, DEFAULT_LICENSE,
+    DEFAULT_SOL_VERSION, DEFAULT_SPLIT_CONTRACT_NAME, DEFAULT_SPLIT_FILE_NAME,
+    FORGE_STD_TEST_CONTRACT,
+};
 
 /// `bulloak-core`'s configuration.
 ///
@@ -10,15 +25,84 @@ use crate::constants::DEFAULT_SOL_VERSION;
 /// struct only if it makes sense. For example, the `--fix` flag, doesn't make
 /// sense in the context of `bulloak-core`.
 #[derive(Debug, Clone)]
+// Each bool is an independent switch coming from a command line flag.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     /// The set of tree files to work on.
     pub files: Vec<PathBuf>,
     /// Whether to emit modifiers.
     pub skip_modifiers: bool,
     /// Sets a Solidity version for the test contracts.
+    ///
+    /// This can be any version requirement accepted by Solidity's `pragma`
+    /// directive, e.g. `>=0.8.22 <0.9.0`.
     pub solidity_version: String,
+    /// The SPDX license identifier of the emitted files.
+    pub license: String,
     /// Whether to add `vm.skip(true)` at the beginning of each test.
     pub emit_vm_skip: bool,
+    /// Whether to add `vm.expectEmit()` skeletons for actions that describe
+    /// emitted events.
+    pub emit_expect_emit: bool,
+    /// User-defined rules that turn matching actions into Solidity snippets.
+    pub action_rules: Vec<ActionRule>,
+    /// The contracts test contracts inherit from.
+    ///
+    /// When empty, test contracts inherit from forge-std's `Test` if needed.
+    pub base_contracts: Vec<String>,
+    /// Additional import directives for the emitted files.
+    pub imports: Vec<Import>,
+    /// Whether to add a `setUp` function to test contracts.
+    pub emit_set_up: bool,
+    /// Whether to add an anchor to test functions, i.e. a
+    /// `/// @custom:bulloak <anchor>` comment that identifies the branch of
+    /// the tree they were generated from.
+    pub emit_anchors: bool,
+    /// The `forge fmt` settings used to format the emitted code.
+    pub formatter_config: FormatterConfig,
+    /// Mappings from the directories of `.tree` files to the directories of
+    /// their Solidity files. The first matching mapping applies.
+    pub dir_mappings: Vec<DirMapping>,
+    /// The name of the Solidity file of a `.tree` file.
+    ///
+    /// Can reference the `{tree}`, `{contract}`, `{function}` and
+    /// `{Function}` placeholders. See [`Config::sol_path`]. Defaults to
+    /// `{tree}.t.sol`, or `{function}.t.sol` when splitting trees.
+    pub file_name: Option<String>,
+    /// Whether to emit one file and contract per root of a `.tree` file whose
+    /// roots are all of the form `Contract::function`.
+    pub split_trees: bool,
+    /// The name of the contract of each file when splitting trees.
+    ///
+    /// Accepts the same placeholders as [`Config::file_name`].
+    pub contract_name: String,
+    /// The file holding the modifiers of every test contract, e.g.
+    /// `test/Modifiers.sol`.
+    ///
+    /// When set, modifiers are emitted once, in an abstract `Modifiers`
+    /// contract that test contracts inherit from, instead of in each file.
+    pub modifiers_file: Option<PathBuf>,
+    /// The root directory of the project.
+    ///
+    /// Import paths that are neither relative nor remapped resolve against
+    /// it, as do the targets of [`Config::remappings`]. Defaults to the
+    /// current directory.
+    pub root: PathBuf,
+    /// The import remappings of the project, e.g.
+    /// `forge-std/=lib/forge-std/src/`.
+    pub remappings: Vec<Remapping>,
+    /// Whether to report functions and modifiers that are named like
+    /// generated ones, but are not part of the tree.
+    pub check_orphans: bool,
+    /// Whether to report a missing function or modifier that most likely was
+    /// renamed as such, so that fixing it renames the existing one instead of
+    /// adding another.
+    pub detect_renames: bool,
+    /// Whether to report tests whose action comments don't match the
+    /// actions of the tree.
+    pub check_comments: bool,
+    /// What fixing an orphaned function or modifier does to it.
+    pub prune: Prune,
 }
 
 impl Default for Config {
@@ -26,8 +110,745 @@ impl Default for Config {
         Self {
             files: vec![],
             solidity_version: DEFAULT_SOL_VERSION.to_owned(),
+            license: DEFAULT_LICENSE.to_owned(),
             emit_vm_skip: false,
+            emit_expect_emit: false,
+            action_rules: vec![],
             skip_modifiers: false,
+            base_contracts: vec![],
+            imports: vec![],
+            emit_set_up: false,
+            emit_anchors: false,
+            formatter_config: FormatterConfig::default(),
+            dir_mappings: vec![],
+            file_name: None,
+            split_trees: false,
+            contract_name: DEFAULT_SPLIT_CONTRACT_NAME.to_owned(),
+            modifiers_file: None,
+            root: PathBuf::new(),
+            remappings: vec![],
+            check_orphans: false,
+            detect_renames: false,
+            check_comments: true,
+            prune: Prune::Delete,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the path of the Solidity file that matches a `.tree` file.
+    ///
+    /// The directory of the tree is rewritten by the first matching
+    /// [`DirMapping`], and the file is named after [`Config::file_name`],
+    /// whose placeholders expand to:
+    ///
+    /// - `{tree}`: the file name of the tree, without its extension.
+    /// - `{contract}`: the contract name of the first tree in `text`.
+    /// - `{function}`: the function name of the first tree in `text`, e.g.
+    ///   `bar` for `Foo::bar`. Empty if there is none.
+    /// - `{Function}`: the same function name, starting with an uppercase
+    ///   letter.
+    #[must_use]
+    pub fn sol_path(&self, tree: &Path, text: &str) -> PathBuf {
+        self.root_sol_path(tree, &root_name(text).unwrap_or_default())
+    }
+
+    /// Returns the path of the Solidity file of a tree whose root is `root`,
+    /// e.g. `Foo::bar`.
+    ///
+    /// See [`Config::sol_path`].
+    #[must_use]
+    pub fn root_sol_path(&self, tree: &Path, root: &str) -> PathBuf {
+        let dir = tree.parent().unwrap_or_else(|| Path::new(""));
+        let dir = self
+            .dir_mappings
+            .iter()
+            .find_map(|mapping| mapping.apply(dir))
+            .unwrap_or_else(|| dir.to_path_buf());
+        let template =
+            self.file_name.as_deref().unwrap_or(if self.split_trees {
+                DEFAULT_SPLIT_FILE_NAME
+            } else {
+                DEFAULT_FILE_NAME
+            });
+
+        dir.join(expand(template, tree, root))
+    }
+
+    /// Returns the name of the contract emitted for a tree whose root is
+    /// `root` when splitting trees.
+    ///
+    /// See [`Config::contract_name`].
+    #[must_use]
+    pub fn split_contract_name(&self, tree: &Path, root: &str) -> String {
+        expand(&self.contract_name, tree, root)
+    }
+
+    /// Returns the base contracts that define a `setUp` function, which the
+    /// `setUp` function of test contracts overrides and calls.
+    ///
+    /// That is every base contract but forge-std's `Test`.
+    pub fn set_up_bases(&self) -> impl Iterator<Item = &str> {
+        self.base_contracts
+            .iter()
+            .map(String::as_str)
+            .filter(|base| *base != FORGE_STD_TEST_CONTRACT)
+    }
+
+    /// Whether any base contract defines a `setUp` function. See
+    /// [`Config::set_up_bases`].
+    #[must_use]
+    pub fn inherits_set_up(&self) -> bool {
+        self.set_up_bases().next().is_some()
+    }
+
+    /// Returns the project-wide modifiers file, if modifiers are emitted to
+    /// one. See [`Config::modifiers_file`].
+    #[must_use]
+    pub fn shared_modifiers_file(&self) -> Option<&Path> {
+        self.modifiers_file.as_deref().filter(|_| !self.skip_modifiers)
+    }
+
+    /// Resolves the path of an import directive in the Solidity file at
+    /// `file`.
+    ///
+    /// Relative paths, e.g. `./Base.sol`, resolve against the directory of
+    /// `file`. Other paths are remapped by the remapping with the longest
+    /// matching prefix, if any, and resolve against [`Config::root`]. Among
+    /// remappings with the same prefix, the first one wins.
+    #[must_use]
+    pub fn resolve_import(&self, import: &str, file: &Path) -> PathBuf {
+        if import.starts_with("./") || import.starts_with("../") {
+            let dir = file.parent().unwrap_or_else(|| Path::new(""));
+            return dir.join(import);
+        }
+
+        let file = normalize(file);
+        let file = file.strip_prefix(normalize(&self.root)).unwrap_or(&file);
+        let remapped = self
+            .remappings
+            .iter()
+            .rev()
+            .filter(|remapping| {
+                import.starts_with(&remapping.prefix)
+                    && remapping
+                        .context
+                        .as_ref()
+                        .is_none_or(|context| file.starts_with(context))
+            })
+            .max_by_key(|remapping| remapping.prefix.len())
+            .map(|remapping| {
+                format!(
+                    "{}{}",
+                    remapping.target,
+                    &import[remapping.prefix.len()..]
+                )
+            });
+
+        self.root.join(remapped.as_deref().unwrap_or(import))
+    }
+
+    /// Returns a copy of this config with the settings of a `.tree` file's
+    /// front matter applied on top.
+    ///
+    /// Keys are named after `bulloak scaffold`'s options, e.g.
+    /// `solidity-version` or `vm-skip`. Setting `base` or `import` replaces
+    /// the values passed on the command line; both keys can be repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key is unknown or its value is invalid.
+    pub fn with_front_matter(
+        &self,
+        front_matter: &FrontMatter,
+    ) -> Result<Self, FrontMatterError> {
+        let mut cfg = self.clone();
+        let mut base_contracts = None;
+        let mut imports = None;
+        for entry in &front_matter.entries {
+            let value = entry.value.as_str();
+            let invalid = |reason: String| FrontMatterError::ValueInvalid {
+                key: entry.key.clone(),
+                value: value.to_owned(),
+                reason,
+            };
+            let parse_bool = || {
+                value.parse::<bool>().map_err(|_| {
+                    invalid("expected `true` or `false`".to_owned())
+                })
+            };
+
+            match entry.key.as_str() {
+                "solidity-version" => {
+                    if !is_version_req(value) {
+                        return Err(invalid(
+                            "expected a version requirement".to_owned(),
+                        ));
+                    }
+                    value.clone_into(&mut cfg.solidity_version);
+                }
+                "license" => value.clone_into(&mut cfg.license),
+                "vm-skip" => cfg.emit_vm_skip = parse_bool()?,
+                "expect-emit" => cfg.emit_expect_emit = parse_bool()?,
+                "skip-modifiers" => cfg.skip_modifiers = parse_bool()?,
+                "set-up" => cfg.emit_set_up = parse_bool()?,
+                "anchors" => cfg.emit_anchors = parse_bool()?,
+                "base" => base_contracts.get_or_insert_with(Vec::new).extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|base| !base.is_empty())
+                        .map(ToOwned::to_owned),
+                ),
+                "import" => imports
+                    .get_or_insert_with(Vec::new)
+                    .push(value.parse::<Import>().map_err(invalid)?),
+                key => {
+                    return Err(FrontMatterError::KeyUnknown(key.to_owned()))
+                }
+            }
+        }
+
+        if let Some(base_contracts) = base_contracts {
+            cfg.base_contracts = base_contracts;
+        }
+        if let Some(imports) = imports {
+            cfg.imports = imports;
+        }
+
+        Ok(cfg)
+    }
+}
+
+/// An error that occurred while applying the front matter of a `.tree` file.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FrontMatterError {
+    /// The front matter declares an unsupported setting.
+    #[error("unknown front matter key `{0}`")]
+    KeyUnknown(String),
+    /// The value of a setting is invalid.
+    #[error("invalid value `{value}` for front matter key `{key}`: {reason}")]
+    ValueInvalid {
+        /// The name of the setting.
+        key: String,
+        /// The invalid value.
+        value: String,
+        /// Why the value is invalid.
+        reason: String,
+    },
+}
+
+/// A Solidity version, optionally with wildcards, e.g. `0.8.x`.
+const VERSION: &str = r"\d+(?:\.(?:\d+|[*xX])){0,2}";
+
+/// Matches the version requirements accepted by Solidity's `pragma`
+/// directive.
+///
+/// A requirement is a list of ranges separated by `||`, where a range is
+/// either a hyphen range like `0.8.0 - 0.8.20` or a set of space-separated
+/// comparators like `>=0.8.22 <0.9.0`.
+static VERSION_REQ_REGEX: Lazy<Regex> = Lazy::new(|| {
+    let comparator = format!(r"(?:\^|~|>=|<=|>|<|=)?\s*{VERSION}");
+    let range = format!(
+        r"(?:{VERSION}\s+-\s+{VERSION}|{comparator}(?:\s+{comparator})*)"
+    );
+    Regex::new(&format!(r"^\s*{range}(?:\s*\|\|\s*{range})*\s*$")).unwrap()
+});
+
+/// Whether `version` is a valid version requirement for Solidity's `pragma`
+/// directive, e.g. `0.8.0`, `^0.8.0` or `>=0.8.22 <0.9.0`.
+#[must_use]
+pub fn is_version_req(version: &str) -> bool {
+    VERSION_REQ_REGEX.is_match(version)
+}
+
+/// An import directive added to the emitted files.
+///
+/// Parsed from `SYMBOLS=PATH`, where `SYMBOLS` is a comma-separated list, or
+/// from a plain `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// The symbols imported from `path`. Empty for plain imports.
+    pub symbols: Vec<String>,
+    /// The path of the imported file.
+    pub path: String,
+}
+
+impl Import {
+    /// The import of forge-std's `Test` contract.
+    pub(crate) fn forge_std() -> Self {
+        Self {
+            symbols: vec!["Test".to_owned()],
+            path: "forge-std/Test.sol".to_owned(),
+        }
+    }
+}
+
+impl FromStr for Import {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (symbols, path) = match s.split_once('=') {
+            Some((symbols, path)) => (
+                symbols
+                    .split(',')
+                    .map(|symbol| symbol.trim().to_owned())
+                    .collect(),
+                path,
+            ),
+            None => (vec![], s),
+        };
+
+        let path = path.trim();
+        if path.is_empty() || symbols.iter().any(String::is_empty) {
+            return Err(format!(
+                "expected an import of the form `[SYMBOLS=]PATH`, found `{s}`"
+            ));
+        }
+
+        Ok(Self { symbols, path: path.to_owned() })
+    }
+}
+
+impl fmt::Display for Import {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.symbols.is_empty() {
+            write!(f, r#"import "{}";"#, self.path)
+        } else {
+            let symbols = self.symbols.join(", ");
+            write!(f, r#"import {{{symbols}}} from "{}";"#, self.path)
+        }
+    }
+}
+
+/// Returns the root name of the first tree in `text`, e.g. `Foo::bar`.
+fn root_name(text: &str) -> Option<String> {
+    bulloak_syntax::parse(text).ok()?.into_iter().find_map(|ast| match ast {
+        Ast::Root(root) => Some(root.contract_name),
+        _ => None,
+    })
+}
+
+/// Expands the placeholders of a file or contract name template for a tree
+/// whose root is `root`.
+fn expand(template: &str, tree: &Path, root: &str) -> String {
+    let stem = tree.file_stem().unwrap_or_default().to_string_lossy();
+    let (contract, function) =
+        root.split_once(CONTRACT_IDENTIFIER_SEPARATOR).unwrap_or((root, ""));
+    let function = function.trim();
+
+    template
+        .replace("{tree}", &stem)
+        .replace("{contract}", contract.trim())
+        .replace("{function}", function)
+        .replace("{Function}", &upper_first_letter(function))
+}
+
+/// The placeholders accepted by [`Config::file_name`] and
+/// [`Config::contract_name`].
+const NAME_PLACEHOLDERS: [&str; 4] =
+    ["tree", "contract", "function", "Function"];
+
+/// Matches the placeholders of a file name template, e.g. `{contract}`.
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([^{}]*)\}").unwrap());
+
+/// Whether `template` is a valid file or contract name template, e.g.
+/// `{contract}.t.sol` or `{Function}_Test`.
+#[must_use]
+pub fn is_name_template(template: &str) -> bool {
+    !template.trim().is_empty()
+        && PLACEHOLDER_REGEX
+            .captures_iter(template)
+            .all(|captures| NAME_PLACEHOLDERS.contains(&&captures[1]))
+}
+
+/// Maps the `.tree` files of a directory to Solidity files in another one.
+///
+/// Parsed from `FROM=TO`, e.g. `specs=test/unit/concrete`. Subdirectories of
+/// `FROM` are preserved in `TO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirMapping {
+    /// The directory of the `.tree` files.
+    pub from: PathBuf,
+    /// The directory of the Solidity files.
+    pub to: PathBuf,
+}
+
+impl DirMapping {
+    /// Returns the directory `dir` maps to, if it is inside `from`.
+    fn apply(&self, dir: &Path) -> Option<PathBuf> {
+        let dir = normalize(dir);
+        let rest = dir.strip_prefix(normalize(&self.from)).ok()?;
+        Some(self.to.join(rest))
+    }
+}
+
+impl FromStr for DirMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to))
+                if !from.trim().is_empty() && !to.trim().is_empty() =>
+            {
+                Ok(Self { from: from.trim().into(), to: to.trim().into() })
+            }
+            _ => Err(format!(
+                "expected a mapping of the form `FROM=TO`, found `{s}`"
+            )),
+        }
+    }
+}
+
+/// How orphaned functions and modifiers, i.e. generated code whose branch
+/// was removed from the tree, are pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prune {
+    /// Deletes them, along with the comments right above them.
+    Delete,
+    /// Comments them out.
+    Comment,
+}
+
+impl FromStr for Prune {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "delete" => Ok(Self::Delete),
+            "comment" => Ok(Self::Comment),
+            _ => Err(format!("expected `delete` or `comment`, found `{s}`")),
+        }
+    }
+}
+
+/// An import remapping, as understood by `solc` and Foundry.
+///
+/// Parsed from `[CONTEXT:]PREFIX=TARGET`, e.g.
+/// `@openzeppelin/=lib/openzeppelin-contracts/`. When set, the remapping only
+/// applies to the files in `CONTEXT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remapping {
+    /// The directory of the files the remapping applies to, if restricted.
+    pub context: Option<String>,
+    /// The prefix of the import paths that are remapped.
+    pub prefix: String,
+    /// The path that replaces the prefix.
+    pub target: String,
+}
+
+impl FromStr for Remapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (context, remapping) = match s.split_once(':') {
+            Some((context, remapping)) if !remapping.contains(':') => {
+                (Some(context.trim()), remapping)
+            }
+            _ => (None, s),
+        };
+
+        match remapping.split_once('=') {
+            Some((prefix, target)) if !prefix.trim().is_empty() => Ok(Self {
+                context: context
+                    .filter(|context| !context.is_empty())
+                    .map(str::to_owned),
+                prefix: prefix.trim().to_owned(),
+                target: target.trim().to_owned(),
+            }),
+            _ => Err(format!(
+                "expected a remapping of the form `[CONTEXT:]PREFIX=TARGET`, \
+                 found `{s}`"
+            )),
         }
     }
 }
+
+/// Removes the `.` components of a path, so that `./specs` and `specs` are
+/// considered the same directory.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+/// A user-defined rule that maps an action to a Solidity snippet.
+///
+/// When an action's title matches `pattern`, `snippet` is inserted in the body
+/// of the generated test. The snippet may reference the pattern's capture
+/// groups with `$1` or `${name}` placeholders.
+#[derive(Debug, Clone)]
+pub struct ActionRule {
+    /// The pattern that action titles are matched against.
+    pub pattern: Regex,
+    /// The Solidity code emitted for matching actions.
+    pub snippet: String,
+}
+
+impl ActionRule {
+    /// Creates a new rule, compiling `pattern` into a regular expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` is not a valid regular expression.
+    pub fn new(pattern: &str, snippet: &str) -> Result<Self, regex::Error> {
+        Ok(Self { pattern: Regex::new(pattern)?, snippet: snippet.to_owned() })
+    }
+
+    /// Returns the snippet for `title`, with its placeholders expanded, if
+    /// this rule matches.
+    #[must_use]
+    pub fn apply(&self, title: &str) -> Option<String> {
+        let captures = self.pattern.captures(title.trim())?;
+        let mut snippet = String::with_capacity(self.snippet.len());
+        captures.expand(&self.snippet, &mut snippet);
+
+        Some(snippet)
+    }
+}
+
+impl FromStr for ActionRule {
+    type Err = String;
+
+    /// Parses a rule of the form `PATTERN=>SNIPPET`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, snippet) = s.split_once("=>").ok_or_else(|| {
+            format!(
+                "expected a rule of the form `PATTERN=>SNIPPET`, found `{s}`"
+            )
+        })?;
+
+        Self::new(pattern.trim(), snippet.trim())
+            .map_err(|e| format!("invalid action rule: {e}"))
+    }
+}
+
+/// The form of an [`ActionRule`] in configuration files, i.e. a
+/// `{ pattern = "...", snippet = "..." }` table.
+#[derive(Serialize, Deserialize)]
+struct RawActionRule {
+    pattern: String,
+    snippet: String,
+}
+
+impl Serialize for ActionRule {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        RawActionRule {
+            pattern: self.pattern.as_str().to_owned(),
+            snippet: self.snippet.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ActionRule {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let raw = RawActionRule::deserialize(deserializer)?;
+        Self::new(&raw.pattern, &raw.snippet).map_err(|e| {
+            serde::de::Error::custom(format!("invalid action rule: {e}"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use bulloak_syntax::front_matter;
+
+    use super::{
+        is_name_template, is_version_req, ActionRule, Config, DirMapping,
+        FrontMatterError, Import, Remapping,
+    };
+
+    #[test]
+    fn applies_action_rules() {
+        let rule = ActionRule::new(
+            r"(?i)^it should transfer (?P<amount>\w+) tokens",
+            "token.transfer(recipient, ${amount});",
+        )
+        .unwrap();
+
+        assert_eq!(
+            rule.apply("It should transfer 100 tokens."),
+            Some("token.transfer(recipient, 100);".to_owned())
+        );
+        assert_eq!(rule.apply("it should revert"), None);
+
+        let rule =
+            ActionRule::new(r"^it should update the (\w+)", "// $1").unwrap();
+        assert_eq!(
+            rule.apply("it should update the balance"),
+            Some("// balance".to_owned())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(ActionRule::new("(unclosed", "").is_err());
+        assert!("(unclosed=>".parse::<ActionRule>().is_err());
+        assert!("no arrow".parse::<ActionRule>().is_err());
+
+        let rule: ActionRule = r" ^it should (\w+) => // $1 ".parse().unwrap();
+        assert_eq!(rule.pattern.as_str(), r"^it should (\w+)");
+        assert_eq!(rule.snippet, "// $1");
+    }
+
+    #[test]
+    fn validates_version_reqs() {
+        for version in [
+            "0.8.0",
+            "^0.8.0",
+            "~0.8",
+            ">=0.8.22 <0.9.0",
+            ">= 0.8.22 < 0.9.0",
+            "0.8.0 - 0.8.20",
+            "0.7.6 || >=0.8.0 <0.9.0",
+            "0.8.x",
+        ] {
+            assert!(is_version_req(version), "{version}");
+        }
+
+        for version in ["", "latest", "0.8.0;", ">=0.8.22 <", "0.8.0.1"] {
+            assert!(!is_version_req(version), "{version}");
+        }
+    }
+
+    #[test]
+    fn applies_front_matter() {
+        let mut cfg = Config::default();
+        cfg.base_contracts = vec!["Test".to_owned()];
+        let text = "---\nsolidity-version: ^0.8.22\nvm-skip: true\nbase: Base_Test, Utils\nbase: Constants\n---\nFoo\n";
+        let (front_matter, _) = front_matter::split(text).unwrap();
+        let cfg = cfg.with_front_matter(&front_matter).unwrap();
+
+        assert_eq!(cfg.solidity_version, "^0.8.22");
+        assert!(cfg.emit_vm_skip);
+        assert!(!cfg.emit_expect_emit);
+        assert_eq!(cfg.base_contracts, vec!["Base_Test", "Utils", "Constants"]);
+
+        let text = "---\nsolidity: 0.8.0\n---\nFoo\n";
+        let (front_matter, _) = front_matter::split(text).unwrap();
+        assert_eq!(
+            Config::default().with_front_matter(&front_matter).unwrap_err(),
+            FrontMatterError::KeyUnknown("solidity".to_owned())
+        );
+
+        let text = "---\nset-up: yes\n---\nFoo\n";
+        let (front_matter, _) = front_matter::split(text).unwrap();
+        assert!(matches!(
+            Config::default().with_front_matter(&front_matter),
+            Err(FrontMatterError::ValueInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_imports() {
+        let import: Import =
+            "Base_Test, Utils=test/Base.t.sol".parse().unwrap();
+        assert_eq!(import.symbols, vec!["Base_Test", "Utils"]);
+        assert_eq!(import.path, "test/Base.t.sol");
+        assert_eq!(
+            import.to_string(),
+            r#"import {Base_Test, Utils} from "test/Base.t.sol";"#
+        );
+
+        let import: Import = "test/Base.t.sol".parse().unwrap();
+        assert!(import.symbols.is_empty());
+        assert_eq!(import.to_string(), r#"import "test/Base.t.sol";"#);
+
+        assert!("Base_Test=".parse::<Import>().is_err());
+        assert!("=test/Base.t.sol".parse::<Import>().is_err());
+    }
+
+    #[test]
+    fn maps_sol_paths() {
+        let text = "Foo::bar\n└── it should work";
+        let tree = Path::new("./specs/token/bar.tree");
+
+        let mut cfg = Config::default();
+        assert_eq!(
+            cfg.sol_path(tree, text),
+            PathBuf::from("./specs/token/bar.t.sol")
+        );
+
+        cfg.dir_mappings = vec![
+            "other=test".parse().unwrap(),
+            "specs=test/unit/concrete".parse().unwrap(),
+        ];
+        cfg.file_name = Some("{contract}.{function}.t.sol".to_owned());
+        assert_eq!(
+            cfg.sol_path(tree, text),
+            PathBuf::from("test/unit/concrete/token/Foo.bar.t.sol")
+        );
+
+        cfg.file_name = Some("{tree}/{contract}{function}.t.sol".to_owned());
+        assert_eq!(
+            cfg.sol_path(Path::new("specs/bar.tree"), "FooTest\n└── it x"),
+            PathBuf::from("test/unit/concrete/bar/FooTest.t.sol")
+        );
+
+        cfg.file_name = None;
+        cfg.split_trees = true;
+        cfg.contract_name = "{Function}_Unit_Test".to_owned();
+        assert_eq!(
+            cfg.root_sol_path(tree, "Foo::bar"),
+            PathBuf::from("test/unit/concrete/token/bar.t.sol")
+        );
+        assert_eq!(cfg.split_contract_name(tree, "Foo::bar"), "Bar_Unit_Test");
+    }
+
+    #[test]
+    fn validates_path_mappings() {
+        assert!(is_name_template("{contract}.t.sol"));
+        assert!(is_name_template("{tree}/{function}.t.sol"));
+        assert!(is_name_template("{Function}_Test"));
+        assert!(!is_name_template("{name}.t.sol"));
+        assert!(!is_name_template(" "));
+
+        let mapping: DirMapping = "specs = test/unit".parse().unwrap();
+        assert_eq!(mapping.from, PathBuf::from("specs"));
+        assert_eq!(mapping.to, PathBuf::from("test/unit"));
+        assert!("specs".parse::<DirMapping>().is_err());
+        assert!("=test".parse::<DirMapping>().is_err());
+    }
+
+    #[test]
+    fn resolves_imports() {
+        let remapping: Remapping = "lib/a:@oz/=lib/a/lib/oz/".parse().unwrap();
+        assert_eq!(remapping.context.as_deref(), Some("lib/a"));
+        assert_eq!(remapping.prefix, "@oz/");
+        assert_eq!(remapping.target, "lib/a/lib/oz/");
+        assert!("lib/oz/".parse::<Remapping>().is_err());
+
+        let mut cfg = Config::default();
+        cfg.root = PathBuf::from("..");
+        cfg.remappings = vec![
+            remapping,
+            "@oz/=lib/oz/".parse().unwrap(),
+            "@oz/=lib/other/".parse().unwrap(),
+            "@oz/token/=lib/token/".parse().unwrap(),
+        ];
+        let file = Path::new("../test/Foo.t.sol");
+        let resolve = |import| cfg.resolve_import(import, file);
+
+        assert_eq!(resolve("./Base.sol"), Path::new("../test/./Base.sol"));
+        assert_eq!(resolve("test/Base.sol"), Path::new("../test/Base.sol"));
+        assert_eq!(
+            resolve("@oz/Ownable.sol"),
+            Path::new("../lib/oz/Ownable.sol")
+        );
+        assert_eq!(
+            resolve("@oz/token/ERC20.sol"),
+            Path::new("../lib/token/ERC20.sol")
+        );
+        assert_eq!(
+            cfg.resolve_import("@oz/Ownable.sol", Path::new("../lib/a/A.sol")),
+            Path::new("../lib/a/lib/oz/Ownable.sol")
+        );
+    }
+}
@@ -4,9 +4,34 @@
 pub(crate) const INTERNAL_DEFAULT_INDENTATION: usize = 2;
 /// Default solidity version used internally.
 pub const DEFAULT_SOL_VERSION: &str = "0.8.0";
+/// Default SPDX license identifier of the emitted files.
+pub const DEFAULT_LICENSE: &str = "UNLICENSED";
+/// Default name of the Solidity file of a `.tree` file.
+pub const DEFAULT_FILE_NAME: &str = "{tree}.t.sol";
+/// Default name of the Solidity files of a split `.tree` file.
+pub const DEFAULT_SPLIT_FILE_NAME: &str = "{function}.t.sol";
+/// Default name of the contracts of a split `.tree` file.
+pub const DEFAULT_SPLIT_CONTRACT_NAME: &str = "{contract}_{Function}_Test";
+/// The suffix of the abstract contract holding the modifiers shared by the
+/// roots of a split `.tree` file, e.g. `Foo_Shared_Test`.
+pub const SHARED_CONTRACT_SUFFIX: &str = "_Shared_Test";
+/// The name of the file holding the shared modifiers of a split `.tree` file.
+pub const SHARED_FILE_NAME: &str = "{tree}.shared.sol";
+/// The name of the abstract contract holding the modifiers of all test
+/// contracts when using a project-wide modifiers file.
+pub const MODIFIERS_CONTRACT_NAME: &str = "Modifiers";
 /// The separator used between contract name and function name when parsing
 /// `.tree` files with multiple trees.
 pub const CONTRACT_IDENTIFIER_SEPARATOR: &str = "::";
 /// The separator used between trees when parsing `.tree` files with multiple
 /// trees.
 pub const TREES_SEPARATOR: &str = "\n\n";
+/// The contract name used in the root of a tree to mark it as an invariant
+/// spec, e.g. `Invariant::Foo`.
+pub const INVARIANT_CONTRACT_IDENTIFIER: &str = "Invariant";
+/// The name of forge-std's test contract, which doesn't define a `setUp`
+/// function.
+pub const FORGE_STD_TEST_CONTRACT: &str = "Test";
+/// The NatSpec tag of the comment that holds the anchor of a test function,
+/// e.g. `/// @custom:bulloak 1a2b3c4d`.
+pub const ANCHOR_TAG: &str = "@custom:bulloak";
@@ -1,6 +1,6 @@
 //! The implementation of a high-level intermediate representation (HIR)
 //! combiner.
-use std::{collections::HashSet, fmt, mem, result};
+use std::{collections::HashSet, fmt, result};
 
 use bulloak_syntax::{utils::upper_first_letter, FrontendError, Span};
 use thiserror::Error;
@@ -46,23 +46,12 @@ impl fmt::Display for Error {
     }
 }
 
-type Identifier = String;
 type Index = usize;
 
 /// The type of an error that occurred while combining HIRs.
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum ErrorKind {
-    /// This happens when the contract name in the identifier of one HIR does
-    /// not match the contract name in the identifier of another HIR.
-    #[error("contract name mismatch: expected '{expected}', found '{actual}'")]
-    ContractNameMismatch {
-        /// The name found in the current tree being analyzed.
-        actual: Identifier,
-        /// The name expected to be found during analysis.
-        expected: Identifier,
-    },
-
     /// No contract name was found in one of the tree roots.
     #[error("contract name missing at tree root #{0}")]
     ContractNameMissing(Index),
@@ -76,7 +65,7 @@ pub enum ErrorKind {
 /// A high-level intermediate representation (HIR) combiner.
 ///
 /// It takes a vector of HIRs and combines them into a single HIR
-/// by appending the function nodes to the root contract node.
+/// by appending the function nodes to the contract node with the same name.
 pub struct Combiner;
 
 impl Default for Combiner {
@@ -94,8 +83,9 @@ impl Combiner {
 
     /// Combines the translated HIRs into a single HIR. HIRs are merged by
     /// iterating over each HIR and merging their children into the contract
-    /// definition of the first HIR, while verifying the contract identifiers
-    /// match and filtering out duplicate modifiers.
+    /// definition named after their contract identifier, while filtering out
+    /// duplicate modifiers. Contracts appear in the order their names first
+    /// appear in.
     pub fn combine(
         self,
         text: &str,
@@ -123,9 +113,8 @@ impl<'t> CombinerI<'t> {
 
     /// Internal implementation of `Combiner::combine`.
     fn combine(&self, hirs: impl Iterator<Item = Hir>) -> Result<Hir> {
-        // For `.tree` files with a single root, we don't need to do any work.
-        let acc_contract = &mut ContractDefinition::default();
-        let mut unique_modifiers = HashSet::new();
+        // Each contract keeps track of its own modifiers.
+        let mut contracts: Vec<(ContractDefinition, HashSet<String>)> = vec![];
 
         for (idx, hir) in hirs.into_iter().enumerate() {
             let Hir::Root(r) = hir else {
@@ -156,50 +145,35 @@ impl<'t> CombinerI<'t> {
                     ));
                 }
 
-                // If the accumulated identifier is empty, we're on the first
-                // contract.
-                if acc_contract.identifier.is_empty() {
-                    // Add modifiers to the list of added modifiers and prefix
-                    // test names.
-                    let children = contract
-                        .children
-                        .into_iter()
-                        .map(|c| prefix_test(c, function_name))
-                        .filter_map(|c| {
-                            collect_modifier(c, &mut unique_modifiers)
-                        })
-                        .collect();
-                    let first_contract = ContractDefinition {
-                        identifier: contract_name.to_owned(),
-                        children,
-                    };
-                    *acc_contract = first_contract;
-                    continue;
-                }
-
-                // If the current contract name doesn't match, we error.
-                if contract_name != acc_contract.identifier {
-                    return Err(self.error(
-                        Span::default(),
-                        ErrorKind::ContractNameMismatch {
-                            actual: contract_name.to_owned(),
-                            expected: acc_contract.identifier.clone(),
-                        },
-                    ));
-                }
-
+                let position = contracts
+                    .iter()
+                    .position(|(acc, _)| acc.identifier == contract_name)
+                    .unwrap_or_else(|| {
+                        contracts.push((
+                            ContractDefinition {
+                                identifier: contract_name.to_owned(),
+                                is_abstract: false,
+                                children: vec![],
+                            },
+                            HashSet::new(),
+                        ));
+                        contracts.len() - 1
+                    });
+                let (acc_contract, unique_modifiers) = &mut contracts[position];
                 let children = update_children(
                     contract.children,
                     function_name,
-                    &mut unique_modifiers,
+                    unique_modifiers,
                 );
                 acc_contract.children.extend(children);
             }
         }
 
-        let root =
-            Root { children: vec![Hir::Contract(mem::take(acc_contract))] };
-        Ok(Hir::Root(root))
+        let children = contracts
+            .into_iter()
+            .map(|(contract, _)| Hir::Contract(contract))
+            .collect();
+        Ok(Hir::Root(Root { children }))
     }
 }
 
@@ -244,7 +218,8 @@ fn collect_modifier(
         return Some(child);
     };
 
-    if f.is_modifier() {
+    // Every root of a tree may define the same `setUp` function.
+    if f.is_modifier() || f.is_set_up() {
         if !unique_modifiers.insert(f.identifier.clone()) {
             return None;
         }
@@ -287,7 +262,11 @@ mod tests {
     }
 
     fn contract(identifier: String, children: Vec<Hir>) -> Hir {
-        Hir::Contract(hir::ContractDefinition { identifier, children })
+        Hir::Contract(hir::ContractDefinition {
+            identifier,
+            is_abstract: false,
+            children,
+        })
     }
 
     fn function(
@@ -302,7 +281,9 @@ mod tests {
             ty,
             span,
             modifiers,
+            params: vec![],
             children,
+            anchor: None,
         })
     }
 
@@ -345,6 +326,47 @@ bulloak error: contract name missing at tree root #2";
         }
     }
 
+    #[test]
+    fn groups_roots_by_contract() {
+        let trees = vec![
+            "Foo::function1\n└── it should work",
+            "Bar::function2\n└── it should work",
+            "Foo::function3\n└── it should work",
+        ];
+        let hirs = trees.iter().map(|tree| translate(tree).unwrap());
+
+        let text = trees.join("\n\n");
+        let hir = combine(&text, hirs).unwrap();
+        let contracts: Vec<_> = hir
+            .contracts()
+            .map(|contract| {
+                let fns: Vec<_> = contract
+                    .children
+                    .iter()
+                    .filter_map(|child| match child {
+                        Hir::Function(f) => Some(f.identifier.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (contract.identifier.as_str(), fns)
+            })
+            .collect();
+
+        assert_eq!(
+            contracts,
+            vec![
+                (
+                    "Foo",
+                    vec![
+                        "test_Function1_ShouldWork",
+                        "test_Function3_ShouldWork"
+                    ]
+                ),
+                ("Bar", vec!["test_Function2_ShouldWork"]),
+            ]
+        );
+    }
+
     #[test]
     fn skips_non_function_children() {
         let trees = vec![
@@ -1,5 +1,7 @@
 //! Defines a high-level intermediate representation (HIR).
 
+use std::fmt;
+
 use bulloak_syntax::Span;
 
 /// A high-level intermediate representation (HIR) that describes
@@ -20,6 +22,8 @@ pub enum Hir {
     Contract(ContractDefinition),
     /// A function definition.
     Function(FunctionDefinition),
+    /// A state variable declaration.
+    StateVariable(StateVariable),
     /// A comment.
     Comment(Comment),
     /// A Statement.
@@ -38,6 +42,21 @@ impl Hir {
         }
     }
 
+    /// Returns the contract objects found starting from a root or a contract
+    /// definition, in order.
+    pub fn contracts(&self) -> impl Iterator<Item = &ContractDefinition> {
+        let children: &[Hir] = match self {
+            Hir::Root(root) => &root.children,
+            hir @ Hir::Contract(_) => std::slice::from_ref(hir),
+            _ => &[],
+        };
+
+        children.iter().filter_map(|child| match child {
+            Hir::Contract(contract) => Some(contract),
+            _ => None,
+        })
+    }
+
     /// Whether this hir is a root.
     pub fn is_root(&self) -> bool {
         matches!(self, Hir::Root(_))
@@ -80,20 +99,24 @@ impl Root {
 pub struct ContractDefinition {
     /// The contract name.
     pub identifier: Identifier,
+    /// Whether the contract is abstract.
+    pub is_abstract: bool,
     /// The children HIR nodes of this node.
     pub children: Vec<Hir>,
 }
 
 /// A function's type.
 ///
-/// Currently, we only care about regular functions (tests)
-/// and modifier functions.
+/// Currently, we only care about regular functions (tests),
+/// modifier functions and the `setUp` function.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FunctionTy {
     /// `function`
     Function,
     /// `modifier`
     Modifier,
+    /// `function setUp() public`
+    SetUp,
 }
 
 impl Default for FunctionTy {
@@ -117,8 +140,18 @@ pub struct FunctionDefinition {
     /// `None` if the function's type is
     /// `FunctionTy::Modifier`.
     pub modifiers: Option<Vec<Identifier>>,
+    /// The parameters of this function.
+    ///
+    /// Only fuzz tests have parameters.
+    pub params: Vec<Parameter>,
     /// The children HIR nodes of this node.
     pub children: Option<Vec<Hir>>,
+    /// A stable identifier of the branch that generated this function.
+    ///
+    /// It is a hash of the contract name and the titles of the branch and
+    /// its ancestors, so it doesn't change when the function is renamed.
+    /// `None` for modifiers and `setUp` functions.
+    pub anchor: Option<String>,
 }
 
 impl FunctionDefinition {
@@ -128,11 +161,73 @@ impl FunctionDefinition {
         matches!(self.ty, FunctionTy::Modifier)
     }
 
-    /// Whether a function's type is `Modifier`.
+    /// Whether a function's type is `Function`.
     #[must_use]
     pub fn is_function(&self) -> bool {
         matches!(self.ty, FunctionTy::Function)
     }
+
+    /// Whether a function's type is `SetUp`.
+    #[must_use]
+    pub fn is_set_up(&self) -> bool {
+        matches!(self.ty, FunctionTy::SetUp)
+    }
+
+    /// Whether this function is a fuzz test.
+    #[must_use]
+    pub fn is_fuzz_test(&self) -> bool {
+        self.is_function() && !self.params.is_empty()
+    }
+}
+
+/// A function parameter HIR node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    /// The Solidity type of the parameter, e.g. `uint128`.
+    pub ty: String,
+    /// The parameter name.
+    pub name: Identifier,
+}
+
+impl Parameter {
+    /// Whether this parameter has an integer type that can be passed to
+    /// forge-std's `bound` helper.
+    #[must_use]
+    pub fn is_integer(&self) -> bool {
+        let bits = self
+            .ty
+            .strip_prefix("uint")
+            .or_else(|| self.ty.strip_prefix("int"));
+        bits.is_some_and(|bits| bits.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    /// Whether this parameter needs a data location when declared in a
+    /// function signature.
+    #[must_use]
+    pub fn is_reference(&self) -> bool {
+        self.ty == "string" || self.ty == "bytes" || self.ty.ends_with(']')
+    }
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_reference() {
+            write!(f, "{} memory {}", self.ty, self.name)
+        } else {
+            write!(f, "{} {}", self.ty, self.name)
+        }
+    }
+}
+
+/// A state variable declaration HIR node.
+///
+/// State variables are always emitted with `internal` visibility.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateVariable {
+    /// The Solidity type of the variable, e.g. `FooHandler`.
+    pub ty: Identifier,
+    /// The variable name.
+    pub identifier: Identifier,
 }
 
 /// A comment node.
@@ -147,6 +242,14 @@ pub struct Comment {
 pub enum StatementType {
     /// The `vm.skip(true);` statement.
     VmSkip,
+    /// The `vm.expectEmit();` statement.
+    VmExpectEmit,
+    /// An `emit <Event>();` statement.
+    ///
+    /// The event arguments are left for the user to fill in.
+    Emit(Identifier),
+    /// A line of user-provided Solidity code, emitted as-is.
+    Snippet(String),
 }
 
 /// A statement node.
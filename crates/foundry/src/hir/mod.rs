@@ -9,9 +9,12 @@ pub use hir::*;
 pub mod translator;
 pub mod visitor;
 
-use bulloak_syntax::Ast;
+use bulloak_syntax::{Ast, Span};
 
-use crate::{config::Config, scaffold::modifiers::ModifierDiscoverer};
+use crate::{
+    config::Config, hir::translator::invariant_target,
+    scaffold::modifiers::ModifierDiscoverer,
+};
 
 /// Translates the contents of a `.tree` file into a HIR.
 ///
@@ -26,12 +29,73 @@ use crate::{config::Config, scaffold::modifiers::ModifierDiscoverer};
 pub fn translate(text: &str, cfg: &Config) -> anyhow::Result<Hir> {
     let asts = bulloak_syntax::parse(text)?;
 
+    translate_all(text, asts, cfg)
+}
+
+/// Translates the ASTs of the roots of one or more `.tree` files into a
+/// single HIR, combining the roots of the same contract.
+///
+/// `text` is the contents of the `.tree` files, which errors refer to.
+pub(crate) fn translate_all(
+    text: &str,
+    asts: Vec<Ast>,
+    cfg: &Config,
+) -> anyhow::Result<Hir> {
     if asts.len() == 1 {
         return Ok(translate_one(&asts[0], cfg));
     }
 
-    let hirs = asts.into_iter().map(|ast| translate_one(&ast, cfg));
-    Ok(combiner::Combiner::new().combine(text, hirs)?)
+    // Invariant specs are translated to their own contracts, so they are left
+    // out of the combination step and appended at the end.
+    let (invariants, asts): (Vec<_>, Vec<_>) =
+        asts.into_iter().partition(is_invariant);
+    let mut hir = if asts.is_empty() {
+        Hir::default()
+    } else {
+        let hirs = asts.into_iter().map(|ast| translate_one(&ast, cfg));
+        combiner::Combiner::new().combine(text, hirs)?
+    };
+
+    if let Hir::Root(ref mut root) = hir {
+        for ast in invariants {
+            if let Hir::Root(invariant) = translate_one(&ast, cfg) {
+                root.children.extend(invariant.children);
+            }
+        }
+    }
+
+    Ok(hir)
+}
+
+/// Resolves an anchor to the branch of a `.tree` file it was generated from.
+///
+/// Returns the span of the branch in `text`, the contents of the `.tree`
+/// file, or `None` if no branch has the anchor. See
+/// [`FunctionDefinition::anchor`].
+///
+/// # Errors
+///
+/// Returns an error if the `.tree` file is invalid.
+pub fn find_anchor(text: &str, anchor: &str) -> anyhow::Result<Option<Span>> {
+    let cfg = Config { emit_anchors: true, ..Config::default() };
+    let span = bulloak_syntax::parse(text)?.iter().find_map(|ast| {
+        translate_one(ast, &cfg)
+            .contracts()
+            .flat_map(|contract| &contract.children)
+            .find_map(|child| match child {
+                Hir::Function(f) if f.anchor.as_deref() == Some(anchor) => {
+                    Some(f.span)
+                }
+                _ => None,
+            })
+    });
+
+    Ok(span)
+}
+
+/// Whether an AST describes an invariant spec, e.g. `Invariant::Foo`.
+pub(crate) fn is_invariant(ast: &Ast) -> bool {
+    matches!(ast, Ast::Root(root) if invariant_target(&root.contract_name).is_some())
 }
 
 /// Generates the HIR for a single AST.
@@ -1,15 +1,17 @@
 //! The implementation of a translator between a bulloak tree AST and a
 //! high-level intermediate representation (HIR) -- AST -> HIR.
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::LazyLock};
 
 use bulloak_syntax::{
-    utils::{sanitize, upper_first_letter},
-    Action, Ast, Condition, Description, Visitor,
+    utils::{lower_first_letter, sanitize, to_pascal_case, upper_first_letter},
+    Action, Ast, Condition, Description, Span, Visitor,
 };
 use indexmap::IndexMap;
+use regex::Regex;
 
 use crate::{
-    config::Config,
+    config::{ActionRule, Config},
+    constants::{CONTRACT_IDENTIFIER_SEPARATOR, INVARIANT_CONTRACT_IDENTIFIER},
     hir::{self, Hir},
 };
 
@@ -61,6 +63,8 @@ impl Translator {
 }
 
 /// The internal implementation of the Translator.
+// The bools mirror the emission switches of `Config`.
+#[allow(clippy::struct_excessive_bools)]
 struct TranslatorI<'a> {
     /// A stack of modifiers that will be applied to the
     /// currently visited function.
@@ -80,24 +84,54 @@ struct TranslatorI<'a> {
     modifiers: &'a IndexMap<String, String>,
     /// Whether to add `vm.skip(true)` at the beginning of each test.
     with_vm_skip: bool,
+    /// Whether to add `vm.expectEmit()` skeletons for actions that describe
+    /// emitted events.
+    with_expect_emit: bool,
+    /// User-defined rules that turn matching actions into statements.
+    action_rules: &'a [ActionRule],
+    /// Whether to add a `setUp` function to test contracts.
+    with_set_up: bool,
+    /// Whether the base contracts of test contracts define a `setUp`
+    /// function, in which case `setUp` functions call `super.setUp()`.
+    inherits_set_up: bool,
     /// Keep track of the generated functions so far.
     ///
     /// This is used to make sure only unique function are generated.
     used_fns: HashSet<String>,
     /// Track modifier identifiers emitted in this tree to avoid duplicates.
     seen_modifiers: HashSet<String>,
+    /// A stack of the fuzz parameters declared by the conditions
+    /// that lead to the currently visited condition.
+    param_stack: Vec<hir::Parameter>,
+    /// Whether to give test functions an anchor. See
+    /// [`hir::FunctionDefinition::anchor`].
+    with_anchors: bool,
+    /// The contract name of the root being translated.
+    contract: String,
+    /// A stack of the titles of the conditions that lead to the currently
+    /// visited condition, including it.
+    title_stack: Vec<String>,
 }
 
 impl<'a> TranslatorI<'a> {
     /// Creates a new internal translator.
-    fn new(modifiers: &'a IndexMap<String, String>, cfg: &Config) -> Self {
+    fn new(modifiers: &'a IndexMap<String, String>, cfg: &'a Config) -> Self {
         let with_vm_skip = cfg.emit_vm_skip;
+        let with_expect_emit = cfg.emit_expect_emit;
         Self {
             modifier_stack: Vec::new(),
             modifiers,
             with_vm_skip,
+            with_expect_emit,
+            action_rules: &cfg.action_rules,
+            with_set_up: cfg.emit_set_up,
+            inherits_set_up: cfg.inherits_set_up(),
             used_fns: HashSet::new(),
             seen_modifiers: HashSet::new(),
+            param_stack: Vec::new(),
+            with_anchors: cfg.emit_anchors,
+            contract: String::new(),
+            title_stack: Vec::new(),
         }
     }
 
@@ -113,6 +147,176 @@ impl<'a> TranslatorI<'a> {
         std::mem::take(&mut hirs[0])
     }
 
+    /// Translates an invariant spec, e.g. `Invariant::Foo`.
+    ///
+    /// Conditions describe the actions of a handler contract, which gets one
+    /// function per condition. Top-level actions describe the invariants that
+    /// must hold after any sequence of handler calls, and become
+    /// `invariant_` functions of a test contract that targets the handler.
+    fn translate_invariant_root(
+        &mut self,
+        root: &bulloak_syntax::Root,
+        target: &str,
+    ) -> Result<Hir, ()> {
+        root.contract_name.clone_into(&mut self.contract);
+        let handler_name = format!("{target}Handler");
+
+        let statements = vec![
+            snippet(format!("handler = new {handler_name}();")),
+            snippet("targetContract(address(handler));".to_owned()),
+        ];
+        let mut test_children = vec![
+            Hir::StateVariable(hir::StateVariable {
+                ty: handler_name.clone(),
+                identifier: "handler".to_owned(),
+            }),
+            set_up(root.span, statements, self.inherits_set_up),
+        ];
+        let mut handler_children = Vec::new();
+
+        for ast in &root.children {
+            match ast {
+                Ast::Root(_) | Ast::ActionDescription(_) => {
+                    unreachable!()
+                }
+                Ast::Action(action) => {
+                    let (title, _) = split_params(&action.title);
+                    let identifier = self.make_unique_name(
+                        "invariant_",
+                        &action_name(title),
+                        Joiner::Underscore,
+                    );
+                    test_children.push(Hir::Function(
+                        hir::FunctionDefinition {
+                            identifier,
+                            ty: hir::FunctionTy::Function,
+                            span: action.span,
+                            modifiers: None,
+                            params: vec![],
+                            children: Some(self.visit_action(action)?),
+                            anchor: self.anchor(Some(&action.title)),
+                        },
+                    ));
+                }
+                Ast::Condition(condition) => {
+                    handler_children
+                        .append(&mut self.visit_handler_condition(condition)?);
+                }
+            }
+        }
+
+        let children = vec![
+            Hir::Contract(hir::ContractDefinition {
+                identifier: handler_name,
+                is_abstract: false,
+                children: handler_children,
+            }),
+            Hir::Contract(hir::ContractDefinition {
+                identifier: format!("{target}InvariantTest"),
+                is_abstract: false,
+                children: test_children,
+            }),
+        ];
+
+        Ok(Hir::Root(hir::Root { children }))
+    }
+
+    /// Generates the handler functions of a condition and its descendants.
+    fn visit_handler_condition(
+        &mut self,
+        condition: &Condition,
+    ) -> Result<Vec<Hir>, ()> {
+        let mut children = Vec::new();
+
+        let (title, params) = split_params(&condition.title);
+        let param_count = params.len();
+        self.param_stack.extend(params);
+        self.title_stack.push(condition.title.clone());
+
+        let params = self.condition_params(condition);
+        let mut hirs = bound_statements(&params);
+        for action in &condition.children {
+            if let Ast::Action(action) = action {
+                hirs.append(&mut self.visit_action(action)?);
+            }
+        }
+
+        let name = lower_first_letter(&to_pascal_case(&sanitize(title)));
+        let identifier = self.make_unique_name("", &name, Joiner::Underscore);
+        children.push(Hir::Function(hir::FunctionDefinition {
+            identifier,
+            ty: hir::FunctionTy::Function,
+            span: condition.span,
+            modifiers: None,
+            params,
+            children: Some(hirs),
+            anchor: self.anchor(None),
+        }));
+
+        for condition in &condition.children {
+            if let Ast::Condition(condition) = condition {
+                children.append(&mut self.visit_handler_condition(condition)?);
+            }
+        }
+
+        self.param_stack.truncate(self.param_stack.len() - param_count);
+        self.title_stack.pop();
+
+        Ok(children)
+    }
+
+    /// Pushes the modifier of `condition` to the modifier stack, returning
+    /// its definition the first time it is seen in this tree.
+    fn push_modifier(&mut self, condition: &Condition) -> Option<Hir> {
+        let modifier = self.modifiers.get(&condition.title)?;
+        // Always push the modifier to the stack so it's applied to
+        // functions.
+        self.modifier_stack.push(modifier);
+
+        // Emit the modifier definition only once per tree.
+        self.seen_modifiers.insert(modifier.clone()).then(|| {
+            Hir::Function(hir::FunctionDefinition {
+                identifier: modifier.clone(),
+                ty: hir::FunctionTy::Modifier,
+                span: condition.span,
+                modifiers: None,
+                params: vec![],
+                children: None,
+                anchor: None,
+            })
+        })
+    }
+
+    /// Returns the parameters of the function generated for a condition.
+    ///
+    /// The function takes the parameters declared by the condition, its
+    /// ancestors and its actions.
+    fn condition_params(&self, condition: &Condition) -> Vec<hir::Parameter> {
+        let mut params = self.param_stack.clone();
+        for child in &condition.children {
+            if let Ast::Action(action) = child {
+                params.extend(split_params(&action.title).1);
+            }
+        }
+
+        dedup_params(params)
+    }
+
+    /// Returns the anchor of the function generated for the branch with the
+    /// given title, or for the currently visited condition if `None`.
+    ///
+    /// Returns `None` if anchors are disabled.
+    fn anchor(&self, title: Option<&str>) -> Option<String> {
+        if !self.with_anchors {
+            return None;
+        }
+
+        let mut path = vec![self.contract.as_str()];
+        path.extend(self.title_stack.iter().map(String::as_str));
+        path.extend(title);
+        Some(anchor(&path))
+    }
+
     /// Builds a unique function identifier by optionally prepending nearest
     /// ancestor modifiers (PascalCase) to the suffix until unique.
     fn make_unique_name(
@@ -159,9 +363,21 @@ impl<'a> Visitor for TranslatorI<'a> {
         &mut self,
         root: &bulloak_syntax::Root,
     ) -> Result<Self::Output, Self::Error> {
+        if let Some(target) = invariant_target(&root.contract_name) {
+            return Ok(vec![self.translate_invariant_root(root, target)?]);
+        }
+
+        root.contract_name.clone_into(&mut self.contract);
         let mut root_children = Vec::new();
 
         let mut contract_children = Vec::new();
+        if self.with_set_up {
+            contract_children.push(set_up(
+                root.span,
+                vec![],
+                self.inherits_set_up,
+            ));
+        }
         for ast in &root.children {
             match ast {
                 // Root or ActionDescription nodes cannot be children of a root
@@ -172,33 +388,17 @@ impl<'a> Visitor for TranslatorI<'a> {
                 }
                 // Found a top-level action. This corresponds to a function.
                 Ast::Action(action) => {
-                    let words = action.title.split_whitespace();
-                    let words = words.skip(1); // Removes "it" from the test name.
-
-                    // Map an iterator over the words of an action to the test
-                    // name.
-                    //
-                    // Example: [do, stuff] -> DoStuff
-                    let test_name = words.fold(
-                        String::with_capacity(action.title.len()),
-                        |mut acc, w| {
-                            acc.reserve(w.len() + 1);
-                            acc.push_str(&upper_first_letter(w));
-                            acc
-                        },
-                    );
-
-                    // We need to sanitize here and not in a previous compiler
-                    // phase because we want to emit the action as-is in a
-                    // comment.
-                    let test_name = sanitize(&test_name);
+                    let (title, params) = split_params(&action.title);
+                    let test_name = action_name(title);
+                    let prefix = test_prefix(&params);
                     let test_name = self.make_unique_name(
-                        "test_",
+                        prefix,
                         &test_name,
                         Joiner::Underscore,
                     );
 
-                    let mut hirs = self.visit_action(action)?;
+                    let mut hirs = bound_statements(&params);
+                    hirs.append(&mut self.visit_action(action)?);
 
                     // Include any optional statement for the first function
                     // node.
@@ -213,7 +413,9 @@ impl<'a> Visitor for TranslatorI<'a> {
                         ty: hir::FunctionTy::Function,
                         span: action.span,
                         modifiers: None,
+                        params,
                         children: Some(hirs),
+                        anchor: self.anchor(Some(&action.title)),
                     });
                     contract_children.push(hir);
                 }
@@ -227,6 +429,7 @@ impl<'a> Visitor for TranslatorI<'a> {
         // Add the contract definition to the hir.
         root_children.push(Hir::Contract(hir::ContractDefinition {
             identifier: root.contract_name.clone(),
+            is_abstract: false,
             children: contract_children,
         }));
 
@@ -239,6 +442,11 @@ impl<'a> Visitor for TranslatorI<'a> {
     ) -> Result<Self::Output, Self::Error> {
         let mut children = Vec::new();
 
+        let (title, params) = split_params(&condition.title);
+        let param_count = params.len();
+        self.param_stack.extend(params);
+        self.title_stack.push(condition.title.clone());
+
         let action_count = condition
             .children
             .iter()
@@ -248,23 +456,7 @@ impl<'a> Visitor for TranslatorI<'a> {
         // generate a modifier for it, since it would only be used in
         // the emitted function.
         if condition.children.len() != action_count {
-            if let Some(modifier) = self.modifiers.get(&condition.title) {
-                // Always push the modifier to the stack so it's applied to
-                // functions.
-                self.modifier_stack.push(modifier);
-
-                // Emit the modifier definition only once per tree.
-                if self.seen_modifiers.insert(modifier.clone()) {
-                    let hir = Hir::Function(hir::FunctionDefinition {
-                        identifier: modifier.clone(),
-                        ty: hir::FunctionTy::Modifier,
-                        span: condition.span,
-                        modifiers: None,
-                        children: None,
-                    });
-                    children.push(hir);
-                }
-            };
+            children.extend(self.push_modifier(condition));
         }
 
         // We first visit all actions in order to keep the functions
@@ -290,7 +482,9 @@ impl<'a> Visitor for TranslatorI<'a> {
                 }
             });
 
-            let mut words = condition.title.split_whitespace();
+            let params = self.condition_params(condition);
+
+            let mut words = title.split_whitespace();
             // It is fine to unwrap because conditions have at least one word in
             // them.
             let keyword = upper_first_letter(words.next().unwrap());
@@ -301,9 +495,7 @@ impl<'a> Visitor for TranslatorI<'a> {
                 //
                 // Example: [when, something, happens] -> WhenSomethingHappens
                 let test_name = words.fold(
-                    String::with_capacity(
-                        condition.title.len() - keyword.len(),
-                    ),
+                    String::with_capacity(title.len() - keyword.len()),
                     |mut acc, w| {
                         acc.reserve(w.len() + 1);
                         acc.push_str(&upper_first_letter(w));
@@ -316,7 +508,8 @@ impl<'a> Visitor for TranslatorI<'a> {
                 // test_Revert[KEYWORD]_Description
                 //
                 // where `KEYWORD` is the starting word of the condition.
-                let prefix = format!("test_Revert{keyword}_");
+                let prefix =
+                    format!("{}Revert{keyword}_", test_prefix(&params));
                 self.make_unique_name(&prefix, &test_name, Joiner::None)
             } else {
                 // Map an iterator over the words of a condition to the test
@@ -329,7 +522,8 @@ impl<'a> Visitor for TranslatorI<'a> {
                     acc
                 });
 
-                self.make_unique_name("test_", &test_name, Joiner::Underscore)
+                let prefix = test_prefix(&params);
+                self.make_unique_name(prefix, &test_name, Joiner::Underscore)
             };
 
             let modifiers = if self.modifier_stack.is_empty() {
@@ -347,12 +541,17 @@ impl<'a> Visitor for TranslatorI<'a> {
                 }));
             }
 
+            let mut hirs = bound_statements(&params);
+            hirs.append(&mut actions);
+
             let hir = Hir::Function(hir::FunctionDefinition {
                 identifier: function_name,
                 ty: hir::FunctionTy::Function,
                 span: condition.span,
                 modifiers,
-                children: Some(actions),
+                params,
+                children: Some(hirs),
+                anchor: self.anchor(None),
             });
             children.push(hir);
         }
@@ -367,6 +566,8 @@ impl<'a> Visitor for TranslatorI<'a> {
         if condition.children.len() != action_count {
             self.modifier_stack.pop();
         }
+        self.param_stack.truncate(self.param_stack.len() - param_count);
+        self.title_stack.pop();
 
         Ok(children)
     }
@@ -382,11 +583,46 @@ impl<'a> Visitor for TranslatorI<'a> {
             }
         }
 
-        Ok(std::iter::once(hir::Hir::Comment(hir::Comment {
-            lexeme: action.title.clone(),
-        }))
-        .chain(descriptions)
-        .collect())
+        // Fuzz parameters are already part of the function's signature.
+        let (title, _) = split_params(&action.title);
+        let mut hirs: Vec<Hir> =
+            std::iter::once(hir::Hir::Comment(hir::Comment {
+                lexeme: title.to_owned(),
+            }))
+            .chain(descriptions)
+            .collect();
+
+        // Add a `vm.expectEmit();` skeleton per event the action emits, in
+        // the order they are listed.
+        if self.with_expect_emit {
+            for event in emitted_events(&action.title) {
+                hirs.push(Hir::Comment(hir::Comment {
+                    lexeme: format!("TODO: Set the `{event}` event arguments."),
+                }));
+                hirs.push(Hir::Statement(hir::Statement {
+                    ty: hir::StatementType::VmExpectEmit,
+                }));
+                hirs.push(Hir::Statement(hir::Statement {
+                    ty: hir::StatementType::Emit(event),
+                }));
+            }
+        }
+
+        // Insert the snippet of the first user-defined rule that matches this
+        // action, one statement per line.
+        let snippet =
+            self.action_rules.iter().find_map(|rule| rule.apply(&action.title));
+        if let Some(snippet) = snippet {
+            let lines =
+                snippet.lines().map(str::trim).filter(|l| !l.is_empty());
+            hirs.extend(lines.map(|line| {
+                Hir::Statement(hir::Statement {
+                    ty: hir::StatementType::Snippet(line.to_owned()),
+                })
+            }));
+        }
+
+        Ok(hirs)
     }
 
     fn visit_description(
@@ -399,26 +635,193 @@ impl<'a> Visitor for TranslatorI<'a> {
     }
 }
 
+/// Maps the words of an action to a test name.
+///
+/// Example: `it should do stuff` -> `ShouldDoStuff`
+fn action_name(title: &str) -> String {
+    let words = title.split_whitespace();
+    let words = words.skip(1); // Removes "it" from the test name.
+
+    let test_name =
+        words.fold(String::with_capacity(title.len()), |mut acc, w| {
+            acc.reserve(w.len() + 1);
+            acc.push_str(&upper_first_letter(w));
+            acc
+        });
+
+    // We need to sanitize here and not in a previous compiler phase because we
+    // want to emit the action as-is in a comment.
+    sanitize(&test_name)
+}
+
+/// Hashes the path of a branch, i.e. the contract name followed by the titles
+/// of the branch and its ancestors, into an anchor.
+///
+/// This is the 32-bit FNV-1a hash of the path joined with newlines, which,
+/// unlike the hashers of the standard library, is stable across versions.
+#[must_use]
+pub fn anchor(path: &[&str]) -> String {
+    let hash = path.join("\n").bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{hash:08x}")
+}
+
+/// Returns the name of the contract under test if `contract_name` marks an
+/// invariant spec, e.g. `Invariant::Foo`.
+pub(crate) fn invariant_target(contract_name: &str) -> Option<&str> {
+    let (contract_name, target) =
+        contract_name.split_once(CONTRACT_IDENTIFIER_SEPARATOR)?;
+    (contract_name.trim() == INVARIANT_CONTRACT_IDENTIFIER)
+        .then_some(target.trim())
+}
+
+/// Splits a title into its text and the fuzz parameters it declares.
+///
+/// Fuzz parameters are declared in a trailing parenthesized list, like
+/// `when amount is within bounds (uint128 amount, address to)`. If the title
+/// doesn't end with a valid parameter list, it is returned unchanged.
+pub(crate) fn split_params(title: &str) -> (&str, Vec<hir::Parameter>) {
+    static RE_PARAMS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(.*?)\s*\(([^()]*)\)\s*$").unwrap());
+    static RE_PARAM: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"^([A-Za-z_$][A-Za-z0-9_$.]*(?:\[[0-9]*\])*)(?:\s+(?:memory|calldata|storage))?\s+([A-Za-z_$][A-Za-z0-9_$]*)$",
+        )
+        .unwrap()
+    });
+
+    let Some(captures) = RE_PARAMS.captures(title.trim()) else {
+        return (title, vec![]);
+    };
+
+    let params: Option<Vec<_>> = captures[2]
+        .split(',')
+        .map(|param| {
+            let param = RE_PARAM.captures(param.trim())?;
+            Some(hir::Parameter {
+                ty: param[1].to_owned(),
+                name: param[2].to_owned(),
+            })
+        })
+        .collect();
+
+    match params {
+        Some(params) => {
+            let text = captures.get(1).map_or(title, |text| text.as_str());
+            (text, params)
+        }
+        None => (title, vec![]),
+    }
+}
+
+/// Removes parameters whose name was already declared, keeping the first one.
+fn dedup_params(params: Vec<hir::Parameter>) -> Vec<hir::Parameter> {
+    let mut seen = HashSet::new();
+    params.into_iter().filter(|param| seen.insert(param.name.clone())).collect()
+}
+
+/// Returns the prefix of a test function given its parameters.
+fn test_prefix(params: &[hir::Parameter]) -> &'static str {
+    if params.is_empty() {
+        "test_"
+    } else {
+        "testFuzz_"
+    }
+}
+
+/// Generates a `setUp` function with the given statements.
+///
+/// When the base contracts of the contract define a `setUp` function, the
+/// function calls `super.setUp()` first.
+pub(crate) fn set_up(
+    span: Span,
+    mut statements: Vec<Hir>,
+    inherits_set_up: bool,
+) -> Hir {
+    if inherits_set_up {
+        statements.insert(0, snippet("super.setUp();".to_owned()));
+    }
+
+    Hir::Function(hir::FunctionDefinition {
+        identifier: "setUp".to_owned(),
+        ty: hir::FunctionTy::SetUp,
+        span,
+        modifiers: None,
+        params: vec![],
+        children: Some(statements),
+        anchor: None,
+    })
+}
+
+/// Wraps a line of Solidity code in a statement node.
+fn snippet(code: String) -> Hir {
+    Hir::Statement(hir::Statement { ty: hir::StatementType::Snippet(code) })
+}
+
+/// Returns a `bound` placeholder for each fuzzed integer input.
+///
+/// The bounds default to the whole range of the type, so that the emitted test
+/// compiles and the user only has to narrow them.
+fn bound_statements(params: &[hir::Parameter]) -> Vec<Hir> {
+    params
+        .iter()
+        .filter(|param| param.is_integer())
+        .map(|hir::Parameter { ty, name }| {
+            snippet(format!(
+                "{name} = {ty}(bound({name}, type({ty}).min, type({ty}).max));"
+            ))
+        })
+        .collect()
+}
+
+/// Returns the events an action says it emits, in order.
+///
+/// An action describes emitted events when it reads like `it should emit a
+/// {Transfer} event` or `it should emit {Transfer} and {Approval} events`.
+/// Event names are taken from the curly braces.
+fn emitted_events(title: &str) -> Vec<String> {
+    static RE_EMIT_ACTION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?i)^it\s+(?:should\s+)?emits?\b").unwrap()
+    });
+    static RE_EVENT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"\{\s*([A-Za-z_$][A-Za-z0-9_$]*)\s*\}").unwrap()
+    });
+
+    if !RE_EMIT_ACTION.is_match(title.trim()) {
+        return vec![];
+    }
+
+    RE_EVENT
+        .captures_iter(title)
+        .map(|captures| captures[1].to_owned())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
     use bulloak_syntax::{parse_one, Position, Span};
     use pretty_assertions::assert_eq;
 
+    use super::{anchor, emitted_events, split_params};
     use crate::{
-        config::Config,
+        config::{ActionRule, Config},
         hir::{self, Hir},
         scaffold::modifiers,
     };
 
     fn translate(text: &str) -> Result<hir::Hir> {
+        let cfg = Config { emit_vm_skip: true, ..Config::default() };
+        translate_with(text, &cfg)
+    }
+
+    fn translate_with(text: &str, cfg: &Config) -> Result<hir::Hir> {
         let ast = parse_one(text)?;
         let mut discoverer = modifiers::ModifierDiscoverer::new();
         let modifiers = discoverer.discover(&ast);
 
-        let mut cfg: Config = Config::default();
-        cfg.emit_vm_skip = true;
-        Ok(hir::translator::Translator::new().translate(&ast, modifiers, &cfg))
+        Ok(hir::translator::Translator::new().translate(&ast, modifiers, cfg))
     }
 
     fn root(children: Vec<Hir>) -> Hir {
@@ -426,7 +829,11 @@ mod tests {
     }
 
     fn contract(identifier: String, children: Vec<Hir>) -> Hir {
-        Hir::Contract(hir::ContractDefinition { identifier, children })
+        Hir::Contract(hir::ContractDefinition {
+            identifier,
+            is_abstract: false,
+            children,
+        })
     }
 
     fn function(
@@ -441,7 +848,9 @@ mod tests {
             ty,
             span,
             modifiers,
+            params: vec![],
             children,
+            anchor: None,
         })
     }
 
@@ -762,4 +1171,345 @@ Foo_Test
 
         Ok(())
     }
+
+    #[test]
+    fn finds_emitted_events() {
+        assert_eq!(
+            emitted_events("it should emit a {CancelLockupStream} event"),
+            vec!["CancelLockupStream"]
+        );
+        assert_eq!(
+            emitted_events(
+                "It should emit a {Transfer}, an {Approval} and a {MetadataUpdate} event."
+            ),
+            vec!["Transfer", "Approval", "MetadataUpdate"]
+        );
+        assert_eq!(emitted_events("It emits {Transfer}."), vec!["Transfer"]);
+        assert!(emitted_events("it should revert").is_empty());
+        assert!(emitted_events("it should not emit {Transfer}").is_empty());
+    }
+
+    fn param(ty: &str, name: &str) -> hir::Parameter {
+        hir::Parameter { ty: ty.to_owned(), name: name.to_owned() }
+    }
+
+    #[test]
+    fn splits_fuzz_params() {
+        assert_eq!(
+            split_params("when amount is within bounds (uint128 amount)"),
+            ("when amount is within bounds", vec![param("uint128", "amount")])
+        );
+        assert_eq!(
+            split_params("given a user (address to, string memory memo)"),
+            (
+                "given a user",
+                vec![param("address", "to"), param("string", "memo")]
+            )
+        );
+        assert_eq!(
+            split_params("when it is empty (uint256[] ids)"),
+            ("when it is empty", vec![param("uint256[]", "ids")])
+        );
+        assert_eq!(
+            split_params("when something (not valid params)"),
+            ("when something (not valid params)", vec![])
+        );
+        assert_eq!(split_params("when nothing"), ("when nothing", vec![]));
+    }
+
+    #[test]
+    fn invariant_contracts() -> Result<()> {
+        let file_contents = "Invariant::Vault
+├── it should never lose assets
+└── when a user deposits
+   └── it should mint shares";
+
+        assert_eq!(
+            translate_with(file_contents, &Config::default())?,
+            root(vec![
+                contract(
+                    "VaultHandler".to_owned(),
+                    vec![function(
+                        "whenAUserDeposits".to_owned(),
+                        hir::FunctionTy::Function,
+                        Span::new(
+                            Position::new(55, 3, 1),
+                            Position::new(119, 4, 28)
+                        ),
+                        None,
+                        Some(vec![comment("it should mint shares".to_owned())])
+                    )]
+                ),
+                contract(
+                    "VaultInvariantTest".to_owned(),
+                    vec![
+                        Hir::StateVariable(hir::StateVariable {
+                            ty: "VaultHandler".to_owned(),
+                            identifier: "handler".to_owned(),
+                        }),
+                        function(
+                            "setUp".to_owned(),
+                            hir::FunctionTy::SetUp,
+                            Span::new(
+                                Position::new(0, 1, 1),
+                                Position::new(119, 4, 28)
+                            ),
+                            None,
+                            Some(vec![
+                                statement(hir::StatementType::Snippet(
+                                    "handler = new VaultHandler();".to_owned()
+                                )),
+                                statement(hir::StatementType::Snippet(
+                                    "targetContract(address(handler));"
+                                        .to_owned()
+                                )),
+                            ])
+                        ),
+                        function(
+                            "invariant_ShouldNeverLoseAssets".to_owned(),
+                            hir::FunctionTy::Function,
+                            Span::new(
+                                Position::new(17, 2, 1),
+                                Position::new(53, 2, 31)
+                            ),
+                            None,
+                            Some(vec![comment(
+                                "it should never lose assets".to_owned()
+                            )])
+                        ),
+                    ]
+                ),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fuzz_test_functions() -> Result<()> {
+        let file_contents = "Foo_Test
+└── when amount is valid (uint128 amount)
+   ├── when the caller is not the owner
+   │  └── it should revert
+   └── when the caller is the owner (address caller)
+      └── it should transfer (uint40 deadline)";
+        let hir = translate_with(file_contents, &Config::default())?;
+        let Hir::Root(root) = hir else { unreachable!() };
+        let Hir::Contract(contract) = &root.children[0] else { unreachable!() };
+        let fns: Vec<_> = contract
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Hir::Function(f) if f.is_function() => Some(f),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            fns[0].identifier,
+            "testFuzz_RevertWhen_TheCallerIsNotTheOwner"
+        );
+        assert_eq!(fns[0].params, vec![param("uint128", "amount")]);
+        assert_eq!(
+            fns[0].modifiers,
+            Some(vec!["whenAmountIsValid".to_owned()])
+        );
+        assert_eq!(fns[1].identifier, "testFuzz_WhenTheCallerIsTheOwner");
+        assert_eq!(
+            fns[1].params,
+            vec![
+                param("uint128", "amount"),
+                param("address", "caller"),
+                param("uint40", "deadline")
+            ]
+        );
+        assert_eq!(
+            fns[1].children.as_ref().unwrap()[..2],
+            [
+                statement(hir::StatementType::Snippet(
+                    "amount = uint128(bound(amount, type(uint128).min, type(uint128).max));"
+                        .to_owned()
+                )),
+                statement(hir::StatementType::Snippet(
+                    "deadline = uint40(bound(deadline, type(uint40).min, type(uint40).max));"
+                        .to_owned()
+                )),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn expect_emit_statements() -> Result<()> {
+        let file_contents = "Foo_Test
+└── when stuff happens
+   ├── it should emit a {Transfer} and an {Approval} event
+   └── it should revert";
+        let cfg = Config { emit_expect_emit: true, ..Config::default() };
+
+        assert_eq!(
+            translate_with(file_contents, &cfg)?,
+            root(vec![contract(
+                "Foo_Test".to_owned(),
+                vec![function(
+                    "test_WhenStuffHappens".to_owned(),
+                    hir::FunctionTy::Function,
+                    Span::new(Position::new(9, 2, 1), Position::new(131, 4, 23)),
+                    None,
+                    Some(vec![
+                        comment(
+                            "it should emit a {Transfer} and an {Approval} event"
+                                .to_owned()
+                        ),
+                        comment(
+                            "TODO: Set the `Transfer` event arguments."
+                                .to_owned()
+                        ),
+                        statement(hir::StatementType::VmExpectEmit),
+                        statement(hir::StatementType::Emit(
+                            "Transfer".to_owned()
+                        )),
+                        comment(
+                            "TODO: Set the `Approval` event arguments."
+                                .to_owned()
+                        ),
+                        statement(hir::StatementType::VmExpectEmit),
+                        statement(hir::StatementType::Emit(
+                            "Approval".to_owned()
+                        )),
+                        comment("it should revert".to_owned()),
+                    ])
+                )]
+            )])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn action_rule_statements() -> Result<()> {
+        let file_contents = "Foo_Test
+├── it should transfer 10 tokens
+└── it should update the balance";
+        let mut cfg = Config::default();
+        cfg.action_rules = vec![
+            ActionRule::new(
+                r"^it should transfer (\d+) tokens",
+                "token.transfer(users.bob, $1);",
+            )?,
+            ActionRule::new(
+                r"^it should update the (\w+)",
+                "uint256 $1 = token.${1}Of(users.bob);\nassertEq($1, 0);",
+            )?,
+        ];
+
+        let hir = translate_with(file_contents, &cfg)?;
+        let contract = hir.find_contract().unwrap();
+        let statements: Vec<_> = contract
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Hir::Function(f) => f.children.clone(),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|child| match child {
+                Hir::Statement(hir::Statement {
+                    ty: hir::StatementType::Snippet(snippet),
+                }) => Some(snippet),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            statements,
+            vec![
+                "token.transfer(users.bob, 10);",
+                "uint256 balance = token.balanceOf(users.bob);",
+                "assertEq(balance, 0);",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn anchors_identify_branches() -> Result<()> {
+        let file_contents = "Foo_Test
+├── it should never revert
+└── when stuff happens
+   ├── it should do stuff
+   └── when more stuff happens
+      └── it should revert";
+        let anchors = |cfg: &Config| -> Result<Vec<_>> {
+            let hir = translate_with(file_contents, cfg)?;
+            let contract = hir.find_contract().unwrap();
+            Ok(contract
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    Hir::Function(f) => {
+                        Some((f.identifier.clone(), f.anchor.clone()))
+                    }
+                    _ => None,
+                })
+                .collect())
+        };
+
+        let mut cfg = Config::default();
+        assert!(anchors(&cfg)?.iter().all(|(_, anchor)| anchor.is_none()));
+
+        cfg.emit_anchors = true;
+        let expected = vec![
+            (
+                "test_ShouldNeverRevert".to_owned(),
+                Some(anchor(&["Foo_Test", "it should never revert"])),
+            ),
+            ("whenStuffHappens".to_owned(), None),
+            (
+                "test_WhenStuffHappens".to_owned(),
+                Some(anchor(&["Foo_Test", "when stuff happens"])),
+            ),
+            (
+                "test_RevertWhen_MoreStuffHappens".to_owned(),
+                Some(anchor(&[
+                    "Foo_Test",
+                    "when stuff happens",
+                    "when more stuff happens",
+                ])),
+            ),
+        ];
+        assert_eq!(expected, anchors(&cfg)?);
+        // Anchors only depend on the titles, so they are stable.
+        assert_eq!("96d5171e", anchor(&["Foo_Test", "it should never revert"]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn finds_the_branch_of_an_anchor() -> Result<()> {
+        let file_contents = "---
+vm-skip: true
+---
+Foo
+└── it should a
+
+Bar
+└── when b
+   └── it should c";
+
+        let span =
+            hir::find_anchor(file_contents, &anchor(&["Bar", "when b"]))?
+                .unwrap();
+        assert_eq!(8, span.start.line);
+        let span =
+            hir::find_anchor(file_contents, &anchor(&["Foo", "it should a"]))?
+                .unwrap();
+        assert_eq!(5, span.start.line);
+        assert_eq!(None, hir::find_anchor(file_contents, "00000000")?);
+        assert!(hir::find_anchor("Foo\n└── when", "00000000").is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,912 @@
+//! Defines how the Solidity files of a `.tree` file are laid out.
+//!
+//! A `.tree` file usually maps to a single Solidity file. With
+//! [`Config::split_trees`], a `.tree` file whose roots are all of the form
+//! `Contract::function` maps to one file and contract per root instead. The
+//! modifiers used by more than one root are moved to an abstract contract in a
+//! file of their own, which the contracts of the other files inherit from.
+//!
+//! With [`Config::modifiers_file`], the modifiers of every file are moved to a
+//! single abstract `Modifiers` contract instead, shared by the whole project.
+//!
+//! Conversely, several `.tree` files may map to the same Solidity file, e.g.
+//! with a `{contract}.t.sol` file name, in which case their roots are combined
+//! into a single file. See [`merged_test_files`].
+
+use std::{
+    collections::{HashMap, HashSet},
+    env, mem,
+    path::{Component, Path, PathBuf},
+};
+
+use bulloak_syntax::{front_matter, Ast, Span};
+
+use crate::{
+    config::{normalize, Config, Import},
+    constants::{
+        CONTRACT_IDENTIFIER_SEPARATOR, FORGE_STD_TEST_CONTRACT,
+        MODIFIERS_CONTRACT_NAME, SHARED_CONTRACT_SUFFIX, SHARED_FILE_NAME,
+    },
+    hir::{self, is_invariant, translate_one, Hir},
+    sol::translator::uses_forge_std,
+};
+
+/// A Solidity file emitted for a `.tree` file.
+#[derive(Debug, Clone)]
+pub struct TestFile {
+    /// The path of the Solidity file.
+    pub path: PathBuf,
+    /// The HIR of the contents of the file.
+    pub hir: Hir,
+    /// The config the file is emitted with.
+    ///
+    /// This includes the settings in the front matter of the `.tree` file.
+    pub cfg: Config,
+    /// The modifiers of the file that belong in the project-wide modifiers
+    /// file instead. See [`modifiers_file`].
+    pub modifiers: Vec<hir::FunctionDefinition>,
+    /// The contents of the `.tree` files the file is translated from. Empty
+    /// if the file can't be merged with others, e.g. because it was split
+    /// from a `.tree` file.
+    pub(crate) sources: Vec<String>,
+}
+
+/// Returns the Solidity files of the `.tree` file at `tree`, whose contents
+/// are `text`.
+///
+/// Settings in the front matter of the `.tree` file override `cfg`.
+///
+/// # Errors
+///
+/// Returns an error if the `.tree` file is invalid, or if splitting it maps
+/// several roots to the same file.
+///
+/// # Panics
+///
+/// Never panics in practice: `merged_test_files` returns one result per
+/// `.tree` file.
+pub fn test_files(
+    tree: &Path,
+    text: &str,
+    cfg: &Config,
+) -> anyhow::Result<Vec<TestFile>> {
+    let trees = [(tree.to_path_buf(), text.to_owned())];
+    merged_test_files(&trees, cfg)
+        .pop()
+        .expect("there is a result per .tree file")
+}
+
+/// Returns the Solidity files of a set of `.tree` files, given as their
+/// paths and contents, with one result per `.tree` file.
+///
+/// Files of several `.tree` files that map to the same path are merged into
+/// one, whose roots are combined as if they were all in the first of these
+/// `.tree` files. The merged file is returned with that `.tree` file, and its
+/// front matter applies.
+///
+/// # Errors
+///
+/// The result of a `.tree` file is an error if it is invalid, if its roots
+/// can't be combined with those of the files it is merged with, or if it maps
+/// several roots to the same file when split. See [`test_files`].
+#[must_use]
+pub fn merged_test_files(
+    trees: &[(PathBuf, String)],
+    cfg: &Config,
+) -> Vec<anyhow::Result<Vec<TestFile>>> {
+    let mut results: Vec<_> =
+        trees.iter().map(|(tree, text)| tree_files(tree, text, cfg)).collect();
+
+    // The `.tree` file and the index of the file each path was first emitted
+    // for.
+    let mut firsts: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+    for idx in 0..results.len() {
+        let files = match &mut results[idx] {
+            Ok(files) => mem::take(files),
+            Err(_) => continue,
+        };
+
+        let mut kept = Vec::with_capacity(files.len());
+        let mut error = None;
+        for file in files {
+            let first = firsts.get(&file.path).copied();
+            let Some((tree_idx, file_idx)) = first else {
+                firsts.insert(file.path.clone(), (idx, kept.len()));
+                kept.push(file);
+                continue;
+            };
+            let Ok(first_files) = &mut results[tree_idx] else {
+                unreachable!("only the files of valid trees are merged into");
+            };
+            if let Err(e) = merge(&mut first_files[file_idx], file) {
+                error = Some(e);
+                break;
+            }
+        }
+
+        results[idx] = match error {
+            Some(e) => {
+                firsts.retain(|_, (tree_idx, _)| *tree_idx != idx);
+                Err(e)
+            }
+            None => Ok(kept),
+        };
+    }
+
+    results
+        .into_iter()
+        .map(|result| {
+            result.map(|files| {
+                files
+                    .into_iter()
+                    .map(|file| {
+                        if inherits_modifiers(&file) {
+                            move_modifiers(file)
+                        } else {
+                            file
+                        }
+                    })
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+/// Merges `other` into `file`, which map to the same path, by combining the
+/// roots of their `.tree` files.
+fn merge(file: &mut TestFile, other: TestFile) -> anyhow::Result<()> {
+    if file.sources.is_empty() || other.sources.is_empty() {
+        anyhow::bail!(
+            "several .tree files map to {}, but split trees can't be merged",
+            file.path.display()
+        );
+    }
+
+    file.sources.extend(other.sources);
+    let mut asts = vec![];
+    for source in &file.sources {
+        asts.extend(bulloak_syntax::parse(source)?);
+    }
+    file.hir = hir::translate_all(&file.sources.join("\n\n"), asts, &file.cfg)?;
+
+    Ok(())
+}
+
+/// Returns the Solidity files of a single `.tree` file, before they are
+/// merged with the files of other `.tree` files.
+fn tree_files(
+    tree: &Path,
+    text: &str,
+    cfg: &Config,
+) -> anyhow::Result<Vec<TestFile>> {
+    let (front_matter, _) = front_matter::split(text)?;
+    let mut cfg = cfg.with_front_matter(&front_matter)?;
+
+    let mut files = None;
+    if cfg.split_trees {
+        let asts = bulloak_syntax::parse(text)?;
+        if asts.iter().all(|ast| is_invariant(ast) || is_function_root(ast)) {
+            files = Some(split(tree, &asts, &cfg)?);
+        } else {
+            // Trees that can't be split are emitted as usual.
+            cfg.split_trees = false;
+        }
+    }
+    if let Some(files) = files {
+        return Ok(files);
+    }
+
+    let path = cfg.sol_path(tree, text);
+    inherit_modifiers(&mut cfg, &path);
+    let hir = hir::translate(text, &cfg)?;
+    let sources = vec![text.to_owned()];
+    Ok(vec![TestFile { path, hir, cfg, modifiers: vec![], sources }])
+}
+
+/// Returns the project-wide modifiers file of a set of test files.
+///
+/// The file holds an abstract `Modifiers` contract with the modifiers of all
+/// the test files, in the order they first appear in. It is returned even if
+/// there are none, since the test files inherit from it regardless.
+///
+/// Returns `None` if none of the test files inherits from the `Modifiers`
+/// contract, i.e. if no modifiers file is set.
+#[must_use]
+pub fn modifiers_file(files: &[TestFile], cfg: &Config) -> Option<TestFile> {
+    let inheriting: Vec<_> =
+        files.iter().filter(|file| inherits_modifiers(file)).collect();
+    let path = inheriting.first()?.cfg.shared_modifiers_file()?;
+    let mut seen = HashSet::new();
+    let modifiers: Vec<_> = inheriting
+        .iter()
+        .flat_map(|file| &file.modifiers)
+        .filter(|modifier| seen.insert(modifier.identifier.as_str()))
+        .cloned()
+        .collect();
+    let with_set_up = inheriting.iter().any(|file| file.cfg.emit_set_up);
+    let with_forge_std =
+        inheriting.iter().any(|file| uses_forge_std(&file.hir, &file.cfg));
+
+    Some(abstract_file(
+        path.to_path_buf(),
+        MODIFIERS_CONTRACT_NAME.to_owned(),
+        modifiers,
+        with_set_up,
+        with_forge_std,
+        cfg,
+    ))
+}
+
+/// Makes the contracts of the test file at `path` inherit from the
+/// project-wide modifiers file instead of the configured base contracts, if
+/// `cfg` sets one.
+fn inherit_modifiers(cfg: &mut Config, path: &Path) {
+    let Some(modifiers_file) = cfg.shared_modifiers_file() else {
+        return;
+    };
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let import = Import {
+        symbols: vec![MODIFIERS_CONTRACT_NAME.to_owned()],
+        path: relative_import(dir, modifiers_file),
+    };
+    cfg.base_contracts = vec![MODIFIERS_CONTRACT_NAME.to_owned()];
+    cfg.imports.push(import);
+}
+
+/// Whether the contracts of a test file inherit from the project-wide
+/// modifiers file. See [`inherit_modifiers`].
+fn inherits_modifiers(file: &TestFile) -> bool {
+    file.cfg.shared_modifiers_file().is_some()
+        && file.cfg.base_contracts == [MODIFIERS_CONTRACT_NAME]
+}
+
+/// Moves the modifiers of a test file to the project-wide modifiers file.
+fn move_modifiers(file: TestFile) -> TestFile {
+    let TestFile { path, hir, cfg, sources, .. } = file;
+    let modifiers = contract_modifiers(&hir).into_iter().cloned().collect();
+    let hir = map_contract(&hir, |contract| hir::ContractDefinition {
+        identifier: contract.identifier.clone(),
+        is_abstract: contract.is_abstract,
+        children: contract
+            .children
+            .iter()
+            .filter(
+                |child| !matches!(child, Hir::Function(f) if f.is_modifier()),
+            )
+            .cloned()
+            .collect(),
+    });
+
+    TestFile { path, hir, cfg, modifiers, sources }
+}
+
+/// Whether an AST is a tree of the form `Contract::function`.
+fn is_function_root(ast: &Ast) -> bool {
+    matches!(ast, Ast::Root(root) if root
+    .contract_name
+    .split_once(CONTRACT_IDENTIFIER_SEPARATOR)
+    .is_some_and(|(contract, function)| {
+        !contract.trim().is_empty() && !function.trim().is_empty()
+    }))
+}
+
+/// Emits one file per root, plus a file with the shared modifiers, if any.
+fn split(
+    tree: &Path,
+    asts: &[Ast],
+    cfg: &Config,
+) -> anyhow::Result<Vec<TestFile>> {
+    let roots: Vec<(&str, &Ast, Hir)> = asts
+        .iter()
+        .filter_map(|ast| match ast {
+            Ast::Root(root) => Some((
+                root.contract_name.as_str(),
+                ast,
+                translate_one(ast, cfg),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let shared = shared_modifiers(&roots, cfg);
+    let shared_names: HashSet<_> =
+        shared.iter().map(|modifier| modifier.identifier.clone()).collect();
+
+    let mut files = Vec::with_capacity(roots.len() + 1);
+    let with_forge_std = roots.iter().any(|(name, _, hir)| {
+        !is_invariant_root(name) && uses_forge_std(hir, cfg)
+    });
+    let shared_file = (!shared.is_empty())
+        .then(|| shared_file(tree, roots[0].0, shared, with_forge_std, cfg));
+    for (name, ast, hir) in &roots {
+        let path = cfg.root_sol_path(tree, name);
+        if is_invariant_root(name) {
+            files.push(TestFile {
+                path,
+                hir: hir.clone(),
+                cfg: cfg.clone(),
+                modifiers: vec![],
+                sources: vec![],
+            });
+            continue;
+        }
+
+        let mut file_cfg = cfg.clone();
+        if let Some(ref shared_file) = shared_file {
+            let shared_contract = shared_file
+                .hir
+                .find_contract()
+                .map(|contract| contract.identifier.clone())
+                .unwrap_or_default();
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            file_cfg.base_contracts = vec![shared_contract.clone()];
+            file_cfg.imports.push(Import {
+                symbols: vec![shared_contract],
+                path: relative_import(dir, &shared_file.path),
+            });
+        }
+        inherit_modifiers(&mut file_cfg, &path);
+
+        // The root is translated again, since its `setUp` function depends on
+        // the contracts it inherits from.
+        let hir = translate_one(ast, &file_cfg);
+        let identifier = cfg.split_contract_name(tree, name);
+        let hir = map_contract(&hir, |contract| hir::ContractDefinition {
+            identifier: identifier.clone(),
+            is_abstract: false,
+            children: contract
+                .children
+                .iter()
+                .filter(|child| {
+                    !matches!(child, Hir::Function(f) if f.is_modifier()
+                        && shared_names.contains(&f.identifier))
+                })
+                .cloned()
+                .collect(),
+        });
+        files.push(TestFile {
+            path,
+            hir,
+            cfg: file_cfg,
+            modifiers: vec![],
+            sources: vec![],
+        });
+    }
+    files.extend(shared_file);
+
+    let mut paths = HashSet::new();
+    if let Some(file) = files.iter().find(|file| !paths.insert(&file.path)) {
+        anyhow::bail!(
+            "splitting {} maps several trees to {}, consider using the \
+             `{{function}}` placeholder in the file name",
+            tree.display(),
+            file.path.display()
+        );
+    }
+
+    Ok(files)
+}
+
+/// Returns the modifiers used by more than one root, in the order they first
+/// appear in, unless they all go to the project-wide modifiers file.
+fn shared_modifiers(
+    roots: &[(&str, &Ast, Hir)],
+    cfg: &Config,
+) -> Vec<hir::FunctionDefinition> {
+    if cfg.shared_modifiers_file().is_some() {
+        return vec![];
+    }
+
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    let mut modifiers = vec![];
+    for (name, _, hir) in roots {
+        if is_invariant_root(name) {
+            continue;
+        }
+        for modifier in contract_modifiers(hir) {
+            let count = uses.entry(modifier.identifier.as_str()).or_default();
+            if *count == 0 {
+                modifiers.push(modifier);
+            }
+            *count += 1;
+        }
+    }
+
+    modifiers
+        .into_iter()
+        .filter(|modifier| uses[modifier.identifier.as_str()] > 1)
+        .cloned()
+        .collect()
+}
+
+/// Builds the file holding the abstract contract with the modifiers shared by
+/// the roots of a split `.tree` file.
+///
+/// The contract inherits from the configured base contracts. If there are
+/// none, it inherits from forge-std's `Test` when `with_forge_std` is set,
+/// i.e. when the contracts inheriting from it rely on forge-std, like their
+/// own files would.
+///
+/// With `--set-up`, the contract defines a `setUp` function for the `setUp`
+/// functions of the contracts inheriting from it to override, since `Test`
+/// doesn't define one.
+fn shared_file(
+    tree: &Path,
+    root: &str,
+    modifiers: Vec<hir::FunctionDefinition>,
+    with_forge_std: bool,
+    cfg: &Config,
+) -> TestFile {
+    let (contract, _) =
+        root.split_once(CONTRACT_IDENTIFIER_SEPARATOR).unwrap_or((root, ""));
+    let first_path = cfg.root_sol_path(tree, root);
+    let stem = tree.file_stem().unwrap_or_default().to_string_lossy();
+    let path =
+        first_path.with_file_name(SHARED_FILE_NAME.replace("{tree}", &stem));
+    let identifier = format!("{}{SHARED_CONTRACT_SUFFIX}", contract.trim());
+
+    abstract_file(
+        path,
+        identifier,
+        modifiers,
+        cfg.emit_set_up,
+        with_forge_std,
+        cfg,
+    )
+}
+
+/// Builds a file holding an abstract contract with `modifiers`.
+///
+/// The contract inherits from the configured base contracts. If there are
+/// none, it inherits from forge-std's `Test` when `with_forge_std` is set,
+/// i.e. when the contracts inheriting from it rely on forge-std, like their
+/// own files would.
+///
+/// With `with_set_up`, the contract defines a `setUp` function for the `setUp`
+/// functions of the contracts inheriting from it to override, since `Test`
+/// doesn't define one.
+fn abstract_file(
+    path: PathBuf,
+    identifier: String,
+    modifiers: Vec<hir::FunctionDefinition>,
+    with_set_up: bool,
+    with_forge_std: bool,
+    cfg: &Config,
+) -> TestFile {
+    let mut abstract_cfg = cfg.clone();
+    abstract_cfg.emit_set_up = false;
+    // The translator imports forge-std's `Test` along with it.
+    if with_forge_std && abstract_cfg.base_contracts.is_empty() {
+        abstract_cfg.base_contracts = vec![FORGE_STD_TEST_CONTRACT.to_owned()];
+    }
+
+    let mut children = Vec::with_capacity(modifiers.len() + 1);
+    if with_set_up {
+        let inherits_set_up = abstract_cfg.inherits_set_up();
+        children.push(hir::translator::set_up(
+            Span::default(),
+            vec![],
+            inherits_set_up,
+        ));
+    }
+    children.extend(modifiers.into_iter().map(Hir::Function));
+    let contract =
+        hir::ContractDefinition { identifier, is_abstract: true, children };
+    let hir = Hir::Root(hir::Root { children: vec![Hir::Contract(contract)] });
+
+    TestFile {
+        path,
+        hir,
+        cfg: abstract_cfg,
+        modifiers: vec![],
+        sources: vec![],
+    }
+}
+
+/// Whether a root marks an invariant spec, e.g. `Invariant::Foo`.
+fn is_invariant_root(name: &str) -> bool {
+    hir::translator::invariant_target(name).is_some()
+}
+
+/// Returns the modifiers of the contracts of a translated root.
+fn contract_modifiers(hir: &Hir) -> Vec<&hir::FunctionDefinition> {
+    hir.contracts()
+        .flat_map(|contract| &contract.children)
+        .filter_map(|child| match child {
+            Hir::Function(f) if f.is_modifier() => Some(f),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replaces the contracts of a translated root with the result of `f`.
+fn map_contract(
+    hir: &Hir,
+    mut f: impl FnMut(&hir::ContractDefinition) -> hir::ContractDefinition,
+) -> Hir {
+    let Hir::Root(root) = hir else {
+        return hir.clone();
+    };
+
+    let children = root
+        .children
+        .iter()
+        .map(|child| match child {
+            Hir::Contract(contract) => Hir::Contract(f(contract)),
+            child => child.clone(),
+        })
+        .collect();
+
+    Hir::Root(hir::Root { children })
+}
+
+/// Returns the path of `to` relative to the directory `from`, in the form
+/// Solidity import directives expect, e.g. `./foo.sol` or `../foo.sol`.
+fn relative_import(from: &Path, to: &Path) -> String {
+    // Both paths need to be relative to the same directory.
+    let (from, to) = if from.is_absolute() == to.is_absolute() {
+        (normalize(from), normalize(to))
+    } else {
+        let cwd = env::current_dir().unwrap_or_default();
+        (normalize(&cwd.join(from)), normalize(&cwd.join(to)))
+    };
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut path: PathBuf = match from.components().count() - common {
+        0 => Component::CurDir.as_os_str().into(),
+        ups => (0..ups).map(|_| Component::ParentDir.as_os_str()).collect(),
+    };
+    path.extend(to.components().skip(common));
+
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use pretty_assertions::assert_eq;
+
+    use super::{
+        merged_test_files, modifiers_file, relative_import, test_files,
+    };
+    use crate::{config::Config, hir::Hir, scaffold::scaffold_file};
+
+    /// Returns the contract names and function names of a file.
+    fn contents(hir: &Hir) -> (String, bool, Vec<String>) {
+        let Hir::Root(root) = hir else { unreachable!() };
+        let contract = root.find_contract().unwrap();
+        let functions = contract
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                Hir::Function(f) => Some(f.identifier.clone()),
+                _ => None,
+            })
+            .collect();
+
+        (contract.identifier.clone(), contract.is_abstract, functions)
+    }
+
+    #[test]
+    fn merges_trees_of_the_same_file() {
+        let trees = [
+            (
+                PathBuf::from("specs/foo.withdraw.tree"),
+                "\
+Foo::withdraw
+├── when the vault is paused
+│   └── it should revert
+└── when the vault is not paused
+    ├── given the caller is the owner
+    │   └── it should withdraw
+    └── it should revert"
+                    .to_owned(),
+            ),
+            (
+                PathBuf::from("specs/bar.tree"),
+                "Bar::bar\n└── it should work".to_owned(),
+            ),
+            (
+                PathBuf::from("specs/foo.deposit.tree"),
+                "\
+Foo::deposit
+└── when the vault is not paused
+    ├── given the amount is zero
+    │   └── it should revert
+    └── it should deposit"
+                    .to_owned(),
+            ),
+        ];
+        let mut cfg = Config::default();
+        cfg.file_name = Some("{contract}.t.sol".to_owned());
+        let results = merged_test_files(&trees, &cfg);
+
+        let files: Vec<_> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(files.iter().map(Vec::len).collect::<Vec<_>>(), [1, 1, 0]);
+        assert_eq!(files[0][0].path, PathBuf::from("specs/Foo.t.sol"));
+        assert_eq!(
+            contents(&files[0][0].hir),
+            (
+                "Foo".to_owned(),
+                false,
+                vec![
+                    "test_Withdraw_RevertWhen_TheVaultIsPaused".to_owned(),
+                    "whenTheVaultIsNotPaused".to_owned(),
+                    "test_Withdraw_RevertWhen_TheVaultIsNotPaused".to_owned(),
+                    "test_Withdraw_GivenTheCallerIsTheOwner".to_owned(),
+                    // The modifier is only emitted once.
+                    "test_Deposit_WhenTheVaultIsNotPaused".to_owned(),
+                    "test_Deposit_RevertGiven_TheAmountIsZero".to_owned(),
+                ]
+            )
+        );
+        assert_eq!(files[1][0].path, PathBuf::from("specs/Bar.t.sol"));
+    }
+
+    #[test]
+    fn splits_trees() {
+        let text = "\
+Foo::bar
+├── given the caller is the owner
+│   ├── when the amount is zero
+│   │   └── it should revert
+│   └── it should work
+└── when the caller is not the owner
+    └── it should revert
+
+Foo::baz
+└── given the caller is the owner
+    ├── when the amount is zero
+    │   └── it should revert
+    └── it should work";
+        let cfg = Config { split_trees: true, ..Config::default() };
+        let files =
+            test_files(Path::new("specs/foo.tree"), text, &cfg).unwrap();
+
+        let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("specs/bar.t.sol"),
+                PathBuf::from("specs/baz.t.sol"),
+                PathBuf::from("specs/foo.shared.sol"),
+            ]
+        );
+
+        assert_eq!(
+            contents(&files[0].hir),
+            (
+                "Foo_Bar_Test".to_owned(),
+                false,
+                vec![
+                    "test_GivenTheCallerIsTheOwner".to_owned(),
+                    "test_RevertWhen_TheAmountIsZero".to_owned(),
+                    "test_RevertWhen_TheCallerIsNotTheOwner".to_owned(),
+                ]
+            )
+        );
+        assert_eq!(files[0].cfg.base_contracts, vec!["Foo_Shared_Test"]);
+        assert_eq!(files[0].cfg.imports[0].path, "./foo.shared.sol");
+        assert_eq!(
+            contents(&files[1].hir),
+            (
+                "Foo_Baz_Test".to_owned(),
+                false,
+                vec![
+                    "test_GivenTheCallerIsTheOwner".to_owned(),
+                    "test_RevertWhen_TheAmountIsZero".to_owned(),
+                ]
+            )
+        );
+        assert_eq!(
+            contents(&files[2].hir),
+            (
+                "Foo_Shared_Test".to_owned(),
+                true,
+                vec!["givenTheCallerIsTheOwner".to_owned()]
+            )
+        );
+        assert_eq!(files[2].cfg.base_contracts, Vec::<String>::new());
+    }
+
+    #[test]
+    fn shared_contract_inherits_forge_std_only_when_needed() {
+        let text = "\
+Foo::bar
+└── given the caller is the owner
+    ├── when the amount is zero
+    │   └── it should revert
+    └── it should work
+
+Foo::baz
+└── given the caller is the owner
+    └── when the amount is zero
+        └── it should revert";
+        let cfg = Config { split_trees: true, ..Config::default() };
+        let files =
+            test_files(Path::new("specs/foo.tree"), text, &cfg).unwrap();
+        let shared = scaffold_file(&files[2]).unwrap();
+        assert!(shared.contains("abstract contract Foo_Shared_Test {"));
+        assert!(!shared.contains("forge-std"));
+
+        let cfg = Config { emit_vm_skip: true, ..cfg };
+        let files =
+            test_files(Path::new("specs/foo.tree"), text, &cfg).unwrap();
+        let shared = scaffold_file(&files[2]).unwrap();
+        assert!(shared.contains(r#"import {Test} from "forge-std/Test.sol";"#));
+        assert!(shared.contains("abstract contract Foo_Shared_Test is Test {"));
+        let bar = scaffold_file(&files[0]).unwrap();
+        assert!(!bar.contains("forge-std"));
+        assert!(bar.contains("contract Foo_Bar_Test is Foo_Shared_Test {"));
+    }
+
+    #[test]
+    fn overrides_the_set_up_of_the_shared_contract() {
+        let text = "\
+Foo::bar
+└── given the caller is the owner
+    ├── when the amount is zero
+    │   └── it should revert
+    └── it should work
+
+Foo::baz
+└── given the caller is the owner
+    └── when the amount is zero
+        └── it should revert";
+        let mut cfg = Config {
+            split_trees: true,
+            emit_set_up: true,
+            ..Config::default()
+        };
+        let files =
+            test_files(Path::new("specs/foo.tree"), text, &cfg).unwrap();
+
+        let bar = scaffold_file(&files[0]).unwrap();
+        assert!(bar.contains(
+            "function setUp() public override {\n        super.setUp();\n    }"
+        ));
+        let shared = scaffold_file(&files[2]).unwrap();
+        assert!(shared.contains("function setUp() public virtual {}"));
+
+        cfg.base_contracts = vec!["Base_Test".to_owned(), "Utils".to_owned()];
+        let files =
+            test_files(Path::new("specs/foo.tree"), text, &cfg).unwrap();
+        let shared = scaffold_file(&files[2]).unwrap();
+        assert!(shared.contains(
+            "function setUp() public virtual override(Base_Test, Utils) {\n        super.setUp();\n    }"
+        ));
+    }
+
+    #[test]
+    fn keeps_trees_that_cannot_be_split() {
+        let cfg = Config { split_trees: true, ..Config::default() };
+        let text = "FooTest\n└── it should work";
+        let files = test_files(Path::new("foo.tree"), text, &cfg).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, PathBuf::from("foo.t.sol"));
+    }
+
+    #[test]
+    fn errors_when_split_files_overlap() {
+        let cfg = Config {
+            split_trees: true,
+            file_name: Some("{contract}.t.sol".to_owned()),
+            ..Config::default()
+        };
+        let text =
+            "Foo::bar\n└── it should work\n\nFoo::baz\n└── it should work";
+
+        assert!(test_files(Path::new("foo.tree"), text, &cfg).is_err());
+    }
+
+    #[test]
+    fn moves_modifiers_to_modifiers_file() {
+        let mut cfg = Config {
+            split_trees: true,
+            modifiers_file: Some(PathBuf::from("test/Modifiers.sol")),
+            ..Config::default()
+        };
+        let text = "\
+Foo::bar
+└── given the caller is the owner
+    ├── when the amount is zero
+    │   └── it should revert
+    └── it should work
+
+Foo::baz
+└── when the vault is paused
+    ├── given the caller is the owner
+    │   └── it should revert
+    └── it should work";
+        let mut files =
+            test_files(Path::new("test/foo/foo.tree"), text, &cfg).unwrap();
+        let text = "\
+Qux
+└── given the caller is the owner
+    └── it should work";
+        files.extend(
+            test_files(Path::new("test/qux.tree"), text, &cfg).unwrap(),
+        );
+
+        // No file holds the modifiers shared by the split trees.
+        assert_eq!(files.len(), 3);
+        for file in &files {
+            let (_, _, functions) = contents(&file.hir);
+            assert!(functions.iter().all(|f| f.starts_with("test_")));
+            assert_eq!(file.cfg.base_contracts, vec!["Modifiers"]);
+        }
+        assert_eq!(files[0].cfg.imports[0].path, "../Modifiers.sol");
+        assert_eq!(files[2].cfg.imports[0].path, "./Modifiers.sol");
+
+        let modifiers = modifiers_file(&files, &cfg).unwrap();
+        assert_eq!(modifiers.path, PathBuf::from("test/Modifiers.sol"));
+        assert_eq!(
+            contents(&modifiers.hir),
+            (
+                "Modifiers".to_owned(),
+                true,
+                vec![
+                    "givenTheCallerIsTheOwner".to_owned(),
+                    "whenTheVaultIsPaused".to_owned(),
+                ]
+            )
+        );
+
+        cfg.skip_modifiers = true;
+        let files = test_files(Path::new("test/qux.tree"), text, &cfg).unwrap();
+        assert_eq!(files[0].cfg.base_contracts, Vec::<String>::new());
+        assert!(modifiers_file(&files, &cfg).is_none());
+    }
+
+    #[test]
+    fn emits_modifiers_file_without_modifiers() {
+        let cfg = Config {
+            emit_set_up: true,
+            modifiers_file: Some(PathBuf::from("test/Modifiers.sol")),
+            ..Config::default()
+        };
+        let text = "Foo\n└── it should work";
+        let files = test_files(Path::new("test/foo.tree"), text, &cfg).unwrap();
+
+        // The test file inherits from `Modifiers` regardless, so the file is
+        // still emitted.
+        let modifiers = modifiers_file(&files, &cfg).unwrap();
+        assert_eq!(
+            contents(&modifiers.hir),
+            ("Modifiers".to_owned(), true, vec!["setUp".to_owned()])
+        );
+        let foo = scaffold_file(&files[0]).unwrap();
+        assert!(foo.contains("contract Foo is Modifiers {"));
+        assert!(foo.contains(
+            "function setUp() public override {\n        super.setUp();\n    }"
+        ));
+        let modifiers = scaffold_file(&modifiers).unwrap();
+        assert!(modifiers.contains("function setUp() public virtual {}"));
+    }
+
+    #[test]
+    fn computes_relative_imports() {
+        assert_eq!(
+            relative_import(Path::new("test/foo"), Path::new("test/foo/a.sol")),
+            "./a.sol"
+        );
+        assert_eq!(
+            relative_import(
+                Path::new("./test/foo/bar"),
+                Path::new("test/a.sol")
+            ),
+            "../../a.sol"
+        );
+        assert_eq!(
+            relative_import(Path::new(""), Path::new("a.sol")),
+            "./a.sol"
+        );
+    }
+}
@@ -7,12 +7,16 @@
 //! It also includes the implementation of a system to check that tests
 //! correspond to a spec in the form of a `.tree`. This implementation allows
 //! for defining rules to be checked, which may be automatically fixed.
+//!
+//! Finally, it reports how far the tests of a spec are implemented.
 
 pub mod check;
 pub mod config;
 pub mod constants;
 pub mod hir;
+pub mod layout;
 pub mod scaffold;
 pub mod sol;
+pub mod status;
 
 pub use check::violation::{self, Violation, ViolationKind};
@@ -1,12 +1,12 @@
 //! Defines a Solidity code emitter from a HIR.
 
-use std::result;
+use std::{fmt::Write, result};
 
 use bulloak_syntax::utils::sanitize;
 
 use crate::{
     config::Config,
-    constants::INTERNAL_DEFAULT_INDENTATION,
+    constants::{ANCHOR_TAG, INTERNAL_DEFAULT_INDENTATION},
     hir::{self, visitor::Visitor, Hir},
 };
 
@@ -19,6 +19,10 @@ pub struct Emitter {
     indent: usize,
     /// The Solidity version to be used in the pragma directive.
     solidity_version: String,
+    /// The SPDX license identifier of the emitted file.
+    license: String,
+    /// Whether `setUp` functions override the one in the base contracts.
+    override_set_up: bool,
 }
 
 impl Emitter {
@@ -28,6 +32,8 @@ impl Emitter {
         Self {
             indent: INTERNAL_DEFAULT_INDENTATION,
             solidity_version: cfg.solidity_version.clone(),
+            license: cfg.license.clone(),
+            override_set_up: cfg.inherits_set_up(),
         }
     }
 
@@ -69,6 +75,7 @@ impl EmitterI {
             Hir::Root(ref inner) => self.visit_root(inner).unwrap(),
             Hir::Contract(ref inner) => self.visit_contract(inner).unwrap(),
             Hir::Function(ref inner) => self.visit_function(inner).unwrap(),
+            Hir::StateVariable(ref inner) => self.emit_state_variable(inner),
             Hir::Comment(ref inner) => self.visit_comment(inner).unwrap(),
             Hir::Statement(_) => {
                 unreachable!(
@@ -92,7 +99,10 @@ impl EmitterI {
         // It's fine to unwrap here because we check that the filename always
         // has an extension.
         let contract_name = sanitize(&contract.identifier);
-        emitted.push_str(format!("contract {contract_name} {{\n").as_str());
+        if contract.is_abstract {
+            emitted.push_str("abstract ");
+        }
+        let _ = writeln!(emitted, "contract {contract_name} {{");
 
         emitted
     }
@@ -108,14 +118,23 @@ impl EmitterI {
     fn emit_modifier(&self, modifier: &str) -> String {
         let mut emitted = String::new();
         let indentation = self.emitter.indent();
-        emitted.push_str(&format!("{indentation}modifier {modifier}() {{\n"));
-        emitted.push_str(&format!("{}_;\n", indentation.repeat(2)));
-        emitted.push_str(&format!("{indentation}}}\n"));
+        let _ = writeln!(emitted, "{indentation}modifier {modifier}() {{");
+        let _ = writeln!(emitted, "{}_;", indentation.repeat(2));
+        let _ = writeln!(emitted, "{indentation}}}");
         emitted.push('\n');
 
         emitted
     }
 
+    /// Emit a state variable declaration.
+    fn emit_state_variable(&self, variable: &hir::StateVariable) -> String {
+        let indentation = self.emitter.indent();
+        format!(
+            "{indentation}{} internal {};\n\n",
+            variable.ty, variable.identifier
+        )
+    }
+
     /// Emit a function's definition header.
     ///
     /// This includes:
@@ -128,36 +147,50 @@ impl EmitterI {
         let fn_indentation = self.emitter.indent();
         let fn_body_indentation = fn_indentation.repeat(2);
 
+        if let Some(anchor) = &function.anchor {
+            let _ =
+                writeln!(emitted, "{fn_indentation}/// {ANCHOR_TAG} {anchor}");
+        }
+
+        let params = function
+            .params
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let visibility = if function.is_set_up() && self.emitter.override_set_up
+        {
+            "public override"
+        } else {
+            "external"
+        };
         let has_modifiers = function.modifiers.is_some();
         if has_modifiers {
-            emitted.push_str(
-                format!(
-                    "{}function {}()\n",
-                    fn_indentation, function.identifier
-                )
-                .as_str(),
+            let _ = writeln!(
+                emitted,
+                "{fn_indentation}function {}({params})",
+                function.identifier
             );
-            emitted
-                .push_str(format!("{fn_body_indentation}external\n").as_str());
+            let _ = writeln!(emitted, "{fn_body_indentation}{visibility}");
         } else {
-            emitted.push_str(
-                format!("{}function {}()", fn_indentation, function.identifier)
-                    .as_str(),
+            let _ = write!(
+                emitted,
+                "{fn_indentation}function {}({params})",
+                function.identifier
             );
-            emitted.push_str(" external");
+            emitted.push(' ');
+            emitted.push_str(visibility);
         }
 
         // Emit the modifiers that should be applied to this function.
         if let Some(ref modifiers) = function.modifiers {
             for modifier in modifiers {
-                emitted.push_str(
-                    format!("{fn_body_indentation}{modifier}\n").as_str(),
-                );
+                let _ = writeln!(emitted, "{fn_body_indentation}{modifier}");
             }
         }
 
         if has_modifiers {
-            emitted.push_str(format!("{fn_indentation}{{\n").as_str());
+            let _ = writeln!(emitted, "{fn_indentation}{{");
         } else {
             emitted.push_str(" {\n");
         }
@@ -184,11 +217,16 @@ impl Visitor for EmitterI {
         root: &hir::Root,
     ) -> result::Result<Self::RootOutput, Self::Error> {
         let mut emitted = String::new();
-        emitted.push_str("// SPDX-License-Identifier: UNLICENSED\n");
-        emitted.push_str(&format!(
+        let _ = writeln!(
+            emitted,
+            "// SPDX-License-Identifier: {}",
+            self.emitter.license
+        );
+        let _ = write!(
+            emitted,
             "pragma solidity {};\n\n",
             self.emitter.solidity_version
-        ));
+        );
 
         for hir in &root.children {
             let result = match hir {
@@ -212,8 +250,14 @@ impl Visitor for EmitterI {
         emitted.push_str(&contract_header);
 
         for hir in &contract.children {
-            if let Hir::Function(function) = hir {
-                emitted.push_str(&self.visit_function(function)?);
+            match hir {
+                Hir::Function(function) => {
+                    emitted.push_str(&self.visit_function(function)?);
+                }
+                Hir::StateVariable(variable) => {
+                    emitted.push_str(&self.emit_state_variable(variable));
+                }
+                _ => {}
             }
         }
 
@@ -248,7 +292,7 @@ impl Visitor for EmitterI {
             }
 
             let indentation = self.emitter.indent();
-            emitted.push_str(format!("{indentation}}}\n\n").as_str());
+            let _ = write!(emitted, "{indentation}}}\n\n");
         }
 
         Ok(emitted)
@@ -260,8 +304,7 @@ impl Visitor for EmitterI {
     ) -> result::Result<Self::CommentOutput, Self::Error> {
         let mut emitted = String::new();
         let indentation = self.emitter.indent().repeat(2);
-        emitted
-            .push_str(format!("{indentation}// {}\n", comment.lexeme).as_str());
+        let _ = writeln!(emitted, "{indentation}// {}", comment.lexeme);
 
         Ok(emitted)
     }
@@ -274,11 +317,18 @@ impl Visitor for EmitterI {
         let indentation = self.emitter.indent().repeat(2);
 
         // Match any supported statement to its string representation
-        match statement.ty {
+        match &statement.ty {
             hir::StatementType::VmSkip => {
-                emitted.push_str(
-                    format!("{indentation}vm.skip(true);\n").as_str(),
-                );
+                let _ = writeln!(emitted, "{indentation}vm.skip(true);");
+            }
+            hir::StatementType::VmExpectEmit => {
+                let _ = writeln!(emitted, "{indentation}vm.expectEmit();");
+            }
+            hir::StatementType::Emit(event) => {
+                let _ = writeln!(emitted, "{indentation}emit {event}();");
+            }
+            hir::StatementType::Snippet(snippet) => {
+                let _ = writeln!(emitted, "{indentation}{snippet}");
             }
         }
 
@@ -479,6 +529,31 @@ contract FileTest {
         Ok(())
     }
 
+    #[test]
+    fn with_expect_emit() -> anyhow::Result<()> {
+        let file_contents = "FileTest\n└── when something happens\n   └── it should emit a {Transfer} event";
+        let cfg = Config { emit_expect_emit: true, ..Config::default() };
+        let hir = translate(file_contents, &cfg)?;
+        let emitted = emitter::Emitter::new(&cfg).emit(&hir);
+
+        assert_eq!(
+            emitted,
+            r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+contract FileTest {
+  function test_WhenSomethingHappens() external {
+    // it should emit a {Transfer} event
+    // TODO: Set the `Transfer` event arguments.
+    vm.expectEmit();
+    emit Transfer();
+  }
+}"
+        );
+
+        Ok(())
+    }
+
     #[test]
     #[should_panic]
     fn with_vm_skip_top_level_statement() {
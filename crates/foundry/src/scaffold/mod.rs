@@ -2,9 +2,15 @@
 //!
 //! This command scaffolds a Solidity file from a spec `.tree` file.
 
-use forge_fmt::fmt;
+use bulloak_syntax::front_matter;
+use forge_fmt::{format, parse, FormatterError};
 
-use crate::{config::Config, hir::translate, sol};
+use crate::{
+    config::Config,
+    hir::{translate, Hir},
+    layout::TestFile,
+    sol,
+};
 
 pub mod emitter;
 pub mod modifiers;
@@ -14,12 +20,50 @@ pub mod modifiers;
 /// This function takes the content of a `.tree` file and a configuration,
 /// translates it to an intermediate representation, then to Solidity, and
 /// finally formats the resulting Solidity code.
+///
+/// Settings in the front matter of the `.tree` file override `cfg`.
 pub fn scaffold(text: &str, cfg: &Config) -> anyhow::Result<String> {
+    let (front_matter, _) = front_matter::split(text)?;
+    let cfg = &cfg.with_front_matter(&front_matter)?;
     let hir = translate(text, cfg)?;
-    let pt = sol::Translator::new(cfg).translate(&hir);
-    let source = sol::Formatter::new().emit(pt);
-    let formatted =
-        fmt(&source).expect("should format the emitted solidity code");
+
+    emit(&hir, cfg)
+}
+
+/// Generates the Solidity code of one of the files of a `.tree` file.
+///
+/// See [`crate::layout::test_files`].
+///
+/// # Errors
+///
+/// Returns an error if the emitted code can't be formatted.
+pub fn scaffold_file(file: &TestFile) -> anyhow::Result<String> {
+    emit(&file.hir, &file.cfg)
+}
+
+/// Translates a HIR to Solidity code and formats it.
+fn emit(hir: &Hir, cfg: &Config) -> anyhow::Result<String> {
+    let pt = sol::Translator::new(cfg).translate(hir);
+    let source = sol::Formatter::new(cfg).emit(pt);
+    // User-defined snippets may contain invalid Solidity, so formatting can
+    // fail here.
+    let formatted = fmt(&source, cfg).map_err(|e| {
+        anyhow::anyhow!("failed to format the emitted solidity code: {e}")
+    })?;
+
+    Ok(formatted)
+}
+
+/// Formats Solidity code with the `forge fmt` settings in `cfg`.
+///
+/// # Errors
+///
+/// Returns an error if the code can't be parsed or formatted.
+pub fn fmt(source: &str, cfg: &Config) -> Result<String, FormatterError> {
+    let parsed =
+        parse(source).map_err(|_| FormatterError::Fmt(std::fmt::Error))?;
+    let mut formatted = String::new();
+    format(&mut formatted, parsed, cfg.formatter_config.clone())?;
 
     Ok(formatted)
 }
@@ -4,11 +4,13 @@
 //! phases.
 
 use bulloak_syntax::{
-    utils::{lower_first_letter, to_pascal_case},
+    utils::{lower_first_letter, sanitize, to_pascal_case},
     Action, Ast, Condition, Description, Root, Visitor,
 };
 use indexmap::IndexMap;
 
+use crate::hir::translator::split_params;
+
 /// AST visitor that discovers modifiers.
 ///
 /// Modifiers are discovered by visiting the AST and collecting all condition
@@ -69,9 +71,11 @@ impl Visitor for ModifierDiscoverer {
         &mut self,
         condition: &Condition,
     ) -> anyhow::Result<Self::Output, Self::Error> {
+        // Fuzz parameters are not part of the modifier name.
+        let (title, _) = split_params(&condition.title);
         self.modifiers.insert(
             condition.title.clone(),
-            lower_first_letter(&to_pascal_case(&condition.title)),
+            lower_first_letter(&to_pascal_case(&sanitize(title))),
         );
 
         for condition in &condition.children {
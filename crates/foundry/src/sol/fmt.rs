@@ -9,6 +9,7 @@ use solang_parser::pt::{
 };
 
 use super::visitor::Visitor;
+use crate::{config::Config, constants::ANCHOR_TAG};
 
 trait Identified {
     fn name(&self) -> String;
@@ -20,11 +21,14 @@ impl Identified for Base {
     }
 }
 
-pub(crate) struct Formatter;
+pub(crate) struct Formatter {
+    /// The SPDX license identifier written before the pragma directive.
+    license: String,
+}
 
 impl Formatter {
-    pub(crate) fn new() -> Self {
-        Formatter {}
+    pub(crate) fn new(cfg: &Config) -> Self {
+        Formatter { license: cfg.license.clone() }
     }
 
     pub(crate) fn emit(&mut self, mut pt: SourceUnit) -> String {
@@ -32,7 +36,7 @@ impl Formatter {
             .visit_source_unit(&mut pt)
             .expect("should emit the solidity source");
 
-        cleanup_comments(&source)
+        cleanup_snippets(&cleanup_comments(&source))
     }
 }
 
@@ -58,8 +62,10 @@ impl Visitor for Formatter {
     ) -> Result<Self::Output, Self::Error> {
         match part {
             SourceUnitPart::PragmaDirective(_, _, _) => {
-                let header = "// SPDX-License-Identifier: UNLICENSED\n";
-                let header = format!("{header}{part}");
+                let header = format!(
+                    "// SPDX-License-Identifier: {}\n{part}",
+                    self.license
+                );
 
                 Ok(header)
             }
@@ -137,6 +143,12 @@ impl Visitor for Formatter {
     ) -> Result<Self::Output, Self::Error> {
         let mut result = String::new();
 
+        // The anchor is disguised as the first statement of the body, and
+        // goes in a comment above the function instead.
+        if let Some(anchor) = take_anchor(function) {
+            result.push_str(&format!("/// {ANCHOR_TAG} {anchor}\n"));
+        }
+
         result.push_str(&format!("{}", function.ty));
         result.push(' ');
         if let Some(ref name) = function.name {
@@ -308,6 +320,34 @@ impl Visitor for Formatter {
     }
 }
 
+/// Removes the special `__bulloak_anchor__` variable from the start of the
+/// body of a function, and returns the anchor it holds.
+fn take_anchor(function: &mut FunctionDefinition) -> Option<String> {
+    let Some(Statement::Block { statements, .. }) = &mut function.body else {
+        return None;
+    };
+    let Some(Statement::VariableDefinition(
+        _,
+        declaration,
+        Some(Expression::StringLiteral(literals)),
+    )) = statements.first()
+    else {
+        return None;
+    };
+    if declaration
+        .name
+        .as_ref()
+        .is_none_or(|name| name.name != "__bulloak_anchor__")
+    {
+        return None;
+    }
+
+    let anchor =
+        literals.iter().map(|literal| literal.string.as_str()).collect();
+    statements.remove(0);
+    Some(anchor)
+}
+
 /// Converts special `__bulloak_comment__` variables to regular solidity
 /// comments.
 ///
@@ -321,11 +361,24 @@ fn cleanup_comments(source: &str) -> String {
     RE_BULLOAK_COMMENT.replace_all(source, "// $1").to_string()
 }
 
+/// Converts special `__bulloak_snippet__` variables back to the user-provided
+/// Solidity code they stand for.
+///
+/// Specifically, it looks for patterns matching `string __bulloak_snippet__ =
+/// "<code>";` and converts them into `<code>`.
+fn cleanup_snippets(source: &str) -> String {
+    static RE_BULLOAK_SNIPPET: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#"string __bulloak_snippet__ = "(.*)";"#).unwrap()
+    });
+
+    RE_BULLOAK_SNIPPET.replace_all(source, "$1").to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
-    use crate::sol::fmt::cleanup_comments;
+    use crate::sol::fmt::{cleanup_comments, cleanup_snippets};
 
     #[test]
     fn cleanups_comments() {
@@ -374,4 +427,15 @@ function test_WhenFirstArgIsBiggerThanSecondArg() external {
 }}"#;
         assert_eq!(expected, cleanup_comments(&source));
     }
+
+    #[test]
+    fn cleanups_snippets() {
+        let source = r#"function test_Transfer() external {
+string __bulloak_snippet__ = "token.transfer(users.bob, "10");";
+}"#;
+        let expected = r#"function test_Transfer() external {
+token.transfer(users.bob, "10");
+}"#;
+        assert_eq!(expected, cleanup_snippets(source));
+    }
 }
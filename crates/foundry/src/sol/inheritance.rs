@@ -0,0 +1,249 @@
+//! Resolves the modifiers a contract inherits from its base contracts.
+//!
+//! Base contracts are looked up in the Solidity file of the contract and in
+//! the files it imports, following the import directives of each file. Import
+//! paths are resolved with [`Config::resolve_import`], so remappings apply.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use solang_parser::pt::{
+    ContractDefinition, ContractPart, FunctionTy, Import, ImportPath,
+    SourceUnit, SourceUnitPart,
+};
+
+use crate::config::Config;
+
+/// Returns the names of the modifiers that `contract`, defined in the
+/// Solidity file at `path` whose parse tree is `pt`, inherits from its base
+/// contracts, directly or not.
+///
+/// Base contracts that can't be found, e.g. because a file can't be read or
+/// parsed, are ignored.
+pub(crate) fn inherited_modifiers(
+    contract: &ContractDefinition,
+    pt: &SourceUnit,
+    path: &Path,
+    cfg: &Config,
+) -> HashSet<String> {
+    let mut resolver = Resolver {
+        cfg,
+        files: HashMap::from([(path.to_path_buf(), Some(pt.clone()))]),
+        visited: HashSet::new(),
+        modifiers: HashSet::new(),
+    };
+    resolver.visit_bases(contract, path);
+
+    resolver.modifiers
+}
+
+/// Walks the inheritance graph of a contract, collecting modifiers.
+struct Resolver<'a> {
+    cfg: &'a Config,
+    /// The parsed Solidity files, by path. `None` if a file can't be read or
+    /// parsed.
+    files: HashMap<PathBuf, Option<SourceUnit>>,
+    /// The contracts already visited, by file and name.
+    visited: HashSet<(PathBuf, String)>,
+    /// The names of the modifiers found so far.
+    modifiers: HashSet<String>,
+}
+
+impl Resolver<'_> {
+    /// Collects the modifiers of the base contracts of `contract`, which is
+    /// defined in the file at `path`.
+    fn visit_bases(&mut self, contract: &ContractDefinition, path: &Path) {
+        for base in &contract.base {
+            let identifiers = &base.name.identifiers;
+            let Some(name) = identifiers.last() else { continue };
+            // Bases of the form `Alias.Contract` come from a file imported
+            // with `import "..." as Alias`.
+            let alias = (identifiers.len() > 1).then(|| &identifiers[0].name);
+            let Some((base, base_path)) =
+                self.find_contract(&name.name, alias.map(String::as_str), path)
+            else {
+                continue;
+            };
+            if !self.visited.insert((base_path.clone(), name.name.clone())) {
+                continue;
+            }
+
+            self.modifiers.extend(base.parts.iter().filter_map(
+                |part| match part {
+                    ContractPart::FunctionDefinition(f)
+                        if f.ty == FunctionTy::Modifier =>
+                    {
+                        f.name.as_ref().map(|name| name.name.clone())
+                    }
+                    _ => None,
+                },
+            ));
+            self.visit_bases(&base, &base_path);
+        }
+    }
+
+    /// Finds the definition of the contract `name` in scope in the file at
+    /// `path`. Returns the contract and the path of the file defining it.
+    fn find_contract(
+        &mut self,
+        name: &str,
+        alias: Option<&str>,
+        path: &Path,
+    ) -> Option<(ContractDefinition, PathBuf)> {
+        self.find_in_file(name, alias, path, &mut HashSet::new())
+    }
+
+    /// Like [`Resolver::find_contract`], keeping track of the files searched
+    /// so far to break import cycles.
+    fn find_in_file(
+        &mut self,
+        name: &str,
+        alias: Option<&str>,
+        path: &Path,
+        searched: &mut HashSet<(PathBuf, String)>,
+    ) -> Option<(ContractDefinition, PathBuf)> {
+        if !searched.insert((path.to_path_buf(), name.to_owned())) {
+            return None;
+        }
+        let pt = self.parse(path)?;
+
+        if alias.is_none() {
+            let contract = pt.0.iter().find_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract)
+                    if contract
+                        .name
+                        .as_ref()
+                        .is_some_and(|n| n.name == name) =>
+                {
+                    Some(*contract.clone())
+                }
+                _ => None,
+            });
+            if let Some(contract) = contract {
+                return Some((contract, path.to_path_buf()));
+            }
+        }
+
+        for part in &pt.0 {
+            let SourceUnitPart::ImportDirective(import) = part else {
+                continue;
+            };
+            // The name the contract has in the imported file, if the import
+            // brings it into scope.
+            let (import_path, name) = match import {
+                Import::Plain(ImportPath::Filename(file), _) => {
+                    if alias.is_some() {
+                        continue;
+                    }
+                    (&file.string, name)
+                }
+                Import::GlobalSymbol(ImportPath::Filename(file), symbol, _) => {
+                    if alias != Some(symbol.name.as_str()) {
+                        continue;
+                    }
+                    (&file.string, name)
+                }
+                Import::Rename(ImportPath::Filename(file), symbols, _) => {
+                    if alias.is_some() {
+                        continue;
+                    }
+                    let Some((symbol, _)) = symbols
+                        .iter()
+                        .find(|(s, a)| a.as_ref().unwrap_or(s).name == name)
+                    else {
+                        continue;
+                    };
+                    (&file.string, symbol.name.as_str())
+                }
+                _ => continue,
+            };
+
+            let import_path = self.cfg.resolve_import(import_path, path);
+            if let Some(found) =
+                self.find_in_file(name, None, &import_path, searched)
+            {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the parse tree of the Solidity file at `path`.
+    fn parse(&mut self, path: &Path) -> Option<SourceUnit> {
+        self.files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| {
+                let src = fs::read_to_string(path).ok()?;
+                solang_parser::parse(&src, 0).ok().map(|(pt, _)| pt)
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fs, path::Path};
+
+    use tempfile::tempdir;
+
+    use super::inherited_modifiers;
+    use crate::{config::Config, sol::find_contract};
+
+    fn write(root: &Path, path: &str, src: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, src).unwrap();
+    }
+
+    #[test]
+    fn resolves_inherited_modifiers() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "lib/utils/src/Utils.sol",
+            "abstract contract Utils { modifier whenPaused() { _; } }",
+        );
+        write(
+            root,
+            "test/Base.t.sol",
+            r#"import {Utils as U} from "utils/Utils.sol";
+            abstract contract Base is U { modifier givenOwner() { _; } }"#,
+        );
+        write(
+            root,
+            "test/shared/Shared.sol",
+            r#"import "../Base.t.sol";
+            abstract contract Shared is Base { modifier whenShared() { _; } }"#,
+        );
+        write(
+            root,
+            "test/Other.sol",
+            r#"import "./Foo.t.sol";
+            abstract contract Other { modifier whenOther() { _; } }"#,
+        );
+        let src = r#"import {Shared} from "test/shared/Shared.sol";
+            import "./Other.sol" as O;
+            contract Foo is Shared, O.Other, Missing {}"#;
+        write(root, "test/Foo.t.sol", src);
+
+        let mut cfg = Config::default();
+        cfg.root = root.to_path_buf();
+        cfg.remappings = vec!["utils/=lib/utils/src/".parse().unwrap()];
+        let path = root.join("test/Foo.t.sol");
+        let (pt, _) = solang_parser::parse(src, 0).unwrap();
+        let contract = find_contract(&pt).unwrap();
+
+        assert_eq!(
+            inherited_modifiers(&contract, &pt, &path, &cfg),
+            HashSet::from(
+                ["whenPaused", "givenOwner", "whenShared", "whenOther"]
+                    .map(str::to_owned)
+            )
+        );
+    }
+}
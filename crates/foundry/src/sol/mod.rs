@@ -1,19 +1,52 @@
 //! This module implements functionality related to operating on a parse tree
 //! (PT) from `solang_parser`.
 
-use solang_parser::pt::{
-    ContractDefinition, ContractPart, FunctionDefinition, FunctionTy,
-    Identifier, SourceUnit, SourceUnitPart,
+use std::{collections::HashMap, ops::Range};
+
+use bulloak_syntax::utils::sanitize;
+use solang_parser::{
+    diagnostics::Diagnostic,
+    helpers::CodeLocation,
+    pt::{
+        Comment, ContractDefinition, ContractPart, ContractTy,
+        FunctionAttribute, FunctionDefinition, FunctionTy, Identifier, Loc,
+        Parameter, SourceUnit, SourceUnitPart,
+    },
 };
 
-use crate::hir::hir;
+use crate::{
+    constants::ANCHOR_TAG,
+    hir::{hir, Hir},
+};
 pub(crate) mod fmt;
+pub(crate) mod inheritance;
 pub(crate) mod translator;
 mod visitor;
 
 pub(crate) use fmt::Formatter;
 pub(crate) use translator::Translator;
 
+/// A parsed Solidity file.
+#[derive(Debug)]
+pub struct Parsed<'a> {
+    /// The contents of the file.
+    pub src: &'a str,
+    /// The parse tree of the file.
+    pub pt: SourceUnit,
+    /// The comments of the file, in order.
+    pub comments: Vec<Comment>,
+}
+
+/// Parses a Solidity file, keeping its comments.
+///
+/// # Errors
+///
+/// Returns the diagnostics of `solang_parser` if the file is invalid.
+pub fn parse(src: &str) -> Result<Parsed<'_>, Vec<Diagnostic>> {
+    let (pt, comments) = solang_parser::parse(src, 0)?;
+    Ok(Parsed { src, pt, comments })
+}
+
 /// Searches for and returns the first `ContractDefinition` found in a given
 /// `SourceUnit`.
 #[must_use]
@@ -24,35 +57,297 @@ pub fn find_contract(pt: &SourceUnit) -> Option<Box<ContractDefinition>> {
     })
 }
 
-/// Given a HIR function, `find_matching_fn` performs a search over the sol
-/// contract parts trying to find a sol function with a matching name and type.
-pub(crate) fn find_matching_fn<'a>(
-    contract_sol: &'a ContractDefinition,
-    fn_hir: &'a hir::FunctionDefinition,
-) -> Option<(usize, &'a FunctionDefinition)> {
-    contract_sol.parts.iter().enumerate().find_map(|(idx, part)| {
-        if let ContractPart::FunctionDefinition(fn_sol) = part {
-            if fns_match(fn_hir, fn_sol) {
-                return Some((idx, &**fn_sol));
+/// Pairs each contract of a HIR with its Solidity counterpart, if any.
+///
+/// Contracts are matched by name. HIR contracts left unmatched are then
+/// paired, in order, with the Solidity contracts left unmatched, so that
+/// renamed contracts can be reported. Interfaces, libraries and helper
+/// contracts, i.e. contracts with functions but no tests, `setUp` function or
+/// modifiers, are never paired this way.
+#[must_use]
+pub fn match_contracts<'h>(
+    hir: &'h Hir,
+    pt: &SourceUnit,
+) -> Vec<(&'h hir::ContractDefinition, Option<Box<ContractDefinition>>)> {
+    let contracts_sol: Vec<&ContractDefinition> =
+        pt.0.iter()
+            .filter_map(|part| match part {
+                SourceUnitPart::ContractDefinition(contract)
+                    if matches!(
+                        contract.ty,
+                        ContractTy::Contract(_) | ContractTy::Abstract(_)
+                    ) =>
+                {
+                    Some(&**contract)
+                }
+                _ => None,
+            })
+            .collect();
+    let name_of = |contract: &ContractDefinition| {
+        contract.name.as_ref().map(|name| name.name.clone())
+    };
+
+    let mut pairs: Vec<_> = hir
+        .contracts()
+        .map(|contract_hir| {
+            let name = sanitize(&contract_hir.identifier);
+            let found = contracts_sol
+                .iter()
+                .find(|contract_sol| {
+                    name_of(contract_sol) == Some(name.clone())
+                })
+                .map(|contract_sol| Box::new((*contract_sol).clone()));
+            (contract_hir, found)
+        })
+        .collect();
+
+    let matched: Vec<_> = pairs
+        .iter()
+        .filter_map(|(_, contract_sol)| contract_sol.as_ref().map(|c| &c.name))
+        .cloned()
+        .collect();
+    let mut unmatched = contracts_sol.into_iter().filter(|contract_sol| {
+        !matched.contains(&contract_sol.name) && !is_helper(contract_sol)
+    });
+    for (_, contract_sol) in &mut pairs {
+        if contract_sol.is_none() {
+            *contract_sol = unmatched.next().map(|c| Box::new(c.clone()));
+        }
+    }
+
+    pairs
+}
+
+/// Whether a Solidity contract is a helper. See [`match_contracts`].
+fn is_helper(contract: &ContractDefinition) -> bool {
+    let fns: Vec<_> = contract
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            ContractPart::FunctionDefinition(f) => Some(f),
+            _ => None,
+        })
+        .collect();
+
+    !fns.is_empty()
+        && !fns.iter().any(|f| {
+            f.ty == FunctionTy::Modifier
+                || f.name.as_ref().is_some_and(|name| {
+                    name.name.starts_with("test") || name.name == "setUp"
+                })
+        })
+}
+
+/// Maps the anchors and names of the functions of a Solidity contract to
+/// their indices in the contract's parts.
+///
+/// The index is built once, and then finds the match of each function of a
+/// tree by anchor or by name.
+pub(crate) struct FnIndex<'a> {
+    fns: HashMap<&'a str, Vec<(usize, &'a FunctionDefinition)>>,
+    anchors: HashMap<&'a str, (usize, &'a FunctionDefinition)>,
+}
+
+impl<'a> FnIndex<'a> {
+    /// Creates the index of the functions of `contract_sol`, whose source
+    /// code is `src`.
+    pub(crate) fn new(
+        contract_sol: &'a ContractDefinition,
+        src: &'a str,
+    ) -> Self {
+        let mut fns: HashMap<_, Vec<_>> = HashMap::new();
+        let mut anchors = HashMap::new();
+        for (idx, part) in contract_sol.parts.iter().enumerate() {
+            if let ContractPart::FunctionDefinition(fn_sol) = part {
+                if let Some(name) = &fn_sol.name {
+                    fns.entry(name.name.as_str())
+                        .or_default()
+                        .push((idx, &**fn_sol));
+                }
+                if let Some(anchor) = fn_anchor(src, fn_sol) {
+                    anchors.entry(anchor).or_insert((idx, &**fn_sol));
+                }
             }
-        };
+        }
+
+        Self { fns, anchors }
+    }
+
+    /// Returns the function that matches `fn_hir`, along with its index in
+    /// the contract's parts.
+    ///
+    /// A function with the same anchor and type matches first, whatever its
+    /// name. Otherwise, the first function that matches by name does. See
+    /// [`fns_match`].
+    pub(crate) fn find(
+        &self,
+        fn_hir: &hir::FunctionDefinition,
+    ) -> Option<(usize, &'a FunctionDefinition)> {
+        let anchored = fn_hir
+            .anchor
+            .as_deref()
+            .and_then(|anchor| self.anchors.get(anchor))
+            .filter(|(_, fn_sol)| fn_types_match(&fn_hir.ty, fn_sol.ty));
+        if let Some(found) = anchored {
+            return Some(*found);
+        }
+
+        self.fns
+            .get(fn_hir.identifier.as_str())?
+            .iter()
+            .find(|(_, fn_sol)| fns_match(fn_hir, fn_sol))
+            .copied()
+    }
+}
+
+/// Returns the anchor in the comments right above a Solidity function, if
+/// any. See [`hir::FunctionDefinition::anchor`].
+fn fn_anchor<'a>(src: &'a str, fn_sol: &FunctionDefinition) -> Option<&'a str> {
+    let Loc::File(_, start, _) = fn_sol.loc else { return None };
+    let line_start = src.get(..start)?.rfind('\n')?;
+    src[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .take_while(|line| {
+            line.starts_with("//")
+                || line.starts_with("/*")
+                || line.starts_with('*')
+        })
+        .find_map(|line| line.split_once(ANCHOR_TAG))
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+}
 
-        None
+/// Returns the location and name of every modifier that a Solidity function
+/// applies without arguments, in order.
+pub(crate) fn applied_modifiers(
+    fn_sol: &FunctionDefinition,
+) -> impl Iterator<Item = (Loc, &Identifier)> {
+    fn_sol.attributes.iter().filter_map(|attribute| match attribute {
+        FunctionAttribute::BaseOrModifier(loc, base)
+            if base.args.as_ref().is_none_or(Vec::is_empty) =>
+        {
+            match base.name.identifiers.as_slice() {
+                [identifier] => Some((*loc, identifier)),
+                _ => None,
+            }
+        }
+        _ => None,
     })
 }
 
+/// Returns the comments of the actions in the body of a Solidity function,
+/// along with their range in `src`, in order.
+///
+/// The comments of an action are a line comment that starts with `it`, like
+/// the title of an action, followed by the indented line comments right below
+/// it, like its descriptions. They end at the first blank line, statement,
+/// comment that isn't indented or `TODO:` comment.
+pub(crate) fn action_comments<'a>(
+    src: &'a str,
+    comments: &[Comment],
+    fn_sol: &FunctionDefinition,
+) -> Vec<Vec<(Range<usize>, &'a str)>> {
+    let Some(body) = &fn_sol.body else { return vec![] };
+    let (start, end) = (body.loc().start(), body.loc().end());
+    let comments = comments
+        .iter()
+        .filter(|comment| (start..end).contains(&comment.loc().start()));
+
+    let mut actions: Vec<Vec<(Range<usize>, &str)>> = vec![];
+    // The end of the last comment of the current action, if any.
+    let mut last_end = None;
+    for comment in comments {
+        let Comment::Line(loc, _) = comment else {
+            last_end = None;
+            continue;
+        };
+        let range = loc.start()..loc.end();
+        let line_start =
+            src[..range.start].rfind('\n').map_or(0, |idx| idx + 1);
+        let text = src[range.clone()].trim_start_matches("//");
+        let content = text.trim();
+        if !src[line_start..range.start].trim().is_empty()
+            || content.starts_with("TODO:")
+        {
+            last_end = None;
+            continue;
+        }
+
+        // Descriptions are indented, like in the tree, so they may start
+        // with `it` too.
+        let is_indented = text
+            .strip_prefix(' ')
+            .unwrap_or(text)
+            .starts_with(char::is_whitespace);
+        let is_title = !is_indented
+            && content
+                .split_whitespace()
+                .next()
+                .is_some_and(|word| word.eq_ignore_ascii_case("it"));
+        let is_below = last_end.is_some_and(|last_end| {
+            let gap = &src[last_end..range.start];
+            gap.trim().is_empty() && gap.matches('\n').count() == 1
+        });
+        match actions.last_mut() {
+            _ if is_title => actions.push(vec![(range.clone(), content)]),
+            Some(action) if is_indented && is_below => {
+                action.push((range.clone(), content));
+            }
+            _ => {
+                last_end = None;
+                continue;
+            }
+        }
+        last_end = Some(range.end);
+    }
+
+    actions
+}
+
 /// Check whether a Solidity function matches its bulloak counterpart.
 ///
-/// Two functions match if they have the same name and their types match.
+/// Two functions match if they have the same name, their types match and
+/// their parameter types match.
 fn fns_match(
     fn_hir: &hir::FunctionDefinition,
     fn_sol: &FunctionDefinition,
 ) -> bool {
     fn_sol.name.clone().is_some_and(|Identifier { ref name, .. }| {
-        name == &fn_hir.identifier && fn_types_match(&fn_hir.ty, fn_sol.ty)
+        name == &fn_hir.identifier
+            && fn_types_match(&fn_hir.ty, fn_sol.ty)
+            && params_match(&fn_hir.params, &fn_sol.params)
     })
 }
 
+/// Checks that the parameter types of a HIR function and a `solang_parser`
+/// function match. Parameter names are not taken into account.
+fn params_match(
+    params_hir: &[hir::Parameter],
+    params_sol: &[(Loc, Option<Parameter>)],
+) -> bool {
+    params_hir.len() == params_sol.len()
+        && params_hir.iter().zip(params_sol).all(
+            |(param_hir, (_, param_sol))| {
+                param_sol.as_ref().is_some_and(|param_sol| {
+                    canonical_ty(&param_hir.ty)
+                        == canonical_ty(&param_sol.ty.to_string())
+                })
+            },
+        )
+}
+
+/// Returns the canonical spelling of a Solidity type, so that aliases like
+/// `uint` and `uint256` compare equal.
+fn canonical_ty(ty: &str) -> String {
+    let ty: String = ty.split_whitespace().collect();
+    match ty.as_str() {
+        "uint" => "uint256".to_owned(),
+        "int" => "int256".to_owned(),
+        _ => ty,
+    }
+}
+
 /// Checks that the function types between a HIR function
 /// and a `solang_parser` function match.
 ///
@@ -61,7 +356,9 @@ fn fns_match(
 /// named the same as a function per Foundry's best practices.
 const fn fn_types_match(ty_hir: &hir::FunctionTy, ty_sol: FunctionTy) -> bool {
     match ty_hir {
-        hir::FunctionTy::Function => matches!(ty_sol, FunctionTy::Function),
+        hir::FunctionTy::Function | hir::FunctionTy::SetUp => {
+            matches!(ty_sol, FunctionTy::Function)
+        }
         hir::FunctionTy::Modifier => matches!(ty_sol, FunctionTy::Modifier),
     }
 }
@@ -73,7 +370,7 @@ mod tests {
 
     use crate::{
         hir,
-        sol::{find_matching_fn, fn_types_match, fns_match},
+        sol::{fn_anchor, fn_types_match, fns_match, match_contracts, FnIndex},
     };
 
     #[test]
@@ -94,7 +391,9 @@ mod tests {
             ty,
             span: Default::default(),
             modifiers: Default::default(),
+            params: Default::default(),
             children: Default::default(),
+            anchor: Default::default(),
         }
     }
 
@@ -145,15 +444,13 @@ mod tests {
     }
 
     #[test]
-    fn test_find_matching_fn() {
+    fn test_fn_index() {
         let needle_sol = fn_sol("needle", pt::FunctionTy::Function);
         let haystack = vec![
             fn_sol_as_part("hay", pt::FunctionTy::Function),
-            fn_sol_as_part("more_hay", pt::FunctionTy::Function),
+            fn_sol_as_part("needle", pt::FunctionTy::Modifier),
             fn_sol_as_part("needle", pt::FunctionTy::Function),
-            fn_sol_as_part("hay_more", pt::FunctionTy::Function),
         ];
-        let needle_hir = fn_hir("needle", hir::FunctionTy::Function);
         let contract = pt::ContractDefinition {
             loc: Default::default(),
             ty: pt::ContractTy::Contract(Default::default()),
@@ -161,22 +458,118 @@ mod tests {
             base: Default::default(),
             parts: haystack,
         };
+        let index = FnIndex::new(&contract, "");
 
-        let expected = needle_sol;
-        let actual = find_matching_fn(&contract, &needle_hir).unwrap();
-        assert_eq!((2, &expected), actual);
-
-        let haystack = vec![];
         let needle_hir = fn_hir("needle", hir::FunctionTy::Function);
-        let contract = pt::ContractDefinition {
-            loc: Default::default(),
-            ty: pt::ContractTy::Contract(Default::default()),
-            name: Default::default(),
-            base: Default::default(),
-            parts: haystack,
+        assert_eq!(Some((2, &needle_sol)), index.find(&needle_hir));
+        let needle_hir = fn_hir("missing", hir::FunctionTy::Function);
+        assert_eq!(None, index.find(&needle_hir));
+    }
+
+    #[test]
+    fn test_fn_index_matches_anchors_first() {
+        let src = r"contract C {
+    /// Renamed by hand.
+    /// @custom:bulloak 1a2b3c4d
+    function test_Renamed() external {}
+
+    function test_Foo() external {}
+}";
+        let (pt, _) = solang_parser::parse(src, 0).unwrap();
+        let pt::SourceUnitPart::ContractDefinition(contract) = &pt.0[0] else {
+            unreachable!()
+        };
+        let index = FnIndex::new(contract, src);
+        let fn_at = |idx: usize| match &contract.parts[idx] {
+            pt::ContractPart::FunctionDefinition(f) => &**f,
+            _ => unreachable!(),
+        };
+
+        let mut needle_hir = fn_hir("test_Foo", hir::FunctionTy::Function);
+        assert_eq!(Some((1, fn_at(1))), index.find(&needle_hir));
+        needle_hir.anchor = Some("1a2b3c4d".to_owned());
+        assert_eq!(Some((0, fn_at(0))), index.find(&needle_hir));
+        needle_hir.ty = hir::FunctionTy::Modifier;
+        assert_eq!(None, index.find(&needle_hir));
+
+        assert_eq!(Some("1a2b3c4d"), fn_anchor(src, fn_at(0)));
+        assert_eq!(None, fn_anchor(src, fn_at(1)));
+    }
+
+    fn contract_hir(identifier: &str) -> hir::Hir {
+        hir::Hir::Contract(hir::ContractDefinition {
+            identifier: identifier.to_owned(),
+            is_abstract: false,
+            children: vec![],
+        })
+    }
+
+    #[test]
+    fn test_match_contracts() {
+        let hir = hir::Hir::Root(hir::Root {
+            children: vec![
+                contract_hir("Foo"),
+                contract_hir("Bar"),
+                contract_hir("Baz"),
+            ],
+        });
+        let src = r"
+            interface Bar { function bar() external; }
+            library Baz { function baz() internal {} }
+            contract Helper { function help() external {} }
+            contract Renamed { function test_Bar() external {} }
+            contract Foo { function test_Foo() external {} }
+        ";
+        let (pt, _) = solang_parser::parse(src, 0).unwrap();
+
+        let actual: Vec<_> = match_contracts(&hir, &pt)
+            .into_iter()
+            .map(|(contract_hir, contract_sol)| {
+                (
+                    contract_hir.identifier.as_str(),
+                    contract_sol.and_then(|c| c.name).map(|n| n.name),
+                )
+            })
+            .collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("Foo", Some("Foo".to_owned())),
+                ("Bar", Some("Renamed".to_owned())),
+                ("Baz", None),
+            ]
+        );
+    }
+
+    fn param_sol(ty: pt::Type) -> (pt::Loc, Option<pt::Parameter>) {
+        let param = pt::Parameter {
+            loc: pt::Loc::default(),
+            annotation: None,
+            ty: pt::Expression::Type(pt::Loc::default(), ty),
+            storage: None,
+            name: Some(pt::Identifier::new("x")),
         };
+        (pt::Loc::default(), Some(param))
+    }
+
+    #[test]
+    fn test_fns_match_params() {
+        let mut needle_hir = fn_hir("testFuzz_x", hir::FunctionTy::Function);
+        needle_hir.params = vec![hir::Parameter {
+            ty: "uint".to_owned(),
+            name: "amount".to_owned(),
+        }];
+
+        let mut needle_sol = fn_sol("testFuzz_x", pt::FunctionTy::Function);
+        assert!(!fns_match(&needle_hir, &needle_sol));
+
+        needle_sol.params = vec![param_sol(pt::Type::Uint(256))];
+        assert!(fns_match(&needle_hir, &needle_sol));
+
+        needle_sol.params = vec![param_sol(pt::Type::Uint(128))];
+        assert!(!fns_match(&needle_hir, &needle_sol));
 
-        let actual = find_matching_fn(&contract, &needle_hir);
-        assert_eq!(None, actual);
+        needle_sol.params = vec![param_sol(pt::Type::Address)];
+        assert!(!fns_match(&needle_hir, &needle_sol));
     }
 }
@@ -15,18 +15,20 @@
 //! interface for initiating the translation process. Internally, a
 //! `TranslatorI` struct implements the detailed translation logic.
 
-use std::cell::Cell;
+use std::{cell::Cell, collections::HashSet};
 
 use bulloak_syntax::utils::sanitize;
 use solang_parser::pt::{
     Base, ContractDefinition, ContractPart, ContractTy, Expression,
     FunctionAttribute, FunctionDefinition, FunctionTy, Identifier,
-    IdentifierPath, Import, ImportPath, Loc, SourceUnit, SourceUnitPart,
-    Statement, StringLiteral, Type, VariableDeclaration, Visibility,
+    IdentifierPath, Import, ImportPath, Loc, Parameter, SourceUnit,
+    SourceUnitPart, Statement, StorageLocation, StringLiteral, Type,
+    VariableAttribute, VariableDeclaration, VariableDefinition, Visibility,
 };
 
 use crate::{
-    config::Config,
+    config::{self, Config},
+    constants::FORGE_STD_TEST_CONTRACT,
     hir::{self, visitor::Visitor, Hir},
 };
 
@@ -43,18 +45,29 @@ pub(crate) struct Translator {
     with_forge_std: bool,
     /// Whether to emit modifiers.
     skip_modifiers: bool,
+    /// The contracts test contracts inherit from.
+    base_contracts: Vec<String>,
+    /// The base contracts that define a `setUp` function. See
+    /// [`Config::set_up_bases`].
+    set_up_bases: Vec<String>,
+    /// Additional import directives.
+    imports: Vec<config::Import>,
 }
 
 impl Translator {
     /// Create a new translator.
     #[must_use]
     pub(crate) fn new(cfg: &Config) -> Self {
-        let with_forge_std = [cfg.emit_vm_skip].into_iter().any(|f| f);
+        let with_forge_std =
+            [cfg.emit_vm_skip, cfg.emit_expect_emit].into_iter().any(|f| f);
 
         Self {
             sol_version: cfg.solidity_version.clone(),
             with_forge_std,
             skip_modifiers: cfg.skip_modifiers,
+            base_contracts: cfg.base_contracts.clone(),
+            set_up_bases: cfg.set_up_bases().map(ToOwned::to_owned).collect(),
+            imports: cfg.imports.clone(),
         }
     }
 
@@ -74,12 +87,21 @@ struct TranslatorI {
     offset: Cell<usize>,
     /// The translator state.
     translator: Translator,
+    /// The handler contracts of the root being translated. See [`handlers`].
+    handlers: HashSet<String>,
+    /// Whether the contract being translated is abstract.
+    is_abstract: bool,
 }
 
 impl TranslatorI {
     /// Creates a new internal translator.
     fn new(translator: Translator) -> Self {
-        Self { offset: Cell::new(0), translator }
+        Self {
+            offset: Cell::new(0),
+            translator,
+            handlers: HashSet::new(),
+            is_abstract: false,
+        }
     }
 
     /// Concrete implementation of the translation from AST to HIR.
@@ -104,7 +126,7 @@ impl TranslatorI {
     /// `pt::FunctionTy` variant.
     fn translate_function_ty(&self, ty: &hir::FunctionTy) -> FunctionTy {
         match ty {
-            hir::FunctionTy::Function => {
+            hir::FunctionTy::Function | hir::FunctionTy::SetUp => {
                 self.bump("function");
                 FunctionTy::Function
             }
@@ -122,6 +144,133 @@ impl TranslatorI {
         Identifier { loc: function_name_loc, name: identifier.to_owned() }
     }
 
+    /// Bumps `self.offset` given a function's parameters and returns the
+    /// appropriate list of `Parameter`s.
+    ///
+    /// The parameter type is kept as written in the tree, so it is emitted
+    /// as a plain identifier.
+    fn translate_params(
+        &self,
+        params: &[hir::Parameter],
+    ) -> Vec<(Loc, Option<Parameter>)> {
+        self.bump("(");
+        let params = params
+            .iter()
+            .enumerate()
+            .map(|(idx, param)| {
+                if idx > 0 {
+                    self.bump(", ");
+                }
+                let param_start = self.offset.get();
+                let ty_loc = self.bump(&param.ty);
+                let ty = Expression::Variable(Identifier {
+                    loc: ty_loc,
+                    name: param.ty.clone(),
+                });
+                let storage = param.is_reference().then(|| {
+                    self.bump(" ");
+                    StorageLocation::Memory(self.bump("memory"))
+                });
+                self.bump(" ");
+                let name = Identifier {
+                    loc: self.bump(&param.name),
+                    name: param.name.clone(),
+                };
+                let loc = Loc::File(0, param_start, self.offset.get());
+
+                (
+                    loc,
+                    Some(Parameter {
+                        loc,
+                        annotation: None,
+                        ty,
+                        storage,
+                        name: Some(name),
+                    }),
+                )
+            })
+            .collect();
+        self.bump(") ");
+
+        params
+    }
+
+    /// Bumps `self.offset` given an import directive and returns the
+    /// appropriate `SourceUnitPart::ImportDirective`.
+    fn translate_import(&self, import: &config::Import) -> SourceUnitPart {
+        let loc_import_start = self.offset.get();
+        let symbols: Vec<(Identifier, Option<Identifier>)> =
+            if import.symbols.is_empty() {
+                self.bump("import \"");
+                vec![]
+            } else {
+                self.bump("import { ");
+                import
+                    .symbols
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, symbol)| {
+                        if idx > 0 {
+                            self.bump(", ");
+                        }
+                        let loc = self.bump(symbol);
+                        (Identifier { loc, name: symbol.clone() }, None)
+                    })
+                    .collect()
+            };
+        if !symbols.is_empty() {
+            self.bump(" } from \"");
+        }
+        let loc_path = self.bump(&import.path);
+        let path = ImportPath::Filename(StringLiteral {
+            loc: loc_path,
+            unicode: false,
+            string: import.path.clone(),
+        });
+        let loc = Loc::File(0, loc_import_start, loc_path.end());
+        self.bump("\";\n");
+
+        // The import directive `Rename` corresponds to `import {x} from
+        // y.sol`.
+        if symbols.is_empty() {
+            SourceUnitPart::ImportDirective(Import::Plain(path, loc))
+        } else {
+            SourceUnitPart::ImportDirective(Import::Rename(path, symbols, loc))
+        }
+    }
+
+    /// Bumps `self.offset` given a state variable and returns the appropriate
+    /// `ContractPart::VariableDefinition`.
+    fn translate_state_variable(
+        &self,
+        variable: &hir::StateVariable,
+    ) -> ContractPart {
+        let start_offset = self.offset.get();
+        let ty_loc = self.bump(&variable.ty);
+        self.bump(" ");
+        let visibility_loc = self.bump("internal");
+        self.bump(" ");
+        let name_loc = self.bump(&variable.identifier);
+        let variable_def = VariableDefinition {
+            loc: Loc::File(0, start_offset, self.offset.get()),
+            ty: Expression::Variable(Identifier {
+                loc: ty_loc,
+                name: variable.ty.clone(),
+            }),
+            attrs: vec![VariableAttribute::Visibility(Visibility::Internal(
+                Some(visibility_loc),
+            ))],
+            name: Some(Identifier {
+                loc: name_loc,
+                name: variable.identifier.clone(),
+            }),
+            initializer: None,
+        };
+        self.bump(";\n\n");
+
+        ContractPart::VariableDefinition(Box::new(variable_def))
+    }
+
     /// Bumps `self.offset` given a modifier and returns the appropriate
     /// `FunctionAttribute::BaseOrModifier`.
     fn translate_modifier(&self, modifier: &str) -> FunctionAttribute {
@@ -180,11 +329,74 @@ impl TranslatorI {
                     );
                 };
 
+                attrs
+            }
+            hir::FunctionTy::SetUp => {
+                let inherits_set_up = !self.translator.set_up_bases.is_empty();
+                let mut attrs = vec![];
+                if inherits_set_up || self.is_abstract {
+                    // Base contracts usually define a `setUp` function that
+                    // is `public virtual`, and so do the abstract contracts
+                    // test contracts inherit from.
+                    attrs.push(FunctionAttribute::Visibility(
+                        Visibility::Public(Some(self.bump("public"))),
+                    ));
+                } else {
+                    attrs.push(FunctionAttribute::Visibility(
+                        Visibility::External(Some(self.bump("external"))),
+                    ));
+                }
+                if self.is_abstract {
+                    self.bump(" ");
+                    attrs
+                        .push(FunctionAttribute::Virtual(self.bump("virtual")));
+                }
+                if inherits_set_up {
+                    self.bump(" ");
+                    attrs.push(self.translate_set_up_override());
+                }
+                self.bump(" ");
+
                 attrs
             }
         }
     }
 
+    /// Generates the `override` attribute of a `setUp` function.
+    ///
+    /// With several base contracts that define `setUp`, every one of them is
+    /// listed, e.g. `override(Base_Test, Utils)`, since Solidity requires it.
+    fn translate_set_up_override(&self) -> FunctionAttribute {
+        let start = self.offset.get();
+        self.bump("override");
+        let bases = &self.translator.set_up_bases;
+        if bases.len() < 2 {
+            return FunctionAttribute::Override(
+                Loc::File(0, start, self.offset.get()),
+                vec![],
+            );
+        }
+
+        self.bump("(");
+        let mut paths = Vec::with_capacity(bases.len());
+        for (idx, base) in bases.iter().enumerate() {
+            if idx > 0 {
+                self.bump(", ");
+            }
+            let loc = self.bump(base);
+            paths.push(IdentifierPath {
+                loc,
+                identifiers: vec![Identifier { loc, name: base.clone() }],
+            });
+        }
+        self.bump(")");
+
+        FunctionAttribute::Override(
+            Loc::File(0, start, self.offset.get()),
+            paths,
+        )
+    }
+
     /// Generates the statements of a modifier function. In the context of this
     /// translation, a modifier's body is represented by a special variable
     /// definition. This function creates and returns a vector of statements
@@ -226,6 +438,49 @@ impl TranslatorI {
         stmts
     }
 
+    /// Generates a `string <variable_name> = "<text>";` statement.
+    ///
+    /// Text that has no direct representation in the parse tree, like
+    /// comments, is disguised as a variable definition with a special name and
+    /// converted back to its original form when formatting the emitted source.
+    /// See [`crate::sol::fmt`].
+    fn gen_disguised_statement(
+        &self,
+        variable_name: &str,
+        text: &str,
+    ) -> Statement {
+        let definition_start = self.offset.get();
+        let declaration_start = definition_start;
+        let ty = Expression::Type(self.bump("string"), Type::String);
+        self.bump(" "); // ` ` after type.
+        let variable_loc = self.bump(variable_name);
+        let declaration_loc =
+            Loc::File(0, declaration_start, self.offset.get());
+        let name =
+            Identifier { loc: variable_loc, name: variable_name.to_owned() };
+        let variable = VariableDeclaration {
+            loc: declaration_loc,
+            ty,
+            storage: None,
+            name: Some(name),
+        };
+        self.bump(" = ");
+        let text_loc = self.bump(&format!(r#""{text}""#));
+        let string_literal =
+            Some(Expression::StringLiteral(vec![StringLiteral {
+                loc: text_loc,
+                unicode: false,
+                string: text.to_owned(),
+            }]));
+        self.bump(";"); // `;` after string literal.
+
+        Statement::VariableDefinition(
+            Loc::File(0, definition_start, self.offset.get()),
+            variable,
+            string_literal,
+        )
+    }
+
     /// Generates the statements of a function by processing its child nodes.
     /// This function iterates through each child node in the provided
     /// vector, translating comments into statements and adding them to the
@@ -302,12 +557,21 @@ impl TranslatorI {
     ) -> Result<Vec<Statement>, ()> {
         let stmts = match function.ty {
             hir::FunctionTy::Modifier => self.gen_modifier_statements(),
-            hir::FunctionTy::Function => {
+            hir::FunctionTy::Function | hir::FunctionTy::SetUp => {
+                let mut stmts = function
+                    .anchor
+                    .iter()
+                    .map(|anchor| {
+                        self.gen_disguised_statement(
+                            "__bulloak_anchor__",
+                            anchor,
+                        )
+                    })
+                    .collect::<Vec<_>>();
                 if let Some(ref children) = function.children {
-                    self.gen_function_statements(children)?
-                } else {
-                    vec![]
+                    stmts.append(&mut self.gen_function_statements(children)?);
                 }
+                stmts
             }
         };
 
@@ -374,35 +638,24 @@ impl Visitor for TranslatorI {
         ));
         self.bump(";\n");
 
-        // Add the forge-std's Test import, if needed.
-        if self.translator.with_forge_std {
-            // Getting the relevant offsets for `import {Test} from
-            // "forge-std/Test.sol"`.
-            let loc_import_start = self.offset.get();
-            self.bump("import { ");
-            let loc_identifier = self.bump("Test");
-            self.bump(" } from \"");
-            let loc_path = self.bump("forge-std/Test.sol");
-
-            // The import directive `Rename` corresponds to `import {x} from
-            // y.sol`.
-            source_unit.push(SourceUnitPart::ImportDirective(Import::Rename(
-                ImportPath::Filename(StringLiteral {
-                    loc: loc_path,
-                    unicode: false,
-                    string: "forge-std/Test.sol".to_string(),
-                }),
-                vec![(
-                    Identifier {
-                        loc: loc_identifier,
-                        name: "Test".to_string(),
-                    },
-                    None,
-                )],
-                Loc::File(0, loc_import_start, loc_path.end()),
-            )));
+        self.translator.with_forge_std |= needs_forge_std(root);
+        self.handlers = handlers(root);
+
+        // Add the forge-std's Test import, if needed. User-defined base
+        // contracts are expected to provide forge-std's cheatcodes
+        // themselves, but handlers don't inherit from them. forge-std's
+        // `Test` may be a base contract too.
+        let bases = &self.translator.base_contracts;
+        let with_test = (self.translator.with_forge_std
+            && (bases.is_empty() || !self.handlers.is_empty()))
+            || bases.iter().any(|base| base == FORGE_STD_TEST_CONTRACT);
+        if with_test && !imports_forge_std(&self.translator.imports) {
+            source_unit
+                .push(self.translate_import(&config::Import::forge_std()));
+        }
 
-            self.bump("\";\n");
+        for import in &self.translator.imports.clone() {
+            source_unit.push(self.translate_import(import));
         }
 
         for child in &root.children {
@@ -440,7 +693,12 @@ impl Visitor for TranslatorI {
         contract: &hir::ContractDefinition,
     ) -> Result<Self::ContractDefinitionOutput, Self::Error> {
         let contract_start = self.offset.get();
-        let contract_ty = ContractTy::Contract(self.bump("contract"));
+        self.is_abstract = contract.is_abstract;
+        let contract_ty = if contract.is_abstract {
+            ContractTy::Abstract(self.bump("abstract contract"))
+        } else {
+            ContractTy::Contract(self.bump("contract"))
+        };
         self.bump(" ");
         let contract_name = sanitize(&contract.identifier);
         let contract_name = Some(Identifier {
@@ -448,36 +706,52 @@ impl Visitor for TranslatorI {
             name: contract.identifier.clone(),
         });
 
-        let mut contract_base = vec![];
+        // User-defined base contracts take precedence over forge-std's `Test`,
+        // except for handlers, which aren't test contracts.
+        let is_handler = self.handlers.contains(&contract.identifier);
+        let bases = if !is_handler && !self.translator.base_contracts.is_empty()
+        {
+            self.translator.base_contracts.clone()
+        } else if self.translator.with_forge_std {
+            vec!["Test".to_owned()]
+        } else {
+            vec![]
+        };
 
-        // If there is an import, inherit the base contract as well.
-        if self.translator.with_forge_std {
-            let base_start = self.offset.get();
-            self.bump(" is ");
-            let base_loc = self.bump("Test");
+        let mut contract_base = Vec::with_capacity(bases.len());
+        for (idx, base) in bases.iter().enumerate() {
+            self.bump(if idx == 0 { " is " } else { ", " });
+            let base_loc = self.bump(base);
             let base_identifier_path = IdentifierPath {
                 loc: base_loc,
                 identifiers: vec![Identifier {
                     loc: base_loc,
-                    name: "Test".to_string(),
+                    name: base.clone(),
                 }],
             };
 
-            contract_base = vec![Base {
-                loc: Loc::File(0, base_start, base_loc.end()),
+            contract_base.push(Base {
+                loc: base_loc,
                 name: base_identifier_path,
                 args: None,
-            }];
+            });
         }
         self.bump(" {"); // `{` after contract identifier and base.
 
         let mut parts = Vec::with_capacity(contract.children.len());
         for child in &contract.children {
-            if let Hir::Function(function) = child {
-                if function.is_modifier() && self.translator.skip_modifiers {
-                    continue;
+            match child {
+                Hir::Function(function) => {
+                    if function.is_modifier() && self.translator.skip_modifiers
+                    {
+                        continue;
+                    }
+                    parts.push(self.visit_function(function)?);
+                }
+                Hir::StateVariable(variable) => {
+                    parts.push(self.translate_state_variable(variable));
                 }
-                parts.push(self.visit_function(function)?);
+                _ => {}
             }
         }
 
@@ -534,7 +808,7 @@ impl Visitor for TranslatorI {
             self.translate_function_id(&function.identifier);
         let function_id_loc = function_identifier.loc;
         let function_name = Some(function_identifier);
-        self.bump("() "); // `(<identifier>) `.
+        let params = self.translate_params(&function.params);
         let attributes = self.gen_function_attr(function);
 
         let body_start = self.offset.get();
@@ -546,7 +820,7 @@ impl Visitor for TranslatorI {
             ty: function_ty,
             name: function_name,
             name_loc: function_id_loc,
-            params: vec![],
+            params,
             attributes,
             return_not_returns: None,
             returns: vec![],
@@ -596,38 +870,7 @@ impl Visitor for TranslatorI {
         //
         // The idea is to remove the extra parts with a search and replace when
         // emitting the parse tree and leave the comment's lexeme as is.
-        let definition_start = self.offset.get();
-        let declaration_start = definition_start;
-        let ty = Expression::Type(self.bump("string"), Type::String);
-        self.bump(" "); // ` ` after type.
-        let variable_name = "__bulloak_comment__";
-        let variable_loc = self.bump(variable_name);
-        let declaration_loc =
-            Loc::File(0, declaration_start, self.offset.get());
-        let name =
-            Identifier { loc: variable_loc, name: variable_name.to_owned() };
-        let variable = VariableDeclaration {
-            loc: declaration_loc,
-            ty,
-            storage: None,
-            name: Some(name),
-        };
-        self.bump(" = ");
-        let comment_loc = self.bump(&format!(r#""{}""#, &comment.lexeme));
-        let string_literal =
-            Some(Expression::StringLiteral(vec![StringLiteral {
-                loc: comment_loc,
-                unicode: false,
-                string: comment.lexeme.clone(),
-            }]));
-        self.bump(";"); // `;` after string literal.
-        let definition = Statement::VariableDefinition(
-            Loc::File(0, definition_start, self.offset.get()),
-            variable,
-            string_literal,
-        );
-
-        Ok(definition)
+        Ok(self.gen_disguised_statement("__bulloak_comment__", &comment.lexeme))
     }
 
     /// Visits a supported statement node and match based on its type.
@@ -637,7 +880,7 @@ impl Visitor for TranslatorI {
     ) -> Result<Self::StatementOutput, Self::Error> {
         let start_offset = self.offset.get();
 
-        match statement.ty {
+        match &statement.ty {
             hir::StatementType::VmSkip => {
                 let loc_vm = self.bump("vm");
                 self.bump(".");
@@ -673,6 +916,155 @@ impl Visitor for TranslatorI {
                     vm_skip_call,
                 ))
             }
+            hir::StatementType::VmExpectEmit => {
+                let loc_vm = self.bump("vm");
+                self.bump(".");
+                let loc_expect_emit = self.bump("expectEmit");
+                self.bump("();");
+
+                let vm_interface = Expression::MemberAccess(
+                    Loc::File(0, start_offset, loc_expect_emit.end()),
+                    Box::new(Expression::Variable(Identifier {
+                        loc: loc_vm,
+                        name: "vm".to_owned(),
+                    })),
+                    Identifier {
+                        loc: loc_expect_emit,
+                        name: "expectEmit".to_owned(),
+                    },
+                );
+
+                let vm_expect_emit_call = Expression::FunctionCall(
+                    Loc::File(0, loc_expect_emit.start(), self.offset.get()),
+                    Box::new(vm_interface),
+                    vec![],
+                );
+
+                Ok(Statement::Expression(
+                    Loc::File(0, start_offset, self.offset.get()),
+                    vm_expect_emit_call,
+                ))
+            }
+            hir::StatementType::Emit(event) => {
+                self.bump("emit ");
+                let loc_event = self.bump(event);
+                self.bump("();");
+
+                let event_call = Expression::FunctionCall(
+                    Loc::File(0, loc_event.start(), self.offset.get() - 1),
+                    Box::new(Expression::Variable(Identifier {
+                        loc: loc_event,
+                        name: event.clone(),
+                    })),
+                    vec![],
+                );
+
+                Ok(Statement::Emit(
+                    Loc::File(0, start_offset, self.offset.get()),
+                    event_call,
+                ))
+            }
+            hir::StatementType::Snippet(snippet) => {
+                Ok(self.gen_disguised_statement("__bulloak_snippet__", snippet))
+            }
         }
     }
 }
+
+/// Returns the handler contracts of a root, i.e. the contracts that another
+/// contract of the root holds in a state variable, like the handler of an
+/// invariant spec.
+fn handlers(root: &hir::Root) -> HashSet<String> {
+    root.children
+        .iter()
+        .filter_map(|child| match child {
+            Hir::Contract(contract) => Some(&contract.children),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|child| match child {
+            Hir::StateVariable(variable) => Some(variable.ty.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the code emitted for `hir` with `cfg` relies on forge-std's `Test`
+/// contract, i.e. on its cheatcodes or helpers.
+pub(crate) fn uses_forge_std(hir: &Hir, cfg: &Config) -> bool {
+    cfg.emit_vm_skip
+        || cfg.emit_expect_emit
+        || matches!(hir, Hir::Root(root) if needs_forge_std(root))
+}
+
+/// Whether the emitted code relies on forge-std's `Test` contract.
+///
+/// Fuzz tests bound their inputs with `bound` and invariant tests register
+/// their handler with `targetContract` in `setUp`.
+fn needs_forge_std(root: &hir::Root) -> bool {
+    let needs_forge_std = |child: &Hir| match child {
+        Hir::Function(function) => function.is_fuzz_test(),
+        Hir::StateVariable(_) => true,
+        _ => false,
+    };
+
+    root.children.iter().any(|child| match child {
+        Hir::Contract(contract) => {
+            contract.children.iter().any(needs_forge_std)
+        }
+        _ => false,
+    })
+}
+
+/// Whether one of the user-defined imports already imports forge-std's `Test`
+/// contract.
+fn imports_forge_std(imports: &[config::Import]) -> bool {
+    let forge_std = config::Import::forge_std();
+    imports.iter().any(|import| {
+        import.symbols.iter().any(|symbol| symbol == FORGE_STD_TEST_CONTRACT)
+            || (import.symbols.is_empty() && import.path == forge_std.path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{config::Config, scaffold::scaffold};
+
+    #[test]
+    fn imports_forge_std_when_it_is_a_base() -> anyhow::Result<()> {
+        let file_contents = "Foo_Test\n└── it should work";
+        let cfg = Config {
+            emit_vm_skip: true,
+            base_contracts: vec!["Test".to_owned()],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            scaffold(file_contents, &cfg)?,
+            r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+
+import {Test} from "forge-std/Test.sol";
+
+contract Foo_Test is Test {
+    function test_ShouldWork() external {
+        // it should work
+        vm.skip(true);
+    }
+}
+"#
+        );
+
+        // The import isn't repeated when passed explicitly.
+        let cfg = Config {
+            imports: vec!["Test=forge-std/Test.sol".parse().unwrap()],
+            ..cfg
+        };
+        let scaffolded = scaffold(file_contents, &cfg)?;
+        assert_eq!(1, scaffolded.matches("import").count());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Classifies the tests of a spec `.tree` file by how far they are
+//! implemented, for `bulloak status`.
+
+use solang_parser::pt::{self, Expression, Statement};
+
+use crate::{
+    check::context::Context,
+    hir::{self, Hir},
+    sol::{match_contracts, FnIndex},
+};
+
+/// How far a test of the tree is implemented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TestStatus {
+    /// The test is missing from the Solidity file.
+    Missing,
+    /// The body of the test has nothing but comments.
+    Stub,
+    /// The test calls `vm.skip(true)`.
+    Skipped,
+    /// The body of the test has statements.
+    Implemented,
+}
+
+impl TestStatus {
+    /// Classifies a Solidity function by its body.
+    #[must_use]
+    pub fn of(fn_sol: &pt::FunctionDefinition) -> Self {
+        let Some(Statement::Block { statements, .. }) = &fn_sol.body else {
+            return Self::Stub;
+        };
+
+        if statements.iter().any(is_vm_skip) {
+            Self::Skipped
+        } else if statements.is_empty() {
+            Self::Stub
+        } else {
+            Self::Implemented
+        }
+    }
+}
+
+/// Whether a statement is a `vm.skip(true)` call.
+fn is_vm_skip(statement: &Statement) -> bool {
+    let Statement::Expression(_, Expression::FunctionCall(_, callee, args)) =
+        statement
+    else {
+        return false;
+    };
+
+    matches!(
+        &**callee,
+        Expression::MemberAccess(_, object, member)
+            if member.name == "skip"
+                && matches!(&**object, Expression::Variable(v) if v.name == "vm")
+    ) && matches!(args.first(), Some(Expression::BoolLiteral(_, true)))
+}
+
+/// Returns the tests of the tree in `ctx`, along with their status, in tree
+/// order.
+///
+/// Tests are matched with the functions of the Solidity file like `bulloak
+/// check` does, by anchor and then by name.
+#[must_use]
+pub fn test_statuses(
+    ctx: &Context,
+) -> Vec<(&hir::FunctionDefinition, TestStatus)> {
+    let mut statuses = vec![];
+    for (contract_hir, contract_sol) in match_contracts(&ctx.hir, &ctx.pt) {
+        let fn_index = contract_sol
+            .as_deref()
+            .map(|contract_sol| FnIndex::new(contract_sol, &ctx.src));
+        let tests =
+            contract_hir.children.iter().filter_map(|child| match child {
+                Hir::Function(f) if f.is_function() => Some(f),
+                _ => None,
+            });
+        for fn_hir in tests {
+            let status = fn_index
+                .as_ref()
+                .and_then(|fn_index| fn_index.find(fn_hir))
+                .map_or(TestStatus::Missing, |(_, fn_sol)| {
+                    TestStatus::of(fn_sol)
+                });
+            statuses.push((fn_hir, status));
+        }
+    }
+
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn classifies_tests() {
+        let tree = r"Foo
+├── It should do one thing.
+├── It should do another thing.
+├── It should do a third thing.
+└── It should do a fourth thing.
+";
+        let sol = r"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.0;
+contract Foo {
+  function test_ShouldDoOneThing() external {
+    // It should do one thing.
+  }
+
+  function test_ShouldDoAnotherThing() external {
+    // It should do another thing.
+    vm.skip(true);
+  }
+
+  function test_ShouldDoAThirdThing() external {
+    // It should do a third thing.
+    assertTrue(foo.done());
+  }
+}
+";
+        let td = tempdir().unwrap();
+        let tree_path = td.path().join("X.tree");
+        fs::write(&tree_path, tree).unwrap();
+        fs::write(td.path().join("X.t.sol"), sol).unwrap();
+        let ctx = Context::new(tree_path, &Config::default()).unwrap();
+
+        let statuses: Vec<_> = test_statuses(&ctx)
+            .into_iter()
+            .map(|(f, status)| (f.identifier.as_str(), status))
+            .collect();
+        assert_eq!(
+            vec![
+                ("test_ShouldDoOneThing", TestStatus::Stub),
+                ("test_ShouldDoAnotherThing", TestStatus::Skipped),
+                ("test_ShouldDoAThirdThing", TestStatus::Implemented),
+                ("test_ShouldDoAFourthThing", TestStatus::Missing),
+            ],
+            statuses
+        );
+    }
+}
@@ -1,6 +1,6 @@
 //! The AST for a bulloak tree file.
 
-use crate::span::Span;
+use crate::span::{Position, Span};
 
 /// An Abstract Syntax Tree (AST) that describes the semantic
 /// structure of a bulloak tree.
@@ -39,6 +39,21 @@ impl Ast {
     pub fn is_action(&self) -> bool {
         matches!(self, Self::Action(_))
     }
+
+    /// Moves the spans of this node and its children so that they are
+    /// relative to `start` instead of to the beginning of the tree.
+    pub(crate) fn shift(&mut self, start: Position) {
+        let (span, children) = match self {
+            Self::Root(x) => (&mut x.span, x.children.as_mut_slice()),
+            Self::Condition(x) => (&mut x.span, x.children.as_mut_slice()),
+            Self::Action(x) => (&mut x.span, x.children.as_mut_slice()),
+            Self::ActionDescription(x) => (&mut x.span, [].as_mut_slice()),
+        };
+        *span = span.after(start);
+        for child in children {
+            child.shift(start);
+        }
+    }
 }
 
 /// The root node of the AST.
@@ -34,9 +34,7 @@ pub trait FrontendError<K: fmt::Display>: std::error::Error {
         let divider = repeat_str("•", 79);
         writeln!(f, "{divider}")?;
 
-        let start_offset = self.span().start.offset;
-        let end_offset = self.span().end.offset;
-        if start_offset == end_offset && start_offset == 0 {
+        if self.span().is_empty() {
             write!(f, "bulloak error: {}", self.kind())?;
             return Ok(());
         }
@@ -0,0 +1,232 @@
+//! Defines the front matter of a `.tree` file.
+//!
+//! The front matter is an optional block of `key: value` settings at the top
+//! of a `.tree` file, delimited by `---` lines:
+//!
+//! ```text
+//! ---
+//! solidity-version: ">=0.8.22 <0.9.0"
+//! vm-skip: true
+//! ---
+//! FooTest
+//! └── it should do something
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. Interpreting the
+//! settings is left to the consumers of this crate.
+
+use std::{fmt, result};
+
+use thiserror::Error;
+
+use crate::{
+    error::FrontendError,
+    span::{Position, Span},
+};
+
+type Result<T> = result::Result<T, Error>;
+
+/// The delimiter that opens and closes the front matter.
+const DELIMITER: &str = "---";
+
+/// An error that occurred while parsing the front matter of a `.tree` file.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    /// The kind of error.
+    #[source]
+    kind: ErrorKind,
+    /// The original text that the error was generated from. Every span in an
+    /// error is a valid range into this string.
+    text: String,
+    /// The span of this error.
+    span: Span,
+}
+
+impl FrontendError<ErrorKind> for Error {
+    /// Return the type of this error.
+    fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The original text string in which this error occurred.
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Return the span at which this error occurred.
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.format_error(f)
+    }
+}
+
+/// The type of an error that occurred while parsing the front matter.
+#[derive(Error, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The front matter is missing its closing delimiter.
+    #[error("the front matter is missing its closing `---`")]
+    DelimiterMissing,
+    /// Found a line that is not a `key: value` pair.
+    #[error("expected a `key: value` pair, found \"{0}\"")]
+    EntryInvalid(String),
+}
+
+/// The settings declared in the front matter of a `.tree` file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// The settings, in the order they were declared.
+    pub entries: Vec<Entry>,
+}
+
+impl FrontMatter {
+    /// Whether the front matter declares no settings.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A single `key: value` setting of the front matter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The name of the setting.
+    pub key: String,
+    /// The value of the setting, without surrounding quotes.
+    pub value: String,
+    /// The span of the line that declares this setting.
+    pub span: Span,
+}
+
+/// Splits the front matter off the given text.
+///
+/// Returns the parsed front matter, which is empty if there is none, and the
+/// rest of the text.
+///
+/// # Errors
+///
+/// Returns an error if the front matter is not terminated or contains a line
+/// that is not a `key: value` pair.
+pub fn split(text: &str) -> Result<(FrontMatter, &str)> {
+    let start = text.len() - text.trim_start().len();
+    let mut lines = text[start..].split_inclusive('\n');
+    let opening = match lines.next() {
+        Some(line) if line.trim_end() == DELIMITER => line,
+        _ => return Ok((FrontMatter::default(), text)),
+    };
+
+    let opening_line = text[..start].matches('\n').count() + 1;
+    if !lines.clone().any(|line| line.trim() == DELIMITER) {
+        return Err(Error {
+            kind: ErrorKind::DelimiterMissing,
+            text: text.to_owned(),
+            span: Span::new(
+                Position::new(start, opening_line, 1),
+                Position::new(start + DELIMITER.len() - 1, opening_line, 3),
+            ),
+        });
+    }
+
+    let mut front_matter = FrontMatter::default();
+    let mut offset = start + opening.len();
+    for (idx, line) in lines.enumerate() {
+        let line_number = opening_line + idx + 1;
+        let content = line.trim();
+        if content == DELIMITER {
+            return Ok((front_matter, &text[offset + line.len()..]));
+        }
+
+        if !content.is_empty() && !content.starts_with('#') {
+            let end = line.trim_end().len().max(1);
+            let span = Span::new(
+                Position::new(offset, line_number, 1),
+                Position::new(offset + end - 1, line_number, end),
+            );
+            let entry = content
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), unquote(value.trim())))
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| Error {
+                    kind: ErrorKind::EntryInvalid(content.to_owned()),
+                    text: text.to_owned(),
+                    span,
+                })?;
+
+            front_matter.entries.push(Entry {
+                key: entry.0.to_owned(),
+                value: entry.1.to_owned(),
+                span,
+            });
+        }
+
+        offset += line.len();
+    }
+
+    unreachable!("the closing delimiter should be found")
+}
+
+/// Removes the quotes surrounding a value, if any.
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .into_iter()
+        .find_map(|quote| {
+            value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote))
+        })
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{split, ErrorKind};
+    use crate::error::FrontendError;
+
+    #[test]
+    fn splits_front_matter() {
+        let text = "---\n# Settings for this file.\nsolidity-version: \">=0.8.22 <0.9.0\"\n\nvm-skip: true\n---\nFooTest\n└── it should do something";
+        let (front_matter, rest) = split(text).unwrap();
+
+        let entries: Vec<_> = front_matter
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), e.value.as_str(), e.span.start.line))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("solidity-version", ">=0.8.22 <0.9.0", 3),
+                ("vm-skip", "true", 5)
+            ]
+        );
+        assert_eq!(rest, "FooTest\n└── it should do something");
+    }
+
+    #[test]
+    fn skips_text_without_front_matter() {
+        let text = "FooTest\n└── it should do something";
+        let (front_matter, rest) = split(text).unwrap();
+
+        assert!(front_matter.is_empty());
+        assert_eq!(rest, text);
+    }
+
+    #[test]
+    fn errors_on_invalid_front_matter() {
+        let error = split("---\nvm-skip: true\nFooTest\n").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::DelimiterMissing);
+        assert_eq!(error.span().start.line, 1);
+
+        let error = split("---\nvm-skip\n---\nFooTest\n").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::EntryInvalid("vm-skip".to_owned())
+        );
+        assert_eq!(error.span().start.line, 2);
+    }
+}
@@ -6,6 +6,7 @@
 mod ast;
 mod char;
 mod error;
+pub mod front_matter;
 pub mod parser;
 pub mod semantics;
 mod span;
@@ -17,13 +18,46 @@ mod visitor;
 
 pub use ast::{Action, Ast, Condition, Description, Root};
 pub use error::FrontendError;
+pub use front_matter::FrontMatter;
 pub use span::{Position, Span};
 pub use tokenizer::{Token, TokenKind};
 pub use visitor::Visitor;
 
 /// Parses a string containing trees into ASTs.
+///
+/// The front matter of the text, if any, is skipped. The spans of the ASTs and
+/// of the errors are relative to the whole text, so that they point to the
+/// lines of the file the text was read from.
+///
+/// # Errors
+///
+/// Returns an error if the front matter or any of the trees is invalid.
 pub fn parse(text: &str) -> anyhow::Result<Vec<ast::Ast>> {
-    splitter::split_trees(text).map(parse_one).collect()
+    let (_, body) = front_matter::split(text)?;
+    splitter::split_trees(body)
+        .map(|tree| parse_at(text, tree, splitter::tree_start(text, tree)))
+        .collect()
+}
+
+/// Parses `tree`, which starts at `start` in `text`, into an AST whose spans
+/// are relative to `text`.
+fn parse_at(
+    text: &str,
+    tree: &str,
+    start: Position,
+) -> anyhow::Result<ast::Ast> {
+    let tokens = tokenizer::Tokenizer::new()
+        .tokenize(tree)
+        .map_err(|e| e.relocate(text, start))?;
+    let mut ast = parser::Parser::new()
+        .parse(tree, &tokens)
+        .map_err(|e| e.relocate(text, start))?;
+    semantics::SemanticAnalyzer::new(tree)
+        .analyze(&ast)
+        .map_err(|e| e.relocate(text, start))?;
+    ast.shift(start);
+
+    Ok(ast)
 }
 
 /// Parses a string containing a single tree into an AST.
@@ -35,3 +69,37 @@ pub fn parse_one(text: &str) -> anyhow::Result<ast::Ast> {
 
     Ok(ast)
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::{parse, Ast, Position};
+
+    #[test]
+    fn spans_count_from_the_top_of_the_file() {
+        let text = "---\nvm-skip: true\n---\nFoo::a\n└── it should a\n\nFoo::b\n└── it should b";
+        let starts: Vec<_> = parse(text)
+            .unwrap()
+            .iter()
+            .map(|ast| match ast {
+                Ast::Root(root) => root.children[0].span().start,
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(
+            starts,
+            vec![Position::new(29, 5, 1), Position::new(59, 8, 1)]
+        );
+    }
+
+    #[test]
+    fn errors_count_from_the_top_of_the_file() {
+        let text = "---\nvm-skip: true\n---\nFoo\n└── when a\n└── it should b";
+        let error = parse(text).unwrap_err().to_string();
+
+        assert!(error.contains("└── when a\n^\n"), "{error}");
+        assert!(error.contains("--- (line 5, column 1) ---"), "{error}");
+    }
+}
@@ -9,7 +9,7 @@ use super::{
 };
 use crate::{
     error::FrontendError,
-    span::Span,
+    span::{Position, Span},
     utils::{repeat_str, sanitize},
 };
 
@@ -29,6 +29,19 @@ pub struct Error {
     span: Span,
 }
 
+impl Error {
+    /// Moves this error into `text`, where the tree it was generated from
+    /// starts at `start`.
+    pub(crate) fn relocate(self, text: &str, start: Position) -> Self {
+        let span = if self.span.is_empty() {
+            self.span
+        } else {
+            self.span.after(start)
+        };
+        Self { text: text.to_owned(), span, ..self }
+    }
+}
+
 impl FrontendError<ErrorKind> for Error {
     /// Return the type of this error.
     fn kind(&self) -> &ErrorKind {
@@ -378,7 +391,7 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
 
         let previous = self.previous().unwrap();
         Ok(Ast::Condition(Condition {
-            title: sanitize(&title),
+            title: sanitize_condition_title(&title),
             children,
             span: Span::new(token.span.start, previous.span.end),
         }))
@@ -512,6 +525,19 @@ impl<'t, P: Borrow<Parser>> ParserI<'t, P> {
     }
 }
 
+/// Sanitizes a condition title, keeping any trailing fuzz parameter list,
+/// like `(uint128 amount)`, as written.
+fn sanitize_condition_title(title: &str) -> String {
+    let title = title.trim_end();
+    match title.rfind('(') {
+        Some(idx) if title.ends_with(')') => {
+            let (text, params) = title.split_at(idx);
+            format!("{} {params}", sanitize(text).trim_end())
+        }
+        _ => sanitize(title),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -834,6 +860,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keeps_fuzz_params() {
+        let input = indoc! {"
+            Foo
+            └── when st-ff is ok (uint128 amount, address to)
+               └── it should revert
+        "};
+
+        let Ast::Root(root) = parse(input).unwrap() else {
+            panic!("expected a root");
+        };
+        let Ast::Condition(ref condition) = root.children[0] else {
+            panic!("expected a condition");
+        };
+        assert_eq!(
+            condition.title,
+            "when st_ff is ok (uint128 amount, address to)"
+        );
+    }
+
     #[test]
     fn unsanitized_input() {
         let input = indoc! {r#"
@@ -6,7 +6,7 @@ use thiserror::Error;
 use super::ast::{self, Ast};
 use crate::{
     error::FrontendError,
-    span::Span,
+    span::{Position, Span},
     utils::{lower_first_letter, sanitize, to_pascal_case},
     visitor::Visitor,
 };
@@ -38,6 +38,33 @@ impl Error {
     pub fn new(kind: ErrorKind, text: String, span: Span) -> Self {
         Error { kind, text, span }
     }
+
+    /// Moves this error into `text`, where the tree it was generated from
+    /// starts at `start`.
+    pub(crate) fn relocate(self, text: &str, start: Position) -> Self {
+        let kind = match self.kind {
+            ErrorKind::IdentifierDuplicated(spans) => {
+                ErrorKind::IdentifierDuplicated(
+                    spans.into_iter().map(|span| span.after(start)).collect(),
+                )
+            }
+            kind => kind,
+        };
+        let span = if self.span.is_empty() {
+            self.span
+        } else {
+            self.span.after(start)
+        };
+        Self { kind, text: text.to_owned(), span }
+    }
+}
+
+impl Errors {
+    /// Moves these errors into `text`, where the tree they were generated
+    /// from starts at `start`.
+    pub(crate) fn relocate(self, text: &str, start: Position) -> Self {
+        Self(self.0.into_iter().map(|e| e.relocate(text, start)).collect())
+    }
 }
 
 impl FrontendError<ErrorKind> for Error {
@@ -100,6 +100,17 @@ impl Span {
     pub const fn with_end(self, pos: Position) -> Self {
         Self { end: pos, ..self }
     }
+
+    /// Returns this span as if the text it refers to started at `start`.
+    pub(crate) const fn after(self, start: Position) -> Self {
+        Self::new(self.start.after(start), self.end.after(start))
+    }
+
+    /// Whether this span carries no location, which is the case of errors
+    /// that are not tied to a part of the text.
+    pub(crate) const fn is_empty(&self) -> bool {
+        self.start.offset == 0 && self.end.offset == 0
+    }
 }
 
 impl Position {
@@ -114,4 +125,18 @@ impl Position {
     pub const fn new(offset: usize, line: usize, column: usize) -> Self {
         Self { offset, line, column }
     }
+
+    /// Returns this position as if the text it refers to started at `start`.
+    pub(crate) const fn after(self, start: Self) -> Self {
+        let column = if self.line == 1 {
+            start.column + self.column - 1
+        } else {
+            self.column
+        };
+        Self::new(
+            start.offset + self.offset,
+            start.line + self.line - 1,
+            column,
+        )
+    }
 }
@@ -1,3 +1,5 @@
+use crate::span::Position;
+
 /// The separator used between trees when parsing `.tree` files with multiple
 /// trees.
 pub(crate) const TREES_SEPARATOR: &str = "\n\n";
@@ -16,6 +18,23 @@ pub(crate) fn split_trees(text: &str) -> Box<dyn Iterator<Item = &str> + '_> {
     Box::new(no_isolated_comments)
 }
 
+/// Returns the position at which `tree`, one of the trees returned by
+/// [`split_trees`], starts in `text`.
+pub(crate) fn tree_start(text: &str, tree: &str) -> Position {
+    let offset = (tree.as_ptr() as usize)
+        .checked_sub(text.as_ptr() as usize)
+        .filter(|&offset| offset <= text.len())
+        .unwrap_or_default();
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+    Position::new(
+        offset,
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 /// Return whether the given string only contains lines starting with `//`.
 fn not_only_comments(tree: &&str) -> bool {
     !tree.lines().all(|l| l.trim().starts_with("//"))
@@ -23,7 +42,19 @@ fn not_only_comments(tree: &&str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::split_trees;
+    use super::{split_trees, tree_start};
+    use crate::span::Position;
+
+    #[test]
+    fn finds_tree_starts() {
+        let text = "Foo::a\n└── it should a\n\n\n  Foo::b\n└── it should b";
+        let starts: Vec<_> =
+            split_trees(text).map(|tree| tree_start(text, tree)).collect();
+        assert_eq!(
+            starts,
+            vec![Position::new(0, 1, 1), Position::new(33, 5, 3)]
+        );
+    }
 
     #[test]
     fn splits_trees() {
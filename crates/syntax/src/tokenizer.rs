@@ -26,6 +26,19 @@ pub struct Error {
     span: Span,
 }
 
+impl Error {
+    /// Moves this error into `text`, where the tree it was generated from
+    /// starts at `start`.
+    pub(crate) fn relocate(self, text: &str, start: Position) -> Self {
+        let span = if self.span.is_empty() {
+            self.span
+        } else {
+            self.span.after(start)
+        };
+        Self { text: text.to_owned(), span, ..self }
+    }
+}
+
 impl FrontendError<ErrorKind> for Error {
     /// Return the type of this error.
     fn kind(&self) -> &ErrorKind {
@@ -56,6 +69,13 @@ pub enum ErrorKind {
     /// Found an invalid identifier character.
     #[error("invalid identifier: {0}")]
     IdentifierCharInvalid(char),
+    /// Found a parameter list that is not a single `(type name, ...)` list at
+    /// the end of a condition.
+    #[error(
+        "invalid parameter list: expected a single `(type name, ...)` list at \
+         the end of the condition"
+    )]
+    ParamsInvalid,
 }
 
 /// `Token` represents a single unit of meaning in a .tree.
@@ -351,6 +371,29 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
         Ok(tokens)
     }
 
+    /// Checks that the rest of the line, which starts with a `(`, is a
+    /// well-formed parameter list.
+    fn check_params(&self) -> Result<()> {
+        let rest = &self.text()[self.offset()..];
+        let line = rest.lines().next().unwrap_or_default().trim_end();
+        let is_valid = line
+            .strip_prefix('(')
+            .and_then(|line| line.strip_suffix(')'))
+            .is_some_and(|list| !list.contains(['(', ')']) && is_params(list));
+        if is_valid {
+            return Ok(());
+        }
+
+        let start = self.pos();
+        let last = line.chars().last().unwrap_or('(');
+        let end = Position::new(
+            start.offset + line.len() - last.len_utf8(),
+            start.line,
+            start.column + line.chars().count() - 1,
+        );
+        Err(self.error(Span::new(start, end), ErrorKind::ParamsInvalid))
+    }
+
     /// Discards all characters until the end of the line.
     fn scan_comments(&self) {
         loop {
@@ -371,6 +414,13 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
         let span_start = self.pos();
 
         loop {
+            // A parenthesized list declares fuzz parameters, which
+            // are not part of the identifier.
+            if self.is_identifier_mode() && self.char() == '(' {
+                self.check_params()?;
+                self.exit_identifier_mode();
+            }
+
             if self.is_identifier_mode() && !self.char().is_valid_identifier() {
                 let kind = ErrorKind::IdentifierCharInvalid(self.char());
                 let error = self.error(self.span(), kind);
@@ -392,6 +442,42 @@ impl<'s, T: Borrow<Tokenizer>> TokenizerI<'s, T> {
     }
 }
 
+/// Whether `list` declares one or more comma separated parameters of the
+/// form `type [location] name`, e.g. `uint128 amount, address to`.
+fn is_params(list: &str) -> bool {
+    list.split(',').all(|param| {
+        match param.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [ty, name] | [ty, "memory" | "calldata" | "storage", name] => {
+                is_param_type(ty) && is_param_name(name)
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Whether `ty` is a Solidity type name, possibly qualified and with array
+/// dimensions, e.g. `uint256`, `IERC20.Info` or `address[2][]`.
+fn is_param_type(ty: &str) -> bool {
+    let (name, dims) = ty.split_at(ty.find('[').unwrap_or(ty.len()));
+    let dims_valid = dims
+        .strip_prefix('[')
+        .and_then(|dims| dims.strip_suffix(']'))
+        .is_none_or(|dims| {
+            dims.split("][").all(|dim| dim.chars().all(|c| c.is_ascii_digit()))
+        });
+
+    dims_valid && name.split('.').all(is_param_name)
+}
+
+/// Whether `name` is a valid Solidity identifier.
+fn is_param_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -401,7 +487,9 @@ mod tests {
         span::Span,
         test_utils::{p, s, TestError},
         tokenizer::{
-            self, ErrorKind::IdentifierCharInvalid, Token, TokenKind, Tokenizer,
+            self,
+            ErrorKind::{IdentifierCharInvalid, ParamsInvalid},
+            Token, TokenKind, Tokenizer,
         },
     };
 
@@ -598,6 +686,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fuzz_params() {
+        let file_contents = String::from(indoc! {"
+            Foo_Test
+            └── when amount (uint128 amount)
+               └── it should revert
+        "});
+
+        assert_eq!(
+            tokenize(&file_contents).unwrap(),
+            vec![
+                t(TokenKind::Word, "Foo_Test", s(p(0, 1, 1), p(7, 1, 8))),
+                t(TokenKind::Corner, "└", s(p(9, 2, 1), p(9, 2, 1))),
+                t(TokenKind::When, "when", s(p(19, 2, 5), p(22, 2, 8))),
+                t(TokenKind::Word, "amount", s(p(24, 2, 10), p(29, 2, 15))),
+                t(TokenKind::Word, "(uint128", s(p(31, 2, 17), p(38, 2, 24))),
+                t(TokenKind::Word, "amount)", s(p(40, 2, 26), p(46, 2, 32))),
+                t(TokenKind::Corner, "└", s(p(51, 3, 4), p(51, 3, 4))),
+                t(TokenKind::It, "it", s(p(61, 3, 8), p(62, 3, 9))),
+                t(TokenKind::Word, "should", s(p(64, 3, 11), p(69, 3, 16))),
+                t(TokenKind::Word, "revert", s(p(71, 3, 18), p(76, 3, 23))),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_fuzz_params() {
+        // The parameter list of each title starts at the same position.
+        let cases = [
+            ("when a (b) c", p(25, 2, 16)),
+            ("when a (b) c )", p(27, 2, 18)),
+            ("when a (uint b", p(27, 2, 18)),
+            ("when a (uint b) (uint c)", p(37, 2, 28)),
+            ("when a (b)", p(23, 2, 14)),
+            ("when a ()", p(22, 2, 13)),
+            ("when a (uint[x] b)", p(31, 2, 22)),
+            ("when a (uint 1b)", p(29, 2, 20)),
+        ];
+
+        for (title, end) in cases {
+            let input = format!("foo\n└── {title}\n    └── it should revert");
+            assert_eq!(
+                tokenize(&input).unwrap_err(),
+                e(ParamsInvalid, s(p(21, 2, 12), end)),
+                "{title}"
+            );
+        }
+
+        let valid = [
+            "when a (uint128 amount, address to)",
+            "when a (bytes memory data)",
+            "when a (IERC20.Info[2][] infos)",
+        ];
+        for title in valid {
+            let input = format!("foo\n└── {title}\n    └── it should revert");
+            assert!(tokenize(&input).is_ok(), "{title}");
+        }
+    }
+
     #[test]
     fn multiple_children() {
         let file_contents = String::from(indoc! {"